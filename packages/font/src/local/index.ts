@@ -22,6 +22,10 @@ type LocalFont<T extends CssVariable | undefined = undefined> = {
   preload?: boolean
   variable?: T
   declarations?: Array<{ prop: string; value: string }>
+  subset?: {
+    text?: string
+    unicodeRanges?: string[]
+  }
 }
 
 export default function localFont<
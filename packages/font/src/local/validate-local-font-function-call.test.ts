@@ -51,4 +51,15 @@ describe('validateLocalFontFunctionCall', () => {
       })
     ).toThrowErrorMatchingInlineSnapshot(`"Unexpected empty \`src\` array."`)
   })
+
+  test('Subset outside of Turbopack', async () => {
+    expect(() =>
+      validateLocalFontFunctionCall('', {
+        src: './font-file.woff2',
+        subset: { text: 'Hello' },
+      })
+    ).toThrowErrorMatchingInlineSnapshot(
+      `"The \`subset\` option is only supported by Turbopack."`
+    )
+  })
 })
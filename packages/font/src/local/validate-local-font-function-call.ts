@@ -48,6 +48,7 @@ export function validateLocalFontFunctionCall(
     variable,
     adjustFontFallback,
     declarations,
+    subset,
   } = fontData || ({} as any)
 
   if (!allowedDisplayValues.includes(display)) {
@@ -62,6 +63,10 @@ export function validateLocalFontFunctionCall(
     nextFontError('Missing required `src` property')
   }
 
+  if (subset !== undefined) {
+    nextFontError('The `subset` option is only supported by Turbopack.')
+  }
+
   if (!Array.isArray(src)) {
     src = [{ path: src, weight, style }]
   } else {
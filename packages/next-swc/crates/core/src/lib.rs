@@ -66,6 +66,7 @@ pub mod page_config;
 pub mod react_server_components;
 pub mod server_actions;
 pub mod shake_exports;
+pub mod styled_jsx_extract;
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -114,14 +115,13 @@ pub struct TransformOptions {
     pub react_remove_properties: Option<react_remove_properties::Config>,
 
     #[serde(default)]
-    #[cfg(not(target_arch = "wasm32"))]
     pub relay: Option<turbopack_binding::swc::custom_transform::relay::Config>,
 
-    #[allow(unused)]
+    /// The directory relay artifact paths are resolved against. Defaults to
+    /// the current working directory, which is not available in wasm
+    /// builds, where it is required when `relay` is set.
     #[serde(default)]
-    #[cfg(target_arch = "wasm32")]
-    /// Accept any value
-    pub relay: Option<serde_json::Value>,
+    pub relay_root_dir: Option<PathBuf>,
 
     #[serde(default)]
     pub shake_exports: Option<shake_exports::Config>,
@@ -162,22 +162,18 @@ pub fn custom_before_pass<'a, C: Comments + 'a>(
 where
     C: Clone,
 {
-    #[cfg(target_arch = "wasm32")]
-    let relay_plugin = noop();
-
-    #[cfg(not(target_arch = "wasm32"))]
-    let relay_plugin = {
-        if let Some(config) = &opts.relay {
-            Either::Left(turbopack_binding::swc::custom_transform::relay::relay(
+    let relay_plugin = match &opts.relay {
+        Some(config) => match relay_root_dir(opts) {
+            Some(root_dir) => Either::Left(turbopack_binding::swc::custom_transform::relay::relay(
                 config,
                 file.name.clone(),
-                std::env::current_dir().unwrap(),
+                root_dir,
                 opts.pages_dir.clone(),
                 None,
-            ))
-        } else {
-            Either::Right(noop())
-        }
+            )),
+            None => Either::Right(noop()),
+        },
+        None => Either::Right(noop()),
     };
 
     let modularize_imports_config = match &opts.modularize_imports {
@@ -322,6 +318,30 @@ where
     )
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn relay_root_dir(opts: &TransformOptions) -> Option<PathBuf> {
+    Some(
+        opts.relay_root_dir
+            .clone()
+            .unwrap_or_else(|| std::env::current_dir().unwrap()),
+    )
+}
+
+#[cfg(target_arch = "wasm32")]
+fn relay_root_dir(opts: &TransformOptions) -> Option<PathBuf> {
+    // There is no process CWD in the browser to resolve the artifact paths
+    // against, so guessing one would emit wrong `require` paths.
+    if opts.relay_root_dir.is_none() {
+        turbopack_binding::swc::core::common::errors::HANDLER.with(|handler| {
+            handler.err(
+                "`relayRootDir` is required to use the relay transform in the wasm build of \
+                 next-swc",
+            )
+        });
+    }
+    opts.relay_root_dir.clone()
+}
+
 impl TransformOptions {
     pub fn patch(mut self, fm: &SourceFile) -> Self {
         self.swc.swcrc = false;
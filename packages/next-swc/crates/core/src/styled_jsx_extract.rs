@@ -0,0 +1,181 @@
+// This transform runs after the styled-jsx transform and moves static
+// `<style jsx>` blocks out of the JavaScript output:
+// - `<_JSXStyle id="...">{"..."}</_JSXStyle>` elements without a `dynamic`
+//   attribute are removed and their (already scoped) CSS is collected
+// - The `styled-jsx/style` import is dropped once no runtime styles remain
+//
+// The `jsx-<hash>` class names added by styled-jsx are left untouched, so the
+// collected CSS still matches the rendered markup.
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::Serialize;
+use turbopack_binding::swc::core::ecma::{
+    ast::*,
+    atoms::JsWord,
+    visit::{Fold, FoldWith},
+};
+
+/// A static styled-jsx block that was removed from the module.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExtractedStyle {
+    /// The styled-jsx hash, as used in the `jsx-<hash>` class names.
+    pub id: String,
+    /// The scoped CSS of the block.
+    pub css: String,
+}
+
+pub type ExtractedStyles = Rc<RefCell<Vec<ExtractedStyle>>>;
+
+/// Concatenates the extracted styles into a single stylesheet.
+pub fn extracted_css(styles: &[ExtractedStyle]) -> String {
+    styles
+        .iter()
+        .map(|style| style.css.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn styled_jsx_extract(extracted: ExtractedStyles) -> impl Fold {
+    StyledJsxExtract {
+        extracted,
+        style_ident: None,
+        has_runtime_styles: false,
+    }
+}
+
+struct StyledJsxExtract {
+    extracted: ExtractedStyles,
+    style_ident: Option<JsWord>,
+    has_runtime_styles: bool,
+}
+
+const STYLED_JSX_STYLE_MODULE: &str = "styled-jsx/style";
+
+fn string_value(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(Lit::Str(s)) => Some(s.value.to_string()),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() && tpl.quasis.len() == 1 => tpl.quasis[0]
+            .cooked
+            .as_ref()
+            .map(|cooked| cooked.to_string()),
+        _ => None,
+    }
+}
+
+impl StyledJsxExtract {
+    fn is_style_element(&self, el: &JSXElement) -> bool {
+        match (&el.opening.name, &self.style_ident) {
+            (JSXElementName::Ident(name), Some(style_ident)) => &name.sym == style_ident,
+            _ => false,
+        }
+    }
+
+    /// Returns the styled-jsx block if the element only contains static CSS.
+    fn static_style(&self, el: &JSXElement) -> Option<ExtractedStyle> {
+        let mut id = None;
+        for attr in &el.opening.attrs {
+            let JSXAttrOrSpread::JSXAttr(attr) = attr else {
+                return None;
+            };
+            let JSXAttrName::Ident(name) = &attr.name else {
+                return None;
+            };
+            match &*name.sym {
+                "id" => {
+                    id = match &attr.value {
+                        Some(JSXAttrValue::Lit(Lit::Str(s))) => Some(s.value.to_string()),
+                        Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                            expr: JSXExpr::Expr(expr),
+                            ..
+                        })) => string_value(expr),
+                        _ => None,
+                    };
+                }
+                // Styles with interpolations need the runtime to inject them.
+                "dynamic" => return None,
+                _ => {}
+            }
+        }
+
+        let mut css = None;
+        for child in &el.children {
+            match child {
+                JSXElementChild::JSXText(text) if text.value.trim().is_empty() => {}
+                JSXElementChild::JSXExprContainer(JSXExprContainer {
+                    expr: JSXExpr::Expr(expr),
+                    ..
+                }) if css.is_none() => {
+                    css = Some(string_value(expr)?);
+                }
+                _ => return None,
+            }
+        }
+
+        Some(ExtractedStyle { id: id?, css: css? })
+    }
+}
+
+impl Fold for StyledJsxExtract {
+    fn fold_module(&mut self, module: Module) -> Module {
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)) = item {
+                if &*import_decl.src.value != STYLED_JSX_STYLE_MODULE {
+                    continue;
+                }
+                for specifier in &import_decl.specifiers {
+                    if let ImportSpecifier::Default(default) = specifier {
+                        self.style_ident = Some(default.local.sym.clone());
+                    }
+                }
+            }
+        }
+
+        if self.style_ident.is_none() {
+            return module;
+        }
+
+        let mut module = module.fold_children_with(self);
+
+        if !self.has_runtime_styles && !self.extracted.borrow().is_empty() {
+            module.body.retain(|item| {
+                !matches!(
+                    item,
+                    ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl))
+                        if &*import_decl.src.value == STYLED_JSX_STYLE_MODULE
+                )
+            });
+        }
+
+        module
+    }
+
+    fn fold_jsx_element_childs(&mut self, children: Vec<JSXElementChild>) -> Vec<JSXElementChild> {
+        let mut new_children = Vec::with_capacity(children.len());
+
+        for child in children {
+            if let JSXElementChild::JSXElement(el) = &child {
+                if self.is_style_element(el) {
+                    if let Some(style) = self.static_style(el) {
+                        let mut extracted = self.extracted.borrow_mut();
+                        // The same component can render the same block more than once.
+                        if !extracted.iter().any(|s| s.id == style.id) {
+                            extracted.push(style);
+                        }
+                        continue;
+                    }
+                }
+            }
+            new_children.push(child.fold_with(self));
+        }
+
+        new_children
+    }
+
+    fn fold_jsx_element(&mut self, el: JSXElement) -> JSXElement {
+        if self.is_style_element(&el) {
+            self.has_runtime_styles = true;
+        }
+        el.fold_children_with(self)
+    }
+}
@@ -3,6 +3,7 @@ use std::{env::current_dir, path::PathBuf};
 use next_swc::{
     amp_attributes::amp_attributes,
     cjs_optimizer::cjs_optimizer,
+    custom_before_pass,
     named_import_transform::named_import_transform,
     next_dynamic::next_dynamic,
     next_ssg::next_ssg,
@@ -14,6 +15,8 @@ use next_swc::{
         server_actions, {self},
     },
     shake_exports::{shake_exports, Config as ShakeExportsConfig},
+    styled_jsx_extract::{extracted_css, styled_jsx_extract, ExtractedStyles},
+    TransformOptions,
 };
 use next_transform_font::{next_font_loaders, Config as FontLoaderConfig};
 use serde::de::DeserializeOwned;
@@ -30,7 +33,7 @@ use turbopack_binding::swc::{
         },
     },
     custom_transform::relay::{relay, RelayLanguageConfig},
-    testing::fixture,
+    testing::{fixture, NormalizedOutput},
 };
 
 fn syntax() -> Syntax {
@@ -230,6 +233,60 @@ fn relay_no_artifact_dir_fixture(input: PathBuf) {
     );
 }
 
+#[fixture("tests/fixture/styled-jsx-extract/**/input.js")]
+fn styled_jsx_extract_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    let output_css = input.parent().unwrap().join("output.css");
+    let extracted_styles: ExtractedStyles = Default::default();
+    test_fixture(
+        syntax(),
+        &|_tr| styled_jsx_extract(extracted_styles.clone()),
+        &input,
+        &output,
+        Default::default(),
+    );
+    NormalizedOutput::from(extracted_css(&extracted_styles.borrow()))
+        .compare_to_file(output_css)
+        .unwrap();
+}
+
+#[fixture("tests/fixture/relay-root-dir/**/input.ts*")]
+fn relay_root_dir_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    let options: TransformOptions = json(
+        r#"{
+            "disableNextSsg": true,
+            "disablePageConfig": true,
+            "pagesDir": "/virtual-project/src/pages",
+            "relay": {
+                "artifactDirectory": "__generated__",
+                "language": "typescript"
+            },
+            "relayRootDir": "/virtual-project"
+        }"#,
+    );
+    test_fixture(
+        syntax(),
+        &|tr| {
+            let file = tr.cm.new_source_file(
+                FileName::Real(PathBuf::from("/virtual-project/src/pages/input.tsx")),
+                String::new(),
+            );
+            custom_before_pass(
+                tr.cm.clone(),
+                file,
+                &options,
+                SingleThreadedComments::default(),
+                Default::default(),
+                Mark::new(),
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
 #[fixture("tests/fixture/shake-exports/most-usecases/input.js")]
 fn shake_exports_fixture(input: PathBuf) {
     let output = input.parent().unwrap().join("output.js");
@@ -0,0 +1,13 @@
+const variableQuery = graphql`
+  query InputVariableQuery {
+    hello
+  }
+`
+
+function SomeQueryComponent() {
+  useLazyLoadQuery(graphql`
+    query InputInHookQuery {
+      hello
+    }
+  `)
+}
@@ -0,0 +1,4 @@
+const variableQuery = require("/virtual-project/__generated__/InputVariableQuery.graphql.ts");
+function SomeQueryComponent() {
+    useLazyLoadQuery(require("/virtual-project/__generated__/InputInHookQuery.graphql.ts"));
+}
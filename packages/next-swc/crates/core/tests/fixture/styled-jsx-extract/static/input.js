@@ -0,0 +1,4 @@
+import _JSXStyle from "styled-jsx/style";
+export default function Home() {
+    return <div className={"jsx-2b6d9a1f"}><p className={"jsx-2b6d9a1f"}>Hello</p><_JSXStyle id={"2b6d9a1f"}>{"p.jsx-2b6d9a1f{color:red}"}</_JSXStyle><_JSXStyle id={"2b6d9a1f"}>{"p.jsx-2b6d9a1f{color:red}"}</_JSXStyle></div>;
+}
@@ -0,0 +1,3 @@
+export default function Home() {
+    return <div className={"jsx-2b6d9a1f"}><p className={"jsx-2b6d9a1f"}>Hello</p></div>;
+}
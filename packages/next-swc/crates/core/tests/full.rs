@@ -71,6 +71,7 @@ fn test(input: &Path, minify: bool) {
                 remove_console: None,
                 react_remove_properties: None,
                 relay: None,
+                relay_root_dir: None,
                 shake_exports: None,
                 emotion: Some(assert_json("{}")),
                 modularize_imports: None,
@@ -8,7 +8,7 @@ use napi::bindgen_prelude::*;
 use next_build::{
     build as turbo_next_build,
     build_options::{BuildContext, DefineEnv},
-    BuildOptions as NextBuildOptions,
+    prefetch_google_fonts as turbo_prefetch_google_fonts, BuildOptions as NextBuildOptions,
 };
 use next_core::next_config::{Rewrite, Rewrites, RouteHas};
 
@@ -207,6 +207,23 @@ pub async fn next_build(ctx: NextBuildContext) -> napi::Result<()> {
     turbo_next_build(ctx.try_into()?).await.convert_err()
 }
 
+#[napi(object, object_to_js = false)]
+#[derive(Debug)]
+pub struct NextPrefetchGoogleFontsContext {
+    /// The root directory of the workspace.
+    pub root: Option<String>,
+
+    /// The project's directory.
+    pub dir: Option<String>,
+}
+
+#[napi]
+pub async fn next_prefetch_google_fonts(ctx: NextPrefetchGoogleFontsContext) -> napi::Result<()> {
+    turbo_prefetch_google_fonts(ctx.dir.map(PathBuf::from), ctx.root.map(PathBuf::from))
+        .await
+        .convert_err()
+}
+
 #[napi]
 pub async fn experimental_turbo(_unused: Buffer) -> napi::Result<()> {
     unimplemented!("__experimental_turbo is not yet implemented");
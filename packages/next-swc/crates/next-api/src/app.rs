@@ -7,8 +7,9 @@ use next_core::{
     get_edge_resolve_options_context,
     mode::NextMode,
     next_app::{
-        get_app_client_references_chunks, get_app_client_shared_chunks, get_app_page_entry,
-        get_app_route_entry, metadata::route::get_app_metadata_route_entry, AppEntry, AppPage,
+        create_server_actions_manifest, get_app_client_references_chunks,
+        get_app_client_shared_chunks, get_app_page_entry, get_app_route_entry,
+        metadata::route::get_app_metadata_route_entry, AppEntry, AppPage,
     },
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
@@ -18,7 +19,11 @@ use next_core::{
         ClientReferenceGraph, ClientReferenceType, NextEcmascriptClientReferenceTransition,
     },
     next_dynamic::{NextDynamicEntries, NextDynamicTransition},
-    next_edge::route_regex::get_named_middleware_regex,
+    next_edge::{
+        asset_bindings::get_edge_asset_bindings,
+        entry::{app_edge_function_name, edge_entry_global_var_name},
+        route_regex::get_named_middleware_regex,
+    },
     next_manifests::{
         AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest,
         EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2, PagesManifest, Regions,
@@ -55,7 +60,6 @@ use turbopack_binding::{
 use crate::{
     project::Project,
     route::{Endpoint, Route, Routes, WrittenEndpoint},
-    server_actions::create_server_actions_manifest,
     server_paths::all_server_paths,
 };
 
@@ -571,7 +575,7 @@ impl AppEndpoint {
             let client_references_chunks = get_app_client_references_chunks(
                 client_reference_types,
                 this.app_project.project().app_client_chunking_context(),
-                this.app_project.project().app_ssr_chunking_context(),
+                Vc::upcast(this.app_project.project().app_ssr_chunking_context()),
             );
             let client_references_chunks_ref = client_references_chunks.await?;
 
@@ -644,6 +648,7 @@ impl AppEndpoint {
                 client_references_chunks,
                 this.app_project.project().app_client_chunking_context(),
                 Vc::upcast(this.app_project.project().app_ssr_chunking_context()),
+                NextRuntime::NodeJs,
                 this.app_project
                     .project()
                     .next_config()
@@ -718,6 +723,9 @@ impl AppEndpoint {
                 );
                 server_assets.extend(files.await?.iter().copied());
 
+                let bindings = get_edge_asset_bindings(files, node_root).await?;
+                server_assets.extend(bindings.output_assets);
+
                 let node_root_value = node_root.await?;
                 let files_paths_from_root = files
                     .await?
@@ -759,7 +767,7 @@ impl AppEndpoint {
                 };
                 let edge_function_definition = EdgeFunctionDefinition {
                     files: files_paths_from_root,
-                    name: app_entry.pathname.to_string(),
+                    name: app_edge_function_name(&app_entry.original_name),
                     page: app_entry.original_name.clone(),
                     regions: app_entry
                         .config
@@ -768,7 +776,8 @@ impl AppEndpoint {
                         .clone()
                         .map(Regions::Multiple),
                     matchers: vec![matchers],
-                    ..Default::default()
+                    wasm: Some(bindings.wasm),
+                    assets: Some(bindings.assets),
                 };
                 let middleware_manifest_v2 = MiddlewaresManifestV2 {
                     sorted_middleware: vec![app_entry.original_name.clone()],
@@ -803,6 +812,9 @@ impl AppEndpoint {
 
                 AppEndpointOutput::Edge {
                     files,
+                    global_var_name: edge_entry_global_var_name(&app_edge_function_name(
+                        &app_entry.original_name,
+                    )),
                     server_assets: Vc::cell(server_assets),
                     client_assets: Vc::cell(client_assets),
                 }
@@ -932,7 +944,11 @@ impl Endpoint for AppEndpoint {
                     .to_string(),
                 server_paths,
             },
-            AppEndpointOutput::Edge { files, .. } => WrittenEndpoint::Edge {
+            AppEndpointOutput::Edge {
+                files,
+                ref global_var_name,
+                ..
+            } => WrittenEndpoint::Edge {
                 files: files
                     .await?
                     .iter()
@@ -944,7 +960,7 @@ impl Endpoint for AppEndpoint {
                     })
                     .try_join()
                     .await?,
-                global_var_name: "TODO".to_string(),
+                global_var_name: global_var_name.clone(),
                 server_paths,
             },
         };
@@ -979,6 +995,8 @@ enum AppEndpointOutput {
     },
     Edge {
         files: Vc<OutputAssets>,
+        /// The global the edge sandbox reads the entry from.
+        global_var_name: String,
         server_assets: Vc<OutputAssets>,
         client_assets: Vc<OutputAssets>,
     },
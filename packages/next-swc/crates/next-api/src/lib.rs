@@ -8,7 +8,6 @@ mod middleware;
 mod pages;
 pub mod project;
 pub mod route;
-mod server_actions;
 pub mod server_paths;
 mod versioned_content_map;
 
@@ -2,7 +2,10 @@ use anyhow::{bail, Context, Result};
 use next_core::{
     middleware::get_middleware_module,
     mode::NextMode,
-    next_edge::entry::wrap_edge_entry,
+    next_edge::{
+        asset_bindings::get_edge_asset_bindings,
+        entry::{edge_entry_global_var_name, wrap_edge_entry},
+    },
     next_manifests::{EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2},
     next_server::{get_server_runtime_entries, ServerContextType},
     util::parse_config_from_source,
@@ -100,10 +103,13 @@ impl MiddlewareEndpoint {
 
         let config = parse_config_from_source(this.userland_module);
 
-        let mut output_assets = self.edge_files().await?.clone_value();
+        let edge_files = self.edge_files();
+        let mut output_assets = edge_files.await?.clone_value();
 
         let node_root = this.project.node_root();
 
+        let bindings = get_edge_asset_bindings(edge_files, node_root).await?;
+
         let files_paths_from_root = {
             let node_root = &node_root.await?;
             output_assets
@@ -122,7 +128,10 @@ impl MiddlewareEndpoint {
             matchers
                 .iter()
                 .map(|matcher| MiddlewareMatcher {
-                    original_source: matcher.to_string(),
+                    original_source: matcher.source.clone(),
+                    locale: matcher.locale,
+                    has: (!matcher.has.is_empty()).then(|| matcher.has.clone()),
+                    missing: (!matcher.missing.is_empty()).then(|| matcher.missing.clone()),
                     ..Default::default()
                 })
                 .collect()
@@ -140,7 +149,8 @@ impl MiddlewareEndpoint {
             page: "/".to_string(),
             regions: None,
             matchers,
-            ..Default::default()
+            wasm: Some(bindings.wasm),
+            assets: Some(bindings.assets),
         };
         let middleware_manifest_v2 = MiddlewaresManifestV2 {
             sorted_middleware: Default::default(),
@@ -159,6 +169,7 @@ impl MiddlewareEndpoint {
             ),
         ));
         output_assets.push(middleware_manifest_v2);
+        output_assets.extend(bindings.output_assets);
 
         Ok(Vc::cell(output_assets))
     }
@@ -196,7 +207,7 @@ impl Endpoint for MiddlewareEndpoint {
 
         Ok(WrittenEndpoint::Edge {
             files,
-            global_var_name: "TODO".to_string(),
+            global_var_name: edge_entry_global_var_name("middleware"),
             server_paths,
         }
         .cell())
@@ -9,7 +9,11 @@ use next_core::{
         get_client_runtime_entries, ClientContextType, RuntimeEntries,
     },
     next_dynamic::NextDynamicTransition,
-    next_edge::route_regex::get_named_middleware_regex,
+    next_edge::{
+        asset_bindings::get_edge_asset_bindings,
+        entry::{edge_entry_global_var_name, pages_edge_function_name},
+        route_regex::get_named_middleware_regex,
+    },
     next_manifests::{
         BuildManifest, EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2,
         PagesManifest,
@@ -816,6 +820,9 @@ impl PageEndpoint {
                 }
                 server_assets.extend(files_value.iter().copied());
 
+                let bindings = get_edge_asset_bindings(files, node_root).await?;
+                server_assets.extend(bindings.output_assets);
+
                 let node_root_value = node_root.await?;
                 let files_paths_from_root: Vec<String> = files_value
                     .iter()
@@ -840,11 +847,12 @@ impl PageEndpoint {
                 let original_name = this.original_name.await?;
                 let edge_function_definition = EdgeFunctionDefinition {
                     files: files_paths_from_root,
-                    name: pathname.to_string(),
+                    name: pages_edge_function_name(&pathname),
                     page: original_name.to_string(),
                     regions: None,
                     matchers: vec![matchers],
-                    ..Default::default()
+                    wasm: Some(bindings.wasm),
+                    assets: Some(bindings.assets),
                 };
                 let middleware_manifest_v2 = MiddlewaresManifestV2 {
                     sorted_middleware: vec![pathname.to_string()],
@@ -926,7 +934,9 @@ impl Endpoint for PageEndpoint {
                     })
                     .try_join()
                     .await?,
-                global_var_name: "TODO".to_string(),
+                global_var_name: edge_entry_global_var_name(&pages_edge_function_name(
+                    &this.pathname.await?,
+                )),
                 server_paths,
             },
         };
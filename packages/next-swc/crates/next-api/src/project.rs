@@ -9,7 +9,9 @@ use next_core::{
     get_edge_resolve_options_context,
     middleware::middleware_files,
     mode::NextMode,
-    next_client::{get_client_chunking_context, get_client_compile_time_info},
+    next_client::{
+        get_browserslist_query, get_client_chunking_context, get_client_compile_time_info,
+    },
     next_config::{JsConfig, NextConfig},
     next_server::{
         get_server_chunking_context, get_server_compile_time_info,
@@ -235,9 +237,6 @@ impl ProjectContainer {
             dist_dir,
             env: Vc::upcast(env),
             define_env,
-            browserslist_query: "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
-                                 versions, last 1 Edge versions"
-                .to_string(),
             mode: NextMode::Development,
             versioned_content_map: this.versioned_content_map,
         }
@@ -289,8 +288,6 @@ pub struct Project {
     /// time.
     define_env: Vc<ProjectDefineEnv>,
 
-    browserslist_query: String,
-
     mode: NextMode,
 
     versioned_content_map: Vc<VersionedContentMap>,
@@ -460,11 +457,12 @@ impl Project {
     }
 
     #[turbo_tasks::function]
-    pub(super) async fn client_compile_time_info(&self) -> Result<Vc<CompileTimeInfo>> {
+    pub(super) async fn client_compile_time_info(self: Vc<Self>) -> Result<Vc<CompileTimeInfo>> {
+        let this = self.await?;
         Ok(get_client_compile_time_info(
-            self.mode,
-            self.browserslist_query.clone(),
-            self.define_env.client(),
+            this.mode,
+            get_browserslist_query(self.project_path(), self.env(), this.mode),
+            this.define_env.client(),
         ))
     }
 
@@ -529,6 +527,7 @@ impl Project {
             self.project_path(),
             self.node_root(),
             self.client_relative_path(),
+            self.next_config().computed_asset_prefix(),
             self.edge_compile_time_info().environment(),
         )
     }
@@ -0,0 +1,607 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    ops::AddAssign,
+};
+
+use anyhow::Result;
+use flate2::{write::GzEncoder, Compression};
+use next_core::next_manifests::{
+    AppBuildManifest, AppPathsManifest, BuildManifest, MiddlewaresManifestV2, PagesManifest,
+};
+use serde::Serialize;
+use sourcemap::{decode_slice, DecodedMap, SourceMap};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::{Asset, AssetContent},
+        output::OutputAsset,
+    },
+};
+
+use crate::next_build::all_assets_from_entries;
+
+/// The extensions of the output assets which are reported as chunks.
+const CHUNK_EXTENSIONS: &[&str] = &["js", "mjs", "css"];
+
+/// The brotli quality used to measure sizes, which is the one usually used to
+/// precompress static assets.
+const BROTLI_QUALITY: u32 = 11;
+
+/// The `build-stats.json` report written when `full_stats` is set.
+///
+/// Everything is sorted by path or name, so that reports of two builds can be
+/// diffed.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BuildStats {
+    /// All JavaScript and CSS chunks of the build.
+    pub chunks: Vec<ChunkStats>,
+    /// The JavaScript loaded by pages and app entries when they are first
+    /// visited, keyed by page.
+    pub pages: BTreeMap<String, PageStats>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChunkStats {
+    /// The path of the chunk in the output directory, `static/...` for client
+    /// chunks and `server/...` for server chunks.
+    pub path: String,
+    #[serde(flatten)]
+    pub sizes: AssetSizes,
+    /// The modules of the chunk. Module sizes are the bytes of generated code
+    /// the source map of the chunk attributes to them.
+    pub modules: Vec<ModuleStats>,
+    /// The pages, app entries and middleware referencing the chunk.
+    pub entries: BTreeSet<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModuleStats {
+    pub path: String,
+    pub size: usize,
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PageStats {
+    /// The total size of the JavaScript files loaded by the page.
+    pub first_load_js: AssetSizes,
+    /// The JavaScript files loaded by the page.
+    pub files: Vec<String>,
+}
+
+#[derive(Serialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AssetSizes {
+    pub size: usize,
+    pub gzip_size: usize,
+    pub brotli_size: usize,
+}
+
+impl AssetSizes {
+    fn of(content: &[u8]) -> Result<Self> {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(content)?;
+        let gzip_size = gzip.finish()?.len();
+
+        let mut brotli = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut brotli, 4096, BROTLI_QUALITY, 22);
+            writer.write_all(content)?;
+        }
+
+        Ok(AssetSizes {
+            size: content.len(),
+            gzip_size,
+            brotli_size: brotli.len(),
+        })
+    }
+}
+
+impl AddAssign for AssetSizes {
+    fn add_assign(&mut self, other: Self) {
+        self.size += other.size;
+        self.gzip_size += other.gzip_size;
+        self.brotli_size += other.brotli_size;
+    }
+}
+
+/// The manifests the entries and pages of the build report are read from.
+pub(crate) struct BuildStatsManifests<'a> {
+    pub build_manifest: &'a BuildManifest,
+    pub app_build_manifest: &'a AppBuildManifest,
+    pub pages_manifest: &'a PagesManifest,
+    pub app_paths_manifest: &'a AppPathsManifest,
+    pub middlewares_manifest: &'a MiddlewaresManifestV2,
+}
+
+/// Computes the build report for the given chunks and their references.
+pub(crate) async fn compute_build_stats(
+    all_chunks: Vec<Vc<Box<dyn OutputAsset>>>,
+    node_root: &FileSystemPath,
+    client_relative_path: &FileSystemPath,
+    manifests: BuildStatsManifests<'_>,
+) -> Result<BuildStats> {
+    let get_output_path = |path: &FileSystemPath| {
+        client_relative_path
+            .get_path_to(path)
+            .or_else(|| node_root.get_path_to(path))
+            .map(|path| path.to_string())
+    };
+
+    let mut chunks = BTreeMap::new();
+    for &asset in all_assets_from_entries(Vc::cell(all_chunks)).await?.iter() {
+        let path = asset.ident().path().await?;
+        if !matches!(path.extension_ref(), Some(ext) if CHUNK_EXTENSIONS.contains(&ext)) {
+            continue;
+        }
+        let Some(output_path) = get_output_path(&path) else {
+            continue;
+        };
+        let AssetContent::File(file) = &*asset.content().await? else {
+            continue;
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            continue;
+        };
+        let content = file.content().to_str()?;
+
+        chunks.insert(
+            output_path.clone(),
+            (
+                asset,
+                ChunkStats {
+                    path: output_path,
+                    sizes: AssetSizes::of(content.as_bytes())?,
+                    modules: get_chunk_modules(asset, &content).await?,
+                    entries: BTreeSet::new(),
+                },
+            ),
+        );
+    }
+
+    // Entries are attributed the chunks listed in the manifests, and the chunks
+    // those reference in turn.
+    for (entry, files) in get_entry_files(&manifests) {
+        let assets = files
+            .iter()
+            .filter_map(|file| chunks.get(file).map(|(asset, _)| *asset))
+            .collect();
+        for &asset in all_assets_from_entries(Vc::cell(assets)).await?.iter() {
+            let Some(output_path) = get_output_path(&*asset.ident().path().await?) else {
+                continue;
+            };
+            if let Some((_, chunk_stats)) = chunks.get_mut(&output_path) {
+                chunk_stats.entries.insert(entry.clone());
+            }
+        }
+    }
+
+    let mut pages = BTreeMap::new();
+    for (page, files) in
+        get_first_load_js_files(manifests.build_manifest, manifests.app_build_manifest)
+    {
+        let mut page_stats = PageStats::default();
+        for file in files {
+            if let Some((_, chunk_stats)) = chunks.get(&file) {
+                page_stats.first_load_js += chunk_stats.sizes;
+                page_stats.files.push(file);
+            }
+        }
+        pages.insert(page, page_stats);
+    }
+
+    Ok(BuildStats {
+        chunks: chunks
+            .into_values()
+            .map(|(_, chunk_stats)| chunk_stats)
+            .collect(),
+        pages,
+    })
+}
+
+/// Returns the output files listed for each page, app entry and middleware.
+fn get_entry_files(manifests: &BuildStatsManifests<'_>) -> BTreeMap<String, BTreeSet<String>> {
+    let mut entry_files: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    let client_files = manifests
+        .build_manifest
+        .pages
+        .iter()
+        .chain(manifests.app_build_manifest.pages.iter());
+    for (entry, files) in client_files {
+        entry_files
+            .entry(entry.clone())
+            .or_default()
+            .extend(files.iter().cloned());
+    }
+
+    // Server files are relative to the `server` directory.
+    let server_files = manifests
+        .pages_manifest
+        .pages
+        .iter()
+        .chain(
+            manifests
+                .app_paths_manifest
+                .node_server_app_paths
+                .pages
+                .iter(),
+        )
+        .chain(
+            manifests
+                .app_paths_manifest
+                .edge_server_app_paths
+                .pages
+                .iter(),
+        );
+    for (entry, file) in server_files {
+        entry_files
+            .entry(entry.clone())
+            .or_default()
+            .insert(format!("server/{file}"));
+    }
+
+    for definition in manifests.middlewares_manifest.middleware.values() {
+        entry_files
+            .entry(definition.name.clone())
+            .or_default()
+            .extend(definition.files.iter().cloned());
+    }
+
+    // Edge functions are the pages and app entries using the edge runtime, so
+    // their files are attributed to the page they render.
+    for definition in manifests.middlewares_manifest.functions.values() {
+        entry_files
+            .entry(definition.page.clone())
+            .or_default()
+            .extend(definition.files.iter().cloned());
+    }
+
+    entry_files
+}
+
+/// Returns the JavaScript files loaded when first visiting each page.
+fn get_first_load_js_files(
+    build_manifest: &BuildManifest,
+    app_build_manifest: &AppBuildManifest,
+) -> BTreeMap<String, Vec<String>> {
+    let is_js = |file: &&String| file.ends_with(".js");
+
+    let app_files = build_manifest.pages.get("/_app");
+    let mut first_load_js_files = BTreeMap::new();
+
+    for (page, files) in &build_manifest.pages {
+        if page == "/_app" || page == "/_document" {
+            continue;
+        }
+        // Pages also load the polyfills and the files of `_app`.
+        let mut files: Vec<String> = build_manifest
+            .polyfill_files
+            .iter()
+            .chain(app_files.into_iter().flatten())
+            .chain(files)
+            .filter(is_js)
+            .cloned()
+            .collect();
+        files.sort();
+        files.dedup();
+        first_load_js_files.insert(page.clone(), files);
+    }
+
+    for (page, files) in &app_build_manifest.pages {
+        let mut files: Vec<String> = files.iter().filter(is_js).cloned().collect();
+        files.sort();
+        files.dedup();
+        first_load_js_files.insert(page.clone(), files);
+    }
+
+    first_load_js_files
+}
+
+/// Returns the modules of a chunk, from its source map.
+async fn get_chunk_modules(
+    chunk: Vc<Box<dyn OutputAsset>>,
+    content: &str,
+) -> Result<Vec<ModuleStats>> {
+    for &reference in chunk.references().await?.iter() {
+        if reference.ident().path().await?.extension_ref() != Some("map") {
+            continue;
+        }
+        let AssetContent::File(file) = &*reference.content().await? else {
+            continue;
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            continue;
+        };
+        let source_map = match decode_slice(file.content().to_str()?.as_bytes()) {
+            Ok(DecodedMap::Regular(source_map)) => source_map,
+            Ok(DecodedMap::Index(source_map_index)) => source_map_index.flatten()?,
+            // Module sizes are informational, so chunks without a usable source map
+            // just don't list their modules.
+            _ => continue,
+        };
+        return Ok(get_module_sizes(&source_map, content));
+    }
+
+    Ok(vec![])
+}
+
+/// Attributes each mapped segment of the generated code to the source it
+/// originates from. Unmapped code, like the runtime glue of a chunk, is not
+/// attributed to any module.
+fn get_module_sizes(source_map: &SourceMap, content: &str) -> Vec<ModuleStats> {
+    let line_lengths: Vec<usize> = content.split('\n').map(str::len).collect();
+
+    let mut tokens: Vec<_> = source_map
+        .tokens()
+        .map(|token| {
+            (
+                token.get_dst_line() as usize,
+                token.get_dst_col() as usize,
+                token.get_source(),
+            )
+        })
+        .collect();
+    tokens.sort_by_key(|&(line, col, _)| (line, col));
+
+    let mut sizes: BTreeMap<&str, usize> = BTreeMap::new();
+    for (i, &(line, col, source)) in tokens.iter().enumerate() {
+        let Some(source) = source else {
+            continue;
+        };
+        let line_length = line_lengths.get(line).copied().unwrap_or_default();
+        let end = match tokens.get(i + 1) {
+            Some(&(next_line, next_col, _)) if next_line == line => next_col.min(line_length),
+            _ => line_length,
+        };
+        *sizes.entry(source).or_default() += end.saturating_sub(col);
+    }
+
+    sizes
+        .into_iter()
+        .map(|(path, size)| ModuleStats {
+            path: path.to_string(),
+            size,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    use next_core::next_manifests::{
+        AppBuildManifest, AppPathsManifest, BuildManifest, EdgeFunctionDefinition,
+        MiddlewaresManifestV2, PagesManifest,
+    };
+    use sourcemap::SourceMap;
+
+    use super::{
+        get_entry_files, get_first_load_js_files, get_module_sizes, AssetSizes, BuildStatsManifests,
+    };
+
+    fn files(files: &[&str]) -> Vec<String> {
+        files.iter().map(|file| file.to_string()).collect()
+    }
+
+    #[test]
+    fn test_module_sizes() {
+        let content = "var a=1;var b=2;\nglue();\na();";
+        // `a.js` maps to the first 8 bytes of the first line and to the third line,
+        // `b.js` to the rest of the first line, and the second line is unmapped.
+        let source_map = SourceMap::from_slice(
+            br#"{
+                "version": 3,
+                "sources": ["a.js", "b.js"],
+                "names": [],
+                "mappings": "AAAA,QCAA;A;ADCA"
+            }"#,
+        )
+        .unwrap();
+
+        let sizes: Vec<_> = get_module_sizes(&source_map, content)
+            .into_iter()
+            .map(|module| (module.path, module.size))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![("a.js".to_string(), 12), ("b.js".to_string(), 8)]
+        );
+    }
+
+    #[test]
+    fn test_module_sizes_are_clamped_to_the_line() {
+        let content = "abc";
+        let source_map = SourceMap::from_slice(
+            br#"{
+                "version": 3,
+                "sources": ["a.js"],
+                "names": [],
+                "mappings": "AAAA,UAAA"
+            }"#,
+        )
+        .unwrap();
+
+        let sizes: Vec<_> = get_module_sizes(&source_map, content)
+            .into_iter()
+            .map(|module| (module.path, module.size))
+            .collect();
+        assert_eq!(sizes, vec![("a.js".to_string(), 3)]);
+    }
+
+    #[test]
+    fn test_first_load_js_files() {
+        let build_manifest = BuildManifest {
+            polyfill_files: files(&["static/chunks/polyfills.js"]),
+            pages: HashMap::from([
+                ("/_app".to_string(), files(&["static/chunks/app.js"])),
+                (
+                    "/_document".to_string(),
+                    files(&["static/chunks/document.js"]),
+                ),
+                (
+                    "/".to_string(),
+                    files(&[
+                        "static/chunks/index.js",
+                        "static/chunks/index.css",
+                        "static/chunks/app.js",
+                    ]),
+                ),
+            ]),
+            ..Default::default()
+        };
+        let app_build_manifest = AppBuildManifest {
+            pages: HashMap::from([(
+                "/page".to_string(),
+                files(&[
+                    "static/chunks/page.js",
+                    "static/chunks/page.css",
+                    "static/chunks/main.js",
+                    "static/chunks/page.js",
+                ]),
+            )]),
+        };
+
+        assert_eq!(
+            get_first_load_js_files(&build_manifest, &app_build_manifest),
+            BTreeMap::from([
+                (
+                    "/".to_string(),
+                    files(&[
+                        "static/chunks/app.js",
+                        "static/chunks/index.js",
+                        "static/chunks/polyfills.js",
+                    ])
+                ),
+                (
+                    "/page".to_string(),
+                    files(&["static/chunks/main.js", "static/chunks/page.js"])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_entry_files() {
+        let build_manifest = BuildManifest {
+            pages: HashMap::from([("/".to_string(), files(&["static/chunks/index.js"]))]),
+            ..Default::default()
+        };
+        let app_build_manifest = AppBuildManifest {
+            pages: HashMap::from([("/page".to_string(), files(&["static/chunks/page.js"]))]),
+        };
+        let pages_manifest = PagesManifest {
+            pages: HashMap::from([("/".to_string(), "pages/index.js".to_string())]),
+        };
+        let app_paths_manifest = AppPathsManifest {
+            edge_server_app_paths: PagesManifest {
+                pages: HashMap::from([("/page".to_string(), "edge/chunks/page.js".to_string())]),
+            },
+            ..Default::default()
+        };
+        let middlewares_manifest = MiddlewaresManifestV2 {
+            middleware: HashMap::from([(
+                "/".to_string(),
+                EdgeFunctionDefinition {
+                    files: files(&["server/edge/chunks/middleware.js"]),
+                    name: "middleware".to_string(),
+                    page: "/".to_string(),
+                    ..Default::default()
+                },
+            )]),
+            functions: HashMap::from([(
+                "/page".to_string(),
+                EdgeFunctionDefinition {
+                    files: files(&[
+                        "server/app/page_client-reference-manifest.js",
+                        "server/edge/chunks/page.js",
+                    ]),
+                    name: "app/page".to_string(),
+                    page: "/page".to_string(),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let entry_files = get_entry_files(&BuildStatsManifests {
+            build_manifest: &build_manifest,
+            app_build_manifest: &app_build_manifest,
+            pages_manifest: &pages_manifest,
+            app_paths_manifest: &app_paths_manifest,
+            middlewares_manifest: &middlewares_manifest,
+        });
+
+        let set = |files: &[&str]| -> BTreeSet<String> {
+            files.iter().map(|file| file.to_string()).collect()
+        };
+        assert_eq!(
+            entry_files,
+            BTreeMap::from([
+                (
+                    "/".to_string(),
+                    set(&["server/pages/index.js", "static/chunks/index.js"])
+                ),
+                (
+                    "/page".to_string(),
+                    set(&[
+                        "server/app/page_client-reference-manifest.js",
+                        "server/edge/chunks/page.js",
+                        "static/chunks/page.js",
+                    ])
+                ),
+                (
+                    "middleware".to_string(),
+                    set(&["server/edge/chunks/middleware.js"])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_asset_sizes() {
+        let content = "const answer = 42;\n".repeat(100);
+        let sizes = AssetSizes::of(content.as_bytes()).unwrap();
+
+        assert_eq!(sizes.size, content.len());
+        // Repetitive content compresses well with both algorithms.
+        assert!(sizes.gzip_size > 0 && sizes.gzip_size < sizes.size / 10);
+        assert!(sizes.brotli_size > 0 && sizes.brotli_size < sizes.size / 10);
+        // Brotli is expected to beat gzip at its highest quality.
+        assert!(sizes.brotli_size < sizes.gzip_size);
+    }
+
+    #[test]
+    fn test_asset_sizes_of_empty_content() {
+        let sizes = AssetSizes::of(&[]).unwrap();
+
+        assert_eq!(sizes.size, 0);
+        // The gzip header and trailer around an empty deflate block.
+        assert_eq!(sizes.gzip_size, 20);
+        // A complete brotli stream, which is never empty.
+        assert_eq!(sizes.brotli_size, 1);
+    }
+
+    #[test]
+    fn test_asset_sizes_add() {
+        let mut sizes = AssetSizes {
+            size: 1,
+            gzip_size: 2,
+            brotli_size: 3,
+        };
+        sizes += AssetSizes {
+            size: 10,
+            gzip_size: 20,
+            brotli_size: 30,
+        };
+        assert_eq!(
+            (sizes.size, sizes.gzip_size, sizes.brotli_size),
+            (11, 22, 33)
+        );
+    }
+}
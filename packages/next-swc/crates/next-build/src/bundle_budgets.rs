@@ -0,0 +1,444 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use anyhow::Result;
+use next_core::next_config::BundleBudgets;
+use turbo_tasks::{Completion, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::issue::{Issue, IssueExt, IssueSeverity},
+};
+
+use crate::build_stats::{BuildStats, ChunkStats, ModuleStats};
+
+/// The number of modules listed in the breakdown of a budget issue.
+const LARGEST_MODULES_COUNT: usize = 10;
+
+/// A client bundle exceeding one of the budgets of
+/// `experimental.bundleBudgets`.
+#[turbo_tasks::value(shared)]
+pub(crate) struct BundleBudgetIssue {
+    pub path: Vc<FileSystemPath>,
+    pub title: String,
+    pub detail: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for BundleBudgetIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("bundle size".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(self.title.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            "Sizes are gzipped and budgets are configured in `experimental.bundleBudgets` of \
+             next.config.js. Module sizes are the uncompressed bytes of generated code the source \
+             maps attribute to them."
+                .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn detail(&self) -> Vc<String> {
+        Vc::cell(self.detail.clone())
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub(crate) struct BundleBudgetIssues(Vec<Vc<BundleBudgetIssue>>);
+
+/// Emits the budget issues, so they can be reported with `handle_issues`.
+#[turbo_tasks::function]
+pub(crate) async fn emit_bundle_budget_issues(
+    issues: Vc<BundleBudgetIssues>,
+) -> Result<Vc<Completion>> {
+    for &issue in issues.await?.iter() {
+        issue.emit();
+    }
+    Ok(Completion::immutable())
+}
+
+/// Checks the client bundles of the build against the budgets. Issues point
+/// to `config_path`, where the budgets are declared.
+pub(crate) fn check_bundle_budgets(
+    budgets: &BundleBudgets,
+    build_stats: &BuildStats,
+    config_path: Vc<FileSystemPath>,
+) -> Vc<BundleBudgetIssues> {
+    Vc::cell(
+        get_budget_violations(budgets, build_stats)
+            .into_iter()
+            .map(|BudgetViolation { title, detail }| {
+                BundleBudgetIssue {
+                    path: config_path,
+                    title,
+                    detail,
+                }
+                .cell()
+            })
+            .collect(),
+    )
+}
+
+/// A budget exceeded by the client bundles, with the breakdown of the chunks
+/// counting towards it.
+#[derive(Debug, PartialEq)]
+struct BudgetViolation {
+    title: String,
+    detail: String,
+}
+
+fn get_budget_violations(
+    budgets: &BundleBudgets,
+    build_stats: &BuildStats,
+) -> Vec<BudgetViolation> {
+    let chunks: BTreeMap<&str, &ChunkStats> = build_stats
+        .chunks
+        .iter()
+        .map(|chunk| (chunk.path.as_str(), chunk))
+        .collect();
+    let client_chunks = || {
+        build_stats
+            .chunks
+            .iter()
+            .filter(|chunk| chunk.path.starts_with("static/"))
+    };
+
+    let mut violations = vec![];
+    let mut push_violation = |title: String, chunks: Vec<&ChunkStats>| {
+        violations.push(BudgetViolation {
+            title,
+            detail: format_breakdown(&chunks),
+        });
+    };
+
+    if let Some(max_size) = budgets.max_first_load_js_size {
+        for (page, page_stats) in &build_stats.pages {
+            let size = page_stats.first_load_js.gzip_size;
+            if size > max_size {
+                push_violation(
+                    format!(
+                        "First load JS of {page} is {}, exceeding the budget of {}",
+                        format_size(size),
+                        format_size(max_size)
+                    ),
+                    page_stats
+                        .files
+                        .iter()
+                        .filter_map(|file| chunks.get(file.as_str()).copied())
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    if let Some(max_size) = budgets.max_chunk_size {
+        for chunk in client_chunks().filter(|chunk| chunk.path.ends_with(".js")) {
+            let size = chunk.sizes.gzip_size;
+            if size > max_size {
+                push_violation(
+                    format!(
+                        "Chunk {} is {}, exceeding the budget of {}",
+                        chunk.path,
+                        format_size(size),
+                        format_size(max_size)
+                    ),
+                    vec![chunk],
+                );
+            }
+        }
+    }
+
+    if let Some(max_size) = budgets.max_total_css_size {
+        let css_chunks: Vec<_> = client_chunks()
+            .filter(|chunk| chunk.path.ends_with(".css"))
+            .collect();
+        let size: usize = css_chunks.iter().map(|chunk| chunk.sizes.gzip_size).sum();
+        if size > max_size {
+            push_violation(
+                format!(
+                    "Total CSS is {}, exceeding the budget of {}",
+                    format_size(size),
+                    format_size(max_size)
+                ),
+                css_chunks,
+            );
+        }
+    }
+
+    violations
+}
+
+/// Lists the largest chunks and the largest modules among them.
+fn format_breakdown(chunks: &[&ChunkStats]) -> String {
+    let mut detail = String::new();
+
+    let mut chunks = chunks.to_vec();
+    chunks.sort_by_key(|chunk| std::cmp::Reverse(chunk.sizes.gzip_size));
+    writeln!(detail, "Largest chunks:").unwrap();
+    for chunk in chunks.iter().take(LARGEST_MODULES_COUNT) {
+        writeln!(
+            detail,
+            "  {:>10}  {}",
+            format_size(chunk.sizes.gzip_size),
+            chunk.path
+        )
+        .unwrap();
+    }
+
+    // A module can be part of several of the chunks.
+    let mut modules: BTreeMap<&str, usize> = BTreeMap::new();
+    for ModuleStats { path, size } in chunks.iter().flat_map(|chunk| &chunk.modules) {
+        *modules.entry(path.as_str()).or_default() += size;
+    }
+    let mut modules: Vec<_> = modules.into_iter().collect();
+    modules.sort_by_key(|&(_, size)| std::cmp::Reverse(size));
+    if !modules.is_empty() {
+        writeln!(detail, "Largest modules:").unwrap();
+        for (path, size) in modules.into_iter().take(LARGEST_MODULES_COUNT) {
+            writeln!(detail, "  {:>10}  {}", format_size(size), path).unwrap();
+        }
+    }
+
+    detail
+}
+
+fn format_size(size: usize) -> String {
+    if size < 1000 {
+        format!("{size} B")
+    } else {
+        format!("{:.1} kB", size as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use next_core::next_config::BundleBudgets;
+
+    use super::{format_breakdown, get_budget_violations, BudgetViolation};
+    use crate::build_stats::{AssetSizes, BuildStats, ChunkStats, ModuleStats, PageStats};
+
+    fn chunk(path: &str, gzip_size: usize, modules: &[(&str, usize)]) -> ChunkStats {
+        ChunkStats {
+            path: path.to_string(),
+            sizes: AssetSizes {
+                size: gzip_size * 3,
+                gzip_size,
+                brotli_size: gzip_size / 2,
+            },
+            modules: modules
+                .iter()
+                .map(|&(path, size)| ModuleStats {
+                    path: path.to_string(),
+                    size,
+                })
+                .collect(),
+            entries: BTreeSet::new(),
+        }
+    }
+
+    fn build_stats() -> BuildStats {
+        BuildStats {
+            chunks: vec![
+                chunk("server/app/page.js", 90_000, &[]),
+                chunk(
+                    "static/chunks/framework.js",
+                    45_000,
+                    &[("react-dom.js", 120_000), ("react.js", 8_000)],
+                ),
+                chunk("static/chunks/page.js", 2_500, &[("app/page.js", 6_000)]),
+                chunk("static/css/app.css", 700, &[]),
+                chunk("static/css/page.css", 400, &[]),
+            ],
+            pages: BTreeMap::from([(
+                "/page".to_string(),
+                PageStats {
+                    first_load_js: AssetSizes {
+                        size: 142_500,
+                        gzip_size: 47_500,
+                        brotli_size: 23_750,
+                    },
+                    files: vec![
+                        "static/chunks/framework.js".to_string(),
+                        "static/chunks/page.js".to_string(),
+                    ],
+                },
+            )]),
+        }
+    }
+
+    fn titles(violations: Vec<BudgetViolation>) -> Vec<String> {
+        violations
+            .into_iter()
+            .map(|violation| violation.title)
+            .collect()
+    }
+
+    #[test]
+    fn test_no_budgets() {
+        assert_eq!(
+            get_budget_violations(&BundleBudgets::default(), &build_stats()),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_max_first_load_js_size() {
+        let budgets = |max_size| BundleBudgets {
+            max_first_load_js_size: Some(max_size),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_budget_violations(&budgets(47_500), &build_stats()),
+            vec![]
+        );
+        assert_eq!(
+            get_budget_violations(&budgets(40_000), &build_stats()),
+            vec![BudgetViolation {
+                title: "First load JS of /page is 47.5 kB, exceeding the budget of 40.0 kB"
+                    .to_string(),
+                detail: concat!(
+                    "Largest chunks:\n",
+                    "     45.0 kB  static/chunks/framework.js\n",
+                    "      2.5 kB  static/chunks/page.js\n",
+                    "Largest modules:\n",
+                    "    120.0 kB  react-dom.js\n",
+                    "      8.0 kB  react.js\n",
+                    "      6.0 kB  app/page.js\n",
+                )
+                .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_max_chunk_size() {
+        let budgets = BundleBudgets {
+            max_chunk_size: Some(2_000),
+            ..Default::default()
+        };
+
+        // Server chunks and CSS are not subject to the chunk budget.
+        assert_eq!(
+            titles(get_budget_violations(&budgets, &build_stats())),
+            vec![
+                "Chunk static/chunks/framework.js is 45.0 kB, exceeding the budget of 2.0 kB",
+                "Chunk static/chunks/page.js is 2.5 kB, exceeding the budget of 2.0 kB",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_total_css_size() {
+        let budgets = |max_size| BundleBudgets {
+            max_total_css_size: Some(max_size),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_budget_violations(&budgets(1_100), &build_stats()),
+            vec![]
+        );
+        assert_eq!(
+            get_budget_violations(&budgets(1_000), &build_stats()),
+            vec![BudgetViolation {
+                title: "Total CSS is 1.1 kB, exceeding the budget of 1.0 kB".to_string(),
+                detail: concat!(
+                    "Largest chunks:\n",
+                    "       700 B  static/css/app.css\n",
+                    "       400 B  static/css/page.css\n",
+                )
+                .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_all_budgets() {
+        let budgets = BundleBudgets {
+            max_first_load_js_size: Some(40_000),
+            max_chunk_size: Some(40_000),
+            max_total_css_size: Some(1_000),
+        };
+
+        assert_eq!(
+            titles(get_budget_violations(&budgets, &build_stats())),
+            vec![
+                "First load JS of /page is 47.5 kB, exceeding the budget of 40.0 kB",
+                "Chunk static/chunks/framework.js is 45.0 kB, exceeding the budget of 40.0 kB",
+                "Total CSS is 1.1 kB, exceeding the budget of 1.0 kB",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_breakdown() {
+        let chunks = [
+            chunk(
+                "static/chunks/a.js",
+                999,
+                &[("shared.js", 900), ("a.js", 50)],
+            ),
+            chunk(
+                "static/chunks/b.js",
+                12_345,
+                &[("shared.js", 900), ("b.js", 1_000)],
+            ),
+        ];
+
+        // Chunks and modules are sorted by size, and the sizes of a module in
+        // several chunks are summed up.
+        assert_eq!(
+            format_breakdown(&chunks.iter().collect::<Vec<_>>()),
+            concat!(
+                "Largest chunks:\n",
+                "     12.3 kB  static/chunks/b.js\n",
+                "       999 B  static/chunks/a.js\n",
+                "Largest modules:\n",
+                "      1.8 kB  shared.js\n",
+                "      1.0 kB  b.js\n",
+                "        50 B  a.js\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_breakdown_limits_the_listed_entries() {
+        let chunks: Vec<_> = (1..=12)
+            .map(|i| {
+                chunk(
+                    &format!("static/chunks/{i}.js"),
+                    i,
+                    &[(&format!("{i}.js"), i)],
+                )
+            })
+            .collect();
+
+        let breakdown = format_breakdown(&chunks.iter().collect::<Vec<_>>());
+        let lines: Vec<_> = breakdown.lines().collect();
+        assert_eq!(lines.len(), 22);
+        assert_eq!(lines[1], "        12 B  static/chunks/12.js");
+        assert_eq!(lines[10], "         3 B  static/chunks/3.js");
+        assert_eq!(lines[11], "Largest modules:");
+        assert_eq!(lines[21], "         3 B  3.js");
+    }
+}
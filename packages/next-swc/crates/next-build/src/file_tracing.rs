@@ -0,0 +1,623 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Path, MAIN_SEPARATOR},
+};
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use next_core::next_manifests::{AppPathsManifest, PagesManifest};
+use serde::Serialize;
+use turbo_tasks::{Completion, Completions, Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{
+        glob::Glob, DirectoryContent, DirectoryEntry, File, FileContent, FileSystemEntryType,
+        FileSystemPath,
+    },
+    turbopack::{
+        core::{
+            asset::{Asset, AssetContent},
+            compile_time_info::CompileTimeInfo,
+            context::AssetContext,
+            module::Module,
+            output::OutputAsset,
+            reference::primary_referenced_modules,
+            reference_type::ReferenceType,
+            source::Source,
+            virtual_source::VirtualSource,
+        },
+        turbopack::{
+            evaluate_context::node_build_environment, module_options::ModuleOptionsContext,
+            resolve_options_context::ResolveOptionsContext, ModuleAssetContext,
+        },
+    },
+};
+
+use crate::next_build::{all_assets_from_entries, write_manifest};
+
+/// The file tracing configuration of the build.
+pub(crate) struct FileTracingOptions<'a> {
+    /// The directory files are traced from, as the root of its own file
+    /// system. Traced files outside of it are left out.
+    pub tracing_root: Vc<FileSystemPath>,
+    /// The project directory, in the file system of the tracing root.
+    pub project_root: Vc<FileSystemPath>,
+    pub includes: &'a IndexMap<String, Vec<String>>,
+    pub ignores: &'a [String],
+}
+
+#[derive(Serialize)]
+struct NftJson {
+    version: u32,
+    files: Vec<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct TracedSources(Vec<Vc<FileSystemPath>>);
+
+/// Returns the tracing root, which is `outputFileTracingRoot` when configured
+/// and the project directory otherwise, like `next build` does, and the path
+/// of the project relative to it.
+pub(crate) fn get_tracing_root(
+    project_dir: &str,
+    output_file_tracing_root: Option<&str>,
+) -> Result<(String, String)> {
+    let tracing_root = match output_file_tracing_root {
+        Some(root) => dunce::canonicalize(root)
+            .context("outputFileTracingRoot can't be found")?
+            .to_str()
+            .context("outputFileTracingRoot contains invalid characters")?
+            .to_string(),
+        None => project_dir.to_string(),
+    };
+
+    let project_relative = Path::new(project_dir)
+        .strip_prefix(&tracing_root)
+        .context("the project directory must be inside outputFileTracingRoot")?
+        .to_str()
+        .context("project directory contains invalid characters")?
+        .replace(MAIN_SEPARATOR, "/");
+
+    Ok((tracing_root, project_relative))
+}
+
+/// Traces the files each Node.js server entry needs at runtime with the
+/// node-file-trace engine, and writes them to a `.nft.json` file next to the
+/// entry chunk.
+///
+/// The rest is left to `next build`, like for webpack builds:
+/// `collectBuildTraces` traces the Next.js server itself to
+/// `next-server.js.nft.json`, and `copyTracedFiles` copies the traced files of
+/// all entries and the files of edge functions to `.next/standalone`.
+pub(crate) async fn trace_server_files(
+    all_chunks: Vec<Vc<Box<dyn OutputAsset>>>,
+    node_root: Vc<FileSystemPath>,
+    pages_manifest: &PagesManifest,
+    app_paths_manifest: &AppPathsManifest,
+    options: FileTracingOptions<'_>,
+) -> Result<Vc<Completion>> {
+    let node_root_ref = node_root.await?;
+    let tracing_root_ref = options.tracing_root.await?;
+    let project_root_ref = options.project_root.await?;
+    let project_prefix = tracing_root_ref
+        .get_path_to(&project_root_ref)
+        .context("the project directory must be inside the tracing root")?;
+    // The node root's file system is rooted at the project directory.
+    let node_root_prefix = join_path(project_prefix, &node_root_ref.path);
+
+    // Output assets of the build, keyed by their path relative to the node root.
+    let mut output_assets = HashMap::new();
+    for &asset in all_assets_from_entries(Vc::cell(all_chunks)).await?.iter() {
+        if let Some(path) = node_root_ref.get_path_to(&*asset.ident().path().await?) {
+            output_assets.insert(path.to_string(), asset);
+        }
+    }
+
+    let tracing_context = file_tracing_context(options.tracing_root);
+    let ignores = Vc::cell(options.ignores.to_vec());
+
+    let mut completions = vec![];
+
+    let server_entries = pages_manifest
+        .pages
+        .iter()
+        .chain(app_paths_manifest.node_server_app_paths.pages.iter());
+    for (page, file) in server_entries {
+        let entry_path = format!("server/{file}");
+        let Some(&entry) = output_assets.get(&entry_path) else {
+            continue;
+        };
+
+        // Paths of the traced files relative to the tracing root.
+        let mut traced_files = BTreeSet::new();
+        for &asset in all_assets_from_entries(Vc::cell(vec![entry])).await?.iter() {
+            let asset_path = asset.ident().path().await?;
+            let Some(path) = node_root_ref.get_path_to(&asset_path) else {
+                continue;
+            };
+            if path != entry_path {
+                traced_files.insert(join_path(&node_root_prefix, path));
+            }
+            if path.ends_with(".js") {
+                let source = chunk_tracing_source(
+                    options.project_root.join(asset_path.path.clone()),
+                    asset.content(),
+                );
+                let sources = trace_source(source, tracing_context, options.tracing_root, ignores);
+                insert_sources(&mut traced_files, &tracing_root_ref, sources).await?;
+            }
+        }
+        for (route, globs) in options.includes {
+            if !Glob::new(route.clone()).await?.execute(page) {
+                continue;
+            }
+            for glob in globs {
+                let sources = read_glob_files(options.project_root, glob).await?;
+                insert_sources(&mut traced_files, &tracing_root_ref, Vc::cell(sources)).await?;
+            }
+        }
+
+        completions.push(write_nft_json(
+            node_root.join(format!("{entry_path}.nft.json")),
+            &join_path(&node_root_prefix, &entry_path),
+            &traced_files,
+        )?);
+    }
+
+    Ok(Completions::all(completions))
+}
+
+/// Returns the source node-file-trace analyzes for a chunk. Chunks load
+/// externals with `__turbopack_external_require__` and
+/// `__turbopack_external_import__`, which the analysis doesn't know about, so
+/// they are rewritten to `require` and `import` first.
+#[turbo_tasks::function]
+async fn chunk_tracing_source(
+    path: Vc<FileSystemPath>,
+    content: Vc<AssetContent>,
+) -> Result<Vc<Box<dyn Source>>> {
+    let AssetContent::File(file) = &*content.await? else {
+        return Ok(Vc::upcast(VirtualSource::new(path, content)));
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(Vc::upcast(VirtualSource::new(path, content)));
+    };
+    let code = rewrite_external_requires(&file.content().to_str()?);
+
+    Ok(Vc::upcast(VirtualSource::new(
+        path,
+        AssetContent::file(File::from(code).into()),
+    )))
+}
+
+/// Rewrites the calls of a chunk loading externals to `require` and `import`
+/// calls of their request only. Further arguments are options of the runtime.
+fn rewrite_external_requires(code: &str) -> String {
+    const CALLS: [(&str, &str); 2] = [
+        ("__turbopack_external_require__(", "require("),
+        ("__turbopack_external_import__(", "import("),
+    ];
+
+    let mut rewritten = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some((index, call, replacement)) = CALLS
+        .iter()
+        .filter_map(|&(call, replacement)| Some((rest.find(call)?, call, replacement)))
+        .min_by_key(|&(index, ..)| index)
+    {
+        rewritten.push_str(&rest[..index]);
+        rewritten.push_str(replacement);
+        rest = &rest[index + call.len()..];
+
+        if let Some(request_len) = string_literal_len(rest) {
+            if let Some(close) = rest[request_len..].find(')') {
+                rewritten.push_str(&rest[..request_len]);
+                rest = &rest[request_len + close..];
+            }
+        }
+    }
+    rewritten.push_str(rest);
+
+    rewritten
+}
+
+/// Returns the length of the string literal `code` starts with, if any.
+fn string_literal_len(code: &str) -> Option<usize> {
+    let quote = code.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let mut escaped = false;
+    for (index, c) in code.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The asset context of node-file-trace, which resolves like Node.js does
+/// without transforming anything.
+#[turbo_tasks::function]
+fn file_tracing_context(tracing_root: Vc<FileSystemPath>) -> Vc<ModuleAssetContext> {
+    ModuleAssetContext::new(
+        Vc::cell(Default::default()),
+        CompileTimeInfo::new(node_build_environment()),
+        ModuleOptionsContext {
+            enable_types: true,
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext {
+            enable_node_modules: Some(tracing_root),
+            enable_node_native_modules: true,
+            custom_conditions: vec!["node".to_string()],
+            ..Default::default()
+        }
+        .cell(),
+    )
+}
+
+/// Returns the files the source references at runtime, transitively. Files
+/// outside of the tracing root or matching one of the `ignores` globs are
+/// neither returned nor traced further.
+#[turbo_tasks::function]
+async fn trace_source(
+    source: Vc<Box<dyn Source>>,
+    tracing_context: Vc<ModuleAssetContext>,
+    tracing_root: Vc<FileSystemPath>,
+    ignores: Vc<Vec<String>>,
+) -> Result<Vc<TracedSources>> {
+    let tracing_root_ref = tracing_root.await?;
+    let mut ignore_globs = vec![];
+    for ignore in ignores.await?.iter() {
+        ignore_globs.push(Glob::new(ignore.clone()).await?);
+    }
+
+    let module = tracing_context.process(source, Value::new(ReferenceType::Undefined));
+    let mut visited = HashSet::new();
+    let mut package_jsons = HashSet::new();
+    let mut queue = vec![module];
+    let mut files = vec![];
+    while let Some(module) = queue.pop() {
+        for &referenced in primary_referenced_modules(module).await?.iter() {
+            let referenced = referenced.resolve().await?;
+            if !visited.insert(referenced) {
+                continue;
+            }
+            let path = referenced.ident().path();
+            let path_ref = path.await?;
+            let Some(relative_path) = tracing_root_ref.get_path_to(&path_ref) else {
+                continue;
+            };
+            if ignore_globs
+                .iter()
+                .any(|ignore| ignore.execute(relative_path))
+            {
+                continue;
+            }
+            // Node.js reads the `package.json` of a package to resolve it.
+            if let Some(package_json) = get_package_json_path(relative_path) {
+                if package_jsons.insert(package_json.clone()) {
+                    let package_json = tracing_root.join(package_json);
+                    if let FileSystemEntryType::File = *package_json.get_type().await? {
+                        files.push(package_json);
+                    }
+                }
+            }
+            files.push(path);
+            queue.push(referenced);
+        }
+    }
+
+    Ok(Vc::cell(files))
+}
+
+/// Returns the `package.json` of the package a file in `node_modules` belongs
+/// to, relative to the same root as the file.
+fn get_package_json_path(path: &str) -> Option<String> {
+    let (node_modules_dir, package_path) = path.rsplit_once("node_modules/")?;
+    let mut segments = package_path.split('/');
+    let name = match segments.next()? {
+        scope if scope.starts_with('@') => format!("{scope}/{}", segments.next()?),
+        name => name.to_string(),
+    };
+    // The file must be inside of the package directory.
+    segments.next()?;
+    Some(format!(
+        "{node_modules_dir}node_modules/{name}/package.json"
+    ))
+}
+
+async fn insert_sources(
+    traced_files: &mut BTreeSet<String>,
+    tracing_root: &FileSystemPath,
+    sources: Vc<TracedSources>,
+) -> Result<()> {
+    for &source in sources.await?.iter() {
+        if let Some(path) = tracing_root.get_path_to(&*source.await?) {
+            traced_files.insert(path.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the files matching a glob relative to `dir`. Only the directory
+/// the glob doesn't match with wildcards is read.
+async fn read_glob_files(dir: Vc<FileSystemPath>, glob: &str) -> Result<Vec<Vc<FileSystemPath>>> {
+    let glob = glob.trim_start_matches("./");
+    let is_static = |segment: &&str| !segment.contains(['*', '?', '[', '{']);
+    let segments: Vec<&str> = glob.split('/').collect();
+    if segments.iter().all(is_static) {
+        let path = dir.join(glob.to_string());
+        return Ok(match *path.get_type().await? {
+            FileSystemEntryType::File => vec![path],
+            _ => vec![],
+        });
+    }
+
+    let base = segments
+        .iter()
+        .copied()
+        .take_while(is_static)
+        .collect::<Vec<_>>()
+        .join("/");
+    let dir_ref = dir.await?;
+    let glob = Glob::new(glob.to_string()).await?;
+
+    let mut files = vec![];
+    let mut queue = vec![dir.join(base)];
+    while let Some(current) = queue.pop() {
+        let DirectoryContent::Entries(entries) = &*current.read_dir().await? else {
+            continue;
+        };
+        for entry in entries.values() {
+            match *entry {
+                DirectoryEntry::File(path) => {
+                    if let Some(relative_path) = dir_ref.get_path_to(&*path.await?) {
+                        if glob.execute(relative_path) {
+                            files.push(path);
+                        }
+                    }
+                }
+                DirectoryEntry::Directory(path) => queue.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Writes the traced files of the file at `traced_path`, relative to the
+/// tracing root, to its `.nft.json` file.
+fn write_nft_json(
+    nft_json_path: Vc<FileSystemPath>,
+    traced_path: &str,
+    traced_files: &BTreeSet<String>,
+) -> Result<Vc<Completion>> {
+    let traced_dir = traced_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    write_manifest(
+        NftJson {
+            version: 1,
+            files: traced_files
+                .iter()
+                .map(|path| get_relative_path(traced_dir, path))
+                .collect(),
+        },
+        nft_json_path,
+    )
+}
+
+fn join_path(dir: &str, path: &str) -> String {
+    if dir.is_empty() {
+        path.to_string()
+    } else {
+        format!("{dir}/{path}")
+    }
+}
+
+/// Returns the path of `to` relative to `from_dir`, both being relative to the
+/// same root.
+fn get_relative_path(from_dir: &str, to: &str) -> String {
+    let from_segments: Vec<_> = from_dir.split('/').filter(|s| !s.is_empty()).collect();
+    let to_segments: Vec<_> = to.split('/').filter(|s| !s.is_empty()).collect();
+    let common = from_segments
+        .iter()
+        .zip(&to_segments)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut segments = vec![".."; from_segments.len() - common];
+    segments.extend(&to_segments[common..]);
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use next_core::next_manifests::PagesManifest;
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::{
+            tasks::{run_once, TurboTasks},
+            tasks_fs::{DiskFileSystem, File, FileContent, FileSystem},
+            tasks_memory::MemoryBackend,
+        },
+        turbopack::core::{asset::AssetContent, virtual_output::VirtualOutputAsset},
+    };
+
+    use super::{
+        get_package_json_path, get_relative_path, get_tracing_root, rewrite_external_requires,
+        trace_server_files, FileTracingOptions, NftJson,
+    };
+
+    /// A server chunk as Turbopack emits it, with an external package.
+    const CHUNK: &str = r#"module.exports = {
+
+"[project]/pages/index.js [ssr] (ecmascript)": (({ r: __turbopack_require__, i: __turbopack_import__, s: __turbopack_esm__, g: global, __dirname, x: __turbopack_external_require__, y: __turbopack_external_import__ }) => (() => {
+
+const __TURBOPACK__external__external$2d$pkg__ = __turbopack_external_require__("external-pkg", true);
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+__turbopack_esm__({
+    "default": ()=>Page
+});
+function Page() {
+    return __TURBOPACK__external__external$2d$pkg__.answer;
+}
+
+})()),
+
+};
+
+//# sourceMappingURL=index.js.map"#;
+
+    #[test]
+    fn test_rewrite_external_requires() {
+        assert_eq!(
+            rewrite_external_requires(
+                "({ x: __turbopack_external_require__ }) => \
+                 __turbopack_external_require__(\"react\", true)"
+            ),
+            "({ x: __turbopack_external_require__ }) => require(\"react\")"
+        );
+        assert_eq!(
+            rewrite_external_requires("__turbopack_external_import__('esm-pkg(1)', true)"),
+            "import('esm-pkg(1)')"
+        );
+        assert_eq!(
+            rewrite_external_requires(
+                "__turbopack_external_import__(\"a\");__turbopack_external_require__(\"b\\\"\")"
+            ),
+            "import(\"a\");require(\"b\\\"\")"
+        );
+        // Dynamic requests are kept, even though they can't be traced.
+        assert_eq!(
+            rewrite_external_requires("__turbopack_external_require__(name, true)"),
+            "require(name, true)"
+        );
+    }
+
+    #[test]
+    fn test_package_json_path() {
+        assert_eq!(
+            get_package_json_path("node_modules/react/cjs/react.production.min.js").as_deref(),
+            Some("node_modules/react/package.json")
+        );
+        assert_eq!(
+            get_package_json_path("node_modules/@next/env/dist/index.js").as_deref(),
+            Some("node_modules/@next/env/package.json")
+        );
+        assert_eq!(
+            get_package_json_path("node_modules/a/node_modules/b/index.js").as_deref(),
+            Some("node_modules/a/node_modules/b/package.json")
+        );
+        assert_eq!(get_package_json_path("pages/index.js"), None);
+        assert_eq!(get_package_json_path("node_modules/@scope"), None);
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            get_relative_path(".next/server/pages", "node_modules/react/index.js"),
+            "../../../node_modules/react/index.js"
+        );
+        assert_eq!(
+            get_relative_path(".next/server/pages", ".next/server/chunks/1.js"),
+            "../chunks/1.js"
+        );
+    }
+
+    #[test]
+    fn test_tracing_root_defaults_to_project_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_dir = dir.path().to_str().unwrap();
+
+        assert_eq!(
+            get_tracing_root(project_dir, None).unwrap(),
+            (project_dir.to_string(), "".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_chunk_externals() {
+        crate::register();
+
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "node_modules/external-pkg/package.json",
+                r#"{ "name": "external-pkg", "main": "lib/index.js" }"#,
+            ),
+            (
+                "node_modules/external-pkg/lib/index.js",
+                "exports.answer = require('./helper');",
+            ),
+            (
+                "node_modules/external-pkg/lib/helper.js",
+                "module.exports = 42;",
+            ),
+            ("node_modules/unused-pkg/index.js", "module.exports = 0;"),
+        ];
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let project_dir = dir.path().to_str().unwrap().to_string();
+
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        run_once(tt, async move {
+            let project_fs: Vc<Box<dyn FileSystem>> =
+                Vc::upcast(DiskFileSystem::new("project".to_string(), project_dir));
+            let project_root = project_fs.root();
+            let node_root = project_root.join(".next".to_string());
+
+            let chunk = VirtualOutputAsset::new(
+                node_root.join("server/pages/index.js".to_string()),
+                AssetContent::file(File::from(CHUNK).into()),
+            );
+
+            trace_server_files(
+                vec![Vc::upcast(chunk)],
+                node_root,
+                &PagesManifest {
+                    pages: [("/".to_string(), "pages/index.js".to_string())]
+                        .into_iter()
+                        .collect(),
+                },
+                &Default::default(),
+                FileTracingOptions {
+                    tracing_root: project_root,
+                    project_root,
+                    includes: &Default::default(),
+                    ignores: &[],
+                },
+            )
+            .await?
+            .await?;
+
+            let nft_json = node_root.join("server/pages/index.js.nft.json".to_string());
+            let FileContent::Content(nft_json) = &*nft_json.read().await? else {
+                anyhow::bail!("the nft.json file should be written");
+            };
+            let nft_json: serde_json::Value = serde_json::from_str(&nft_json.content().to_str()?)?;
+            assert_eq!(
+                nft_json,
+                serde_json::to_value(NftJson {
+                    version: 1,
+                    files: vec![
+                        "../../../node_modules/external-pkg/lib/helper.js".to_string(),
+                        "../../../node_modules/external-pkg/lib/index.js".to_string(),
+                        "../../../node_modules/external-pkg/package.json".to_string(),
+                    ],
+                })?
+            );
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
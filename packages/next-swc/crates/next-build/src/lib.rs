@@ -8,10 +8,18 @@ use turbopack_binding::turbo::{
 };
 
 pub mod build_options;
+pub(crate) mod build_stats;
+pub(crate) mod bundle_budgets;
+pub(crate) mod file_tracing;
 pub(crate) mod next_app;
 pub(crate) mod next_build;
+pub(crate) mod next_dynamic;
+pub(crate) mod next_font;
+pub(crate) mod next_middleware;
 pub(crate) mod next_pages;
 
+use std::path::PathBuf;
+
 use anyhow::Result;
 use turbo_tasks::{StatsType, TurboTasksBackendApi};
 
@@ -44,6 +52,24 @@ pub async fn build(options: BuildOptions) -> Result<()> {
     Ok(())
 }
 
+/// Fetches the Google Fonts used by the project into the cache directory set
+/// in `NEXT_FONT_GOOGLE_CACHE_DIR`, so that builds can run without network.
+pub async fn prefetch_google_fonts(dir: Option<PathBuf>, root: Option<PathBuf>) -> Result<()> {
+    register();
+
+    let (project_root, workspace_root) = next_build::resolve_roots(dir.as_ref(), root.as_ref())?;
+    let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+
+    run_once(tt, async move {
+        next_build::next_prefetch_google_fonts(project_root, workspace_root).await?;
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(())
+}
+
 fn setup_tracing() {
     use tracing_subscriber::{prelude::*, EnvFilter, Registry};
 
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Path, MAIN_SEPARATOR_STR},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use next_core::{
     app_structure::{find_app_dir_if_enabled, get_entrypoints, Entrypoint},
+    get_edge_resolve_options_context,
     mode::NextMode,
     next_app::{
-        get_app_client_shared_chunks, get_app_page_entry, get_app_route_entry,
+        get_app_client_shared_chunks, get_app_page_entry, get_app_route_entry, get_server_actions,
         metadata::route::get_app_metadata_route_entry, AppEntry, ClientReferencesChunks,
     },
     next_client::{
@@ -15,11 +19,20 @@ use next_core::{
     next_client_reference::{ClientReferenceGraph, NextEcmascriptClientReferenceTransition},
     next_config::NextConfig,
     next_dynamic::NextDynamicTransition,
-    next_manifests::{AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest},
+    next_edge::{
+        asset_bindings::get_edge_asset_bindings, entry::app_edge_function_name,
+        route_regex::get_named_middleware_regex,
+    },
+    next_manifests::{
+        AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest,
+        EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2, NextFontManifest,
+        Regions, ServerReferenceManifest,
+    },
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    util::NextRuntime,
 };
 use turbo_tasks::{TryJoinIterExt, Value, Vc};
 use turbopack_binding::{
@@ -27,7 +40,7 @@ use turbopack_binding::{
     turbopack::{
         build::BuildChunkingContext,
         core::{
-            chunk::{ChunkingContext, EvaluatableAssets},
+            chunk::{ChunkingContext, EvaluatableAsset, EvaluatableAssets},
             compile_time_info::CompileTimeInfo,
             file_source::FileSource,
             ident::AssetIdent,
@@ -39,13 +52,27 @@ use turbopack_binding::{
     },
 };
 
+use crate::{
+    next_font::get_entry_font_files,
+    next_middleware::middleware_entry::{get_edge_entry_chunk, get_edge_files_paths},
+};
+
 #[turbo_tasks::value]
 pub struct AppEntries {
     /// All app entries.
     pub entries: Vec<Vc<AppEntry>>,
+    /// The context the server actions loaders of app entries are processed in.
+    /// `None` when there is no app directory.
+    pub rsc_context: Option<Vc<ModuleAssetContext>>,
+    /// The context the server actions loaders of app entries using the edge
+    /// runtime are processed in. `None` when there is no app directory.
+    pub edge_rsc_context: Option<Vc<ModuleAssetContext>>,
     /// The RSC runtime entries that should be evaluated before any app entry
     /// module when server rendering.
     pub rsc_runtime_entries: Vc<EvaluatableAssets>,
+    /// The RSC runtime entries that should be evaluated before any app entry
+    /// module when server rendering with the edge runtime.
+    pub edge_rsc_runtime_entries: Vc<EvaluatableAssets>,
     /// The client runtime entries that should be evaluated before any app entry
     /// module when client rendering.
     pub client_runtime_entries: Vc<EvaluatableAssets>,
@@ -58,6 +85,7 @@ pub async fn get_app_entries(
     execution_context: Vc<ExecutionContext>,
     client_compile_time_info: Vc<CompileTimeInfo>,
     server_compile_time_info: Vc<CompileTimeInfo>,
+    edge_compile_time_info: Vc<CompileTimeInfo>,
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<AppEntries>> {
     let app_dir = find_app_dir_if_enabled(project_root);
@@ -65,7 +93,10 @@ pub async fn get_app_entries(
     let Some(&app_dir) = app_dir.await?.as_ref() else {
         return Ok(AppEntries::cell(AppEntries {
             entries: vec![],
+            rsc_context: None,
+            edge_rsc_context: None,
             rsc_runtime_entries: EvaluatableAssets::empty(),
+            edge_rsc_runtime_entries: EvaluatableAssets::empty(),
             client_runtime_entries: EvaluatableAssets::empty(),
         }));
     };
@@ -135,6 +166,22 @@ pub async fn get_app_entries(
         ssr_resolve_options_context,
     );
 
+    // Client components of segments using the edge runtime are server rendered
+    // in the edge runtime as well.
+    let edge_ssr_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        ssr_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_ssr_transition = ContextTransition::new(
+        edge_compile_time_info,
+        ssr_module_options_context,
+        edge_ssr_resolve_options_context,
+    );
+
     const ECMASCRIPT_CLIENT_TRANSITION_NAME: &str = "next-ecmascript-client-reference";
 
     transitions.insert(
@@ -151,6 +198,15 @@ pub async fn get_app_entries(
         Vc::upcast(NextDynamicTransition::new(client_transition)),
     );
 
+    let mut edge_transitions = transitions.clone();
+    edge_transitions.insert(
+        ECMASCRIPT_CLIENT_TRANSITION_NAME.to_string(),
+        Vc::upcast(NextEcmascriptClientReferenceTransition::new(
+            client_transition,
+            edge_ssr_transition,
+        )),
+    );
+
     let rsc_ty = Value::new(ServerContextType::AppRSC {
         app_dir,
         client_transition: Some(Vc::upcast(client_transition)),
@@ -181,6 +237,22 @@ pub async fn get_app_entries(
         rsc_resolve_options_context,
     );
 
+    // Segments exporting `runtime = 'edge'` are built for the edge runtime instead.
+    let edge_rsc_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        rsc_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_rsc_context = ModuleAssetContext::new(
+        Vc::cell(edge_transitions),
+        edge_compile_time_info,
+        rsc_module_options_context,
+        edge_rsc_resolve_options_context,
+    );
+
     let entries = entrypoints
         .await?
         .iter()
@@ -188,24 +260,21 @@ pub async fn get_app_entries(
             Ok(match entrypoint {
                 Entrypoint::AppPage { page, loader_tree } => get_app_page_entry(
                     rsc_context,
-                    // TODO add edge support
-                    rsc_context,
+                    edge_rsc_context,
                     *loader_tree,
                     page.clone(),
                     project_root,
                 ),
                 Entrypoint::AppRoute { page, path } => get_app_route_entry(
                     rsc_context,
-                    // TODO add edge support
-                    rsc_context,
+                    edge_rsc_context,
                     Vc::upcast(FileSource::new(*path)),
                     page.clone(),
                     project_root,
                 ),
                 Entrypoint::AppMetadata { page, metadata } => get_app_metadata_route_entry(
                     rsc_context,
-                    // TODO add edge support
-                    rsc_context,
+                    edge_rsc_context,
                     project_root,
                     page.clone(),
                     mode,
@@ -233,31 +302,45 @@ pub async fn get_app_entries(
 
     Ok(AppEntries::cell(AppEntries {
         entries,
+        rsc_context: Some(rsc_context),
+        edge_rsc_context: Some(edge_rsc_context),
         rsc_runtime_entries: runtime_entries.resolve_entries(Vc::upcast(rsc_context)),
+        edge_rsc_runtime_entries: runtime_entries.resolve_entries(Vc::upcast(edge_rsc_context)),
         client_runtime_entries: client_runtime_entries.resolve_entries(Vc::upcast(client_context)),
     }))
 }
 
 /// Computes and returns all chunks for app entries. The chunks will be appended
 /// to `all_chunks`, and the chunking information will be added to the provided
-/// manifests.
+/// manifests. Entries using the edge runtime are added to the middleware
+/// manifest's functions, and the server actions of all entries are merged into
+/// the server reference manifest.
 pub async fn compute_app_entries_chunks(
     next_config: Vc<NextConfig>,
     app_entries: &AppEntries,
     app_client_reference_graph: Vc<ClientReferenceGraph>,
     app_client_references_chunks: Vc<ClientReferencesChunks>,
+    edge_app_client_reference_graph: Vc<ClientReferenceGraph>,
+    edge_app_client_references_chunks: Vc<ClientReferencesChunks>,
     rsc_chunking_context: Vc<BuildChunkingContext>,
+    edge_rsc_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     client_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    edge_ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     node_root: Vc<FileSystemPath>,
     client_relative_path: Vc<FileSystemPath>,
+    app_dir_path: Option<&Path>,
     app_paths_manifest_dir_path: &FileSystemPath,
     app_build_manifest: &mut AppBuildManifest,
     build_manifest: &mut BuildManifest,
     app_paths_manifest: &mut AppPathsManifest,
+    next_font_manifest: &mut NextFontManifest,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
+    server_reference_manifest: &mut ServerReferenceManifest,
     all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
 ) -> Result<()> {
     let client_relative_path_ref = client_relative_path.await?;
+    let node_root_ref = node_root.await?;
 
     let app_client_shared_chunks = get_app_client_shared_chunks(
         AssetIdent::from_path(
@@ -282,31 +365,45 @@ pub async fn compute_app_entries_chunks(
         }
     }
 
-    let app_client_references_chunks_ref = app_client_references_chunks.await?;
+    let (Some(rsc_context), Some(edge_rsc_context)) =
+        (app_entries.rsc_context, app_entries.edge_rsc_context)
+    else {
+        return Ok(());
+    };
 
     for app_entry in app_entries.entries.iter().copied() {
         let app_entry = app_entry.await?;
 
-        let app_entry_client_references = app_client_reference_graph
+        let app_entry_config = app_entry.config.await?;
+        let runtime = app_entry_config.runtime.unwrap_or_default();
+
+        // The client components of edge entries are server rendered in the edge
+        // runtime, so they are chunked separately from the ones of Node.js entries.
+        let (client_reference_graph, client_references_chunks, entry_ssr_chunking_context) =
+            match runtime {
+                NextRuntime::NodeJs => (
+                    app_client_reference_graph,
+                    app_client_references_chunks,
+                    ssr_chunking_context,
+                ),
+                NextRuntime::Edge => (
+                    edge_app_client_reference_graph,
+                    edge_app_client_references_chunks,
+                    edge_ssr_chunking_context,
+                ),
+            };
+
+        let app_entry_client_references = client_reference_graph
             .entry(Vc::upcast(app_entry.rsc_entry))
             .await?;
-
-        let rsc_chunk = rsc_chunking_context.entry_chunk_group(
-            node_root.join(format!(
-                "server/app/{original_name}.js",
-                original_name = app_entry.original_name
-            )),
-            app_entry.rsc_entry,
-            app_entries.rsc_runtime_entries,
-        );
-        all_chunks.push(rsc_chunk);
+        let client_references_chunks_ref = client_references_chunks.await?;
 
         let mut app_entry_client_chunks = vec![];
         // TODO(alexkirsz) In which manifest should this go?
         let mut app_entry_ssr_chunks = vec![];
 
         for client_reference in app_entry_client_references.iter() {
-            let client_reference_chunks = app_client_references_chunks_ref
+            let client_reference_chunks = client_references_chunks_ref
                 .get(client_reference.ty())
                 .expect("client reference should have corresponding chunks");
             app_entry_client_chunks
@@ -314,6 +411,115 @@ pub async fn compute_app_entries_chunks(
             app_entry_ssr_chunks.extend(client_reference_chunks.ssr_chunks.await?.iter().copied());
         }
 
+        let entry_manifest = ClientReferenceManifest::build_output(
+            node_root,
+            client_relative_path,
+            app_entry.original_name.clone(),
+            client_reference_graph.entry(Vc::upcast(app_entry.rsc_entry)),
+            client_references_chunks,
+            client_chunking_context,
+            entry_ssr_chunking_context,
+            runtime,
+            next_config.computed_asset_prefix(),
+        );
+        all_chunks.push(entry_manifest);
+
+        let rsc_chunk = match runtime {
+            NextRuntime::NodeJs => {
+                let mut evaluatable_assets = app_entries.rsc_runtime_entries.await?.clone_value();
+
+                let (loader, manifest) = get_server_actions(
+                    app_entry.rsc_entry,
+                    node_root,
+                    &app_entry.original_name,
+                    NextRuntime::NodeJs,
+                    Vc::upcast(rsc_context),
+                    Vc::upcast(rsc_chunking_context),
+                    next_config.enable_server_actions(),
+                )
+                .await?;
+                server_reference_manifest.extend(manifest);
+                evaluatable_assets.extend(loader);
+
+                let rsc_chunk = rsc_chunking_context.entry_chunk_group(
+                    node_root.join(format!(
+                        "server/app/{original_name}.js",
+                        original_name = app_entry.original_name
+                    )),
+                    app_entry.rsc_entry,
+                    Vc::cell(evaluatable_assets),
+                );
+                all_chunks.push(rsc_chunk);
+                rsc_chunk
+            }
+            NextRuntime::Edge => {
+                let Some(evaluatable) =
+                    Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(app_entry.rsc_entry)
+                        .await?
+                else {
+                    bail!("Entry module must be evaluatable");
+                };
+                let mut evaluatable_assets =
+                    app_entries.edge_rsc_runtime_entries.await?.clone_value();
+                evaluatable_assets.push(evaluatable);
+
+                let (loader, manifest) = get_server_actions(
+                    app_entry.rsc_entry,
+                    node_root,
+                    &app_entry.original_name,
+                    NextRuntime::Edge,
+                    Vc::upcast(edge_rsc_context),
+                    edge_rsc_chunking_context,
+                    next_config.enable_server_actions(),
+                )
+                .await?;
+                server_reference_manifest.extend(manifest);
+                evaluatable_assets.extend(loader);
+
+                let edge_files = edge_rsc_chunking_context.evaluated_chunk_group(
+                    app_entry.rsc_entry.ident(),
+                    Vc::cell(evaluatable_assets),
+                );
+                let bindings = get_edge_asset_bindings(edge_files, node_root).await?;
+                let edge_files = edge_files.await?;
+                all_chunks.extend(edge_files.iter().copied());
+                all_chunks.extend(bindings.output_assets);
+
+                // The edge runtime loads the files of a function in order, so the client
+                // reference manifest and the SSR chunks of client components are
+                // registered before the entry is evaluated.
+                let mut function_files = vec![entry_manifest];
+                for &chunk in app_entry_ssr_chunks.iter() {
+                    if chunk.ident().path().await?.extension_ref() == Some("js") {
+                        function_files.push(chunk);
+                    }
+                }
+                function_files.extend(edge_files.iter().copied());
+
+                middlewares_manifest.functions.insert(
+                    app_entry.original_name.clone(),
+                    EdgeFunctionDefinition {
+                        files: get_edge_files_paths(&function_files, &node_root_ref).await?,
+                        name: app_edge_function_name(&app_entry.original_name),
+                        page: app_entry.original_name.clone(),
+                        matchers: vec![MiddlewareMatcher {
+                            regexp: Some(get_named_middleware_regex(&app_entry.pathname)),
+                            original_source: app_entry.pathname.clone(),
+                            ..Default::default()
+                        }],
+                        wasm: Some(bindings.wasm),
+                        assets: Some(bindings.assets),
+                        regions: app_entry_config
+                            .preferred_region
+                            .clone()
+                            .map(Regions::Multiple),
+                    },
+                );
+
+                get_edge_entry_chunk(&edge_files).await?
+            }
+        };
+
         let app_entry_client_chunks_paths = app_entry_client_chunks
             .iter()
             .map(|chunk| chunk.ident().path())
@@ -330,31 +536,39 @@ pub async fn compute_app_entries_chunks(
             .collect();
         app_entry_client_chunks_paths.extend(app_shared_client_chunks_paths.iter().cloned());
 
+        let entry_font_files =
+            get_entry_font_files(app_entry_client_chunks, &client_relative_path_ref).await?;
+        next_font_manifest.app_using_size_adjust |= entry_font_files.using_size_adjust;
+        if let Some(app_dir_path) = app_dir_path {
+            if !entry_font_files.font_files.is_empty() {
+                // Fonts are looked up by the path of the page file without its extension, like
+                // the webpack `NextFontManifestPlugin` does.
+                let entry_path = app_dir_path
+                    .join(app_entry.original_name.trim_start_matches('/'))
+                    .to_string_lossy()
+                    .replace('/', MAIN_SEPARATOR_STR);
+                next_font_manifest
+                    .app
+                    .insert(entry_path, entry_font_files.font_files);
+            }
+        }
+
         app_build_manifest.pages.insert(
             app_entry.original_name.clone(),
             app_entry_client_chunks_paths,
         );
 
-        app_paths_manifest.node_server_app_paths.pages.insert(
+        let app_paths = match runtime {
+            NextRuntime::NodeJs => &mut app_paths_manifest.node_server_app_paths,
+            NextRuntime::Edge => &mut app_paths_manifest.edge_server_app_paths,
+        };
+        app_paths.pages.insert(
             app_entry.original_name.clone(),
             app_paths_manifest_dir_path
                 .get_path_to(&*rsc_chunk.ident().path().await?)
                 .expect("RSC chunk path should be within app paths manifest directory")
                 .to_string(),
         );
-
-        let entry_manifest = ClientReferenceManifest::build_output(
-            node_root,
-            client_relative_path,
-            app_entry.original_name.clone(),
-            app_client_reference_graph.entry(Vc::upcast(app_entry.rsc_entry)),
-            app_client_references_chunks,
-            client_chunking_context,
-            ssr_chunking_context,
-            next_config.computed_asset_prefix(),
-        );
-
-        all_chunks.push(entry_manifest);
     }
 
     Ok(())
@@ -1,25 +1,31 @@
 use std::{
     collections::{HashMap, HashSet},
     env::current_dir,
-    path::{PathBuf, MAIN_SEPARATOR},
+    path::{Path, PathBuf, MAIN_SEPARATOR},
 };
 
 use anyhow::{Context, Result};
 use dunce::canonicalize;
 use next_core::{
+    app_structure::find_app_dir_if_enabled,
+    get_edge_chunking_context, get_edge_compile_time_info,
     mode::NextMode,
     next_app::get_app_client_references_chunks,
-    next_client::{get_client_chunking_context, get_client_compile_time_info},
+    next_client::{
+        get_browserslist_query, get_client_chunking_context, get_client_compile_time_info,
+    },
     next_client_reference::{ClientReferenceGraph, ClientReferenceType},
-    next_config::load_next_config,
+    next_config::{load_next_config, OutputType},
     next_dynamic::NextDynamicEntries,
     next_manifests::{
         AppBuildManifest, AppPathsManifest, BuildManifest, ClientBuildManifest, FontManifest,
-        MiddlewaresManifest, NextFontManifest, PagesManifest, ReactLoadableManifest,
-        ServerReferenceManifest,
+        MiddlewaresManifest, MiddlewaresManifestV2, NextFontManifest, PagesManifest,
+        ReactLoadableManifest, ServerReferenceManifest,
     },
     next_server::{get_server_chunking_context, get_server_compile_time_info},
+    prefetch_google_fonts,
     url_node::get_sorted_routes,
+    util::NextRuntime,
     {self},
 };
 use serde::Serialize;
@@ -49,7 +55,12 @@ use turbopack_binding::{
 
 use crate::{
     build_options::{BuildContext, BuildOptions},
+    build_stats::{compute_build_stats, BuildStatsManifests},
+    bundle_budgets::{check_bundle_budgets, emit_bundle_budget_issues},
+    file_tracing::{get_tracing_root, trace_server_files, FileTracingOptions},
     next_app::app_entries::{compute_app_entries_chunks, get_app_entries},
+    next_dynamic::compute_dynamic_entries_chunks,
+    next_middleware::middleware_entry::{compute_middleware_entry_chunks, get_middleware_entry},
     next_pages::page_entries::{compute_page_entries_chunks, get_page_entries},
 };
 
@@ -58,29 +69,8 @@ static MIN_FAILING_SEVERITY: IssueSeverity = IssueSeverity::Fatal;
 
 #[turbo_tasks::function]
 pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Result<Vc<Completion>> {
-    let project_root = options
-        .dir
-        .as_ref()
-        .map(canonicalize)
-        .unwrap_or_else(current_dir)
-        .context("project directory can't be found")?
-        .to_str()
-        .context("project directory contains invalid characters")?
-        .to_string();
-
-    let workspace_root = if let Some(root) = options.root.as_ref() {
-        canonicalize(root)
-            .context("root directory can't be found")?
-            .to_str()
-            .context("root directory contains invalid characters")?
-            .to_string()
-    } else {
-        project_root.clone()
-    };
-
-    let browserslist_query = "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
-                              versions, last 1 Edge versions"
-        .to_string();
+    let (project_root, workspace_root) =
+        resolve_roots(options.dir.as_ref(), options.root.as_ref())?;
 
     let log_options = LogOptions {
         project_dir: PathBuf::from(project_root.clone()),
@@ -101,16 +91,11 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let node_root = node_fs.root().join(dist_dir.clone());
     let client_fs = client_fs(project_root.clone(), issue_reporter);
     let client_root = client_fs.root().join(dist_dir);
-    // TODO(alexkirsz) This should accept a URL for assetPrefix.
-    // let client_public_fs = VirtualFileSystem::new();
-    // let client_public_root = client_public_fs.root();
     let workspace_fs = workspace_fs(workspace_root.clone(), issue_reporter);
-    let project_relative = project_root.strip_prefix(&workspace_root).unwrap();
-    let project_relative = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/");
-    let project_root = workspace_fs.root().join(project_relative);
+    let project_dir = project_root.clone();
+    let project_root = workspace_fs
+        .root()
+        .join(project_relative_path(&project_root, &workspace_root));
 
     let node_root_ref = node_root.await?;
 
@@ -134,6 +119,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let mode = NextMode::Build;
 
     let client_define_env = Vc::cell(options.define_env.client.iter().cloned().collect());
+    let browserslist_query = get_browserslist_query(project_root, env, mode);
     let client_compile_time_info =
         get_client_compile_time_info(mode, browserslist_query, client_define_env);
 
@@ -146,6 +132,10 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         next_config,
     );
 
+    let edge_define_env = Vc::cell(options.define_env.edge.iter().cloned().collect());
+    let edge_compile_time_info =
+        get_edge_compile_time_info(mode, project_root, ServerAddr::empty(), edge_define_env);
+
     // TODO(alexkirsz) Pages should build their own routes, outside of a FS.
     let next_router_fs = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new());
     let next_router_root = next_router_fs.root();
@@ -155,6 +145,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         execution_context,
         client_compile_time_info,
         server_compile_time_info,
+        edge_compile_time_info,
         next_config,
     );
 
@@ -163,6 +154,14 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         execution_context,
         client_compile_time_info,
         server_compile_time_info,
+        edge_compile_time_info,
+        next_config,
+    );
+
+    let middleware_entry = get_middleware_entry(
+        project_root,
+        execution_context,
+        edge_compile_time_info,
         next_config,
     );
 
@@ -183,40 +182,54 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     )
     .await?;
 
+    handle_issues(
+        middleware_entry,
+        issue_reporter,
+        MIN_FAILING_SEVERITY.cell(),
+        None,
+        None,
+    )
+    .await?;
+
     let page_entries = page_entries.await?;
     let app_entries = app_entries.await?;
 
-    let app_rsc_entries: Vec<_> = app_entries
-        .entries
-        .iter()
-        .copied()
-        .map(|entry| async move { Ok(entry.await?.rsc_entry) })
-        .try_join()
-        .await?;
+    let mut app_rsc_entries = vec![];
+    let mut edge_app_rsc_entries = vec![];
+    for app_entry in app_entries.entries.iter() {
+        let app_entry = app_entry.await?;
+        match app_entry.config.await?.runtime.unwrap_or_default() {
+            NextRuntime::NodeJs => app_rsc_entries.push(app_entry.rsc_entry),
+            NextRuntime::Edge => edge_app_rsc_entries.push(app_entry.rsc_entry),
+        }
+    }
 
+    // Client components are server rendered in the runtime of the entry referencing
+    // them, so edge entries get a client reference graph of their own.
     let app_client_references = ClientReferenceGraph::new(Vc::cell(
         app_rsc_entries.iter().copied().map(Vc::upcast).collect(),
     ));
+    let edge_app_client_references = ClientReferenceGraph::new(Vc::cell(
+        edge_app_rsc_entries
+            .iter()
+            .copied()
+            .map(Vc::upcast)
+            .collect(),
+    ));
 
     // The same client reference can occur from two different server components.
     // Here, we're only interested in deduped client references.
     let app_client_reference_tys = app_client_references.types();
+    let edge_app_client_reference_tys = edge_app_client_references.types();
 
-    let app_ssr_entries: Vec<_> = app_client_reference_tys
-        .await?
-        .iter()
-        .map(|client_reference_ty| async move {
-            let ClientReferenceType::EcmascriptClientReference(entry) = client_reference_ty else {
-                return Ok(None);
-            };
-
-            Ok(Some(entry.await?.ssr_module))
-        })
-        .try_join()
-        .await?
-        .into_iter()
-        .flatten()
-        .collect();
+    let mut app_ssr_entries = vec![];
+    for client_reference_tys in [app_client_reference_tys, edge_app_client_reference_tys] {
+        for client_reference_ty in client_reference_tys.await?.iter() {
+            if let ClientReferenceType::EcmascriptClientReference(entry) = client_reference_ty {
+                app_ssr_entries.push(entry.await?.ssr_module);
+            }
+        }
+    }
 
     let page_ssr_entries = page_entries
         .entries
@@ -230,6 +243,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .iter()
         .copied()
         .chain(app_rsc_entries.iter().copied())
+        .chain(edge_app_rsc_entries.iter().copied())
         .collect();
 
     let all_node_entries: Vec<_> = page_ssr_entries
@@ -238,11 +252,9 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .chain(app_node_entries.iter().copied())
         .collect();
 
-    // TODO(alexkirsz) Handle dynamic entries and dynamic chunks.
-    let _dynamic_entries = NextDynamicEntries::from_entries(Vc::cell(
+    let dynamic_entries = NextDynamicEntries::from_entries(Vc::cell(
         all_node_entries.iter().copied().map(Vc::upcast).collect(),
-    ))
-    .await?;
+    ));
 
     // TODO(alexkirsz) At this point, we have access to the whole module graph via
     // the entries. This is where we should compute unique module ids and optimized
@@ -252,7 +264,8 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
 
     // This ensures that the _next prefix is properly stripped from all client paths
     // in manifests. It will be added back on the client through the chunk_base_path
-    // mechanism.
+    // mechanism. The asset prefix is only ever prepended to these paths, so it can
+    // be a full URL while client assets are still emitted to the client root.
     let next_config_ref = next_config.await?;
     let client_relative_path = client_root.join(format!(
         "{}/_next",
@@ -283,11 +296,41 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let rsc_chunking_context = server_chunking_context.with_layer("rsc".to_string());
     let ssr_chunking_context = server_chunking_context.with_layer("ssr".to_string());
 
+    let edge_chunking_context = get_edge_chunking_context(
+        project_root,
+        node_root,
+        client_relative_path,
+        next_config.computed_asset_prefix(),
+        edge_compile_time_info.environment(),
+    );
+    let edge_ssr_chunking_context = edge_chunking_context.with_layer("edge ssr".to_string());
+    let edge_rsc_chunking_context = edge_chunking_context.with_layer("edge rsc".to_string());
+    let middleware_chunking_context = edge_chunking_context.with_layer("middleware".to_string());
+
     let mut all_chunks = vec![];
 
     let mut build_manifest: BuildManifest = Default::default();
     let build_manifest_path = client_root.join("build-manifest.json".to_string());
 
+    let mut next_font_manifest = NextFontManifest::default();
+    let next_font_manifest_path = node_root.join("server/next-font-manifest.json".to_string());
+
+    let mut middlewares_manifest = MiddlewaresManifestV2::default();
+    let middlewares_manifest_path = node_root.join("server/middleware-manifest.json".to_string());
+
+    // MIDDLEWARE CHUNKING
+
+    if let Some(middleware_entry) = *middleware_entry.await? {
+        compute_middleware_entry_chunks(
+            &*middleware_entry.await?,
+            middleware_chunking_context,
+            node_root,
+            &mut middlewares_manifest,
+            &mut all_chunks,
+        )
+        .await?;
+    }
+
     // PAGE CHUNKING
 
     let mut pages_manifest: PagesManifest = Default::default();
@@ -298,11 +341,14 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         &page_entries,
         client_chunking_context,
         ssr_chunking_context,
+        edge_ssr_chunking_context,
         node_root,
         &pages_manifest_dir_path,
         &client_relative_path_ref,
         &mut pages_manifest,
         &mut build_manifest,
+        &mut next_font_manifest,
+        &mut middlewares_manifest,
         &mut all_chunks,
     )
     .await?;
@@ -316,39 +362,81 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let app_paths_manifest_path = node_root.join("server/app-paths-manifest.json".to_string());
     let app_paths_manifest_dir_path = app_paths_manifest_path.parent().await?;
 
+    let mut server_reference_manifest = ServerReferenceManifest::default();
+    let server_reference_manifest_path =
+        node_root.join("server/server-reference-manifest.json".to_string());
+
     // APP CLIENT REFERENCES CHUNKING
 
     let app_client_references_chunks = get_app_client_references_chunks(
         app_client_reference_tys,
         client_chunking_context,
-        ssr_chunking_context,
+        Vc::upcast(ssr_chunking_context),
+    );
+    let edge_app_client_references_chunks = get_app_client_references_chunks(
+        edge_app_client_reference_tys,
+        client_chunking_context,
+        edge_ssr_chunking_context,
     );
-    let app_client_references_chunks_ref = app_client_references_chunks.await?;
 
-    for app_client_reference_chunks in app_client_references_chunks_ref.values() {
-        let client_chunks = &app_client_reference_chunks.client_chunks.await?;
-        let ssr_chunks = &app_client_reference_chunks.ssr_chunks.await?;
-        all_chunks.extend(client_chunks.iter().copied());
-        all_chunks.extend(ssr_chunks.iter().copied());
+    for client_references_chunks in [
+        app_client_references_chunks,
+        edge_app_client_references_chunks,
+    ] {
+        for app_client_reference_chunks in client_references_chunks.await?.values() {
+            let client_chunks = &app_client_reference_chunks.client_chunks.await?;
+            let ssr_chunks = &app_client_reference_chunks.ssr_chunks.await?;
+            all_chunks.extend(client_chunks.iter().copied());
+            all_chunks.extend(ssr_chunks.iter().copied());
+        }
     }
 
     // APP RSC CHUNKING
     // TODO(alexkirsz) Do some of that in parallel with the above.
 
+    // The app next/font manifest is keyed by the absolute path of app entries.
+    let app_dir_path = match *find_app_dir_if_enabled(project_root).await? {
+        Some(app_dir) => Some(Path::new(&workspace_root).join(&app_dir.await?.path)),
+        None => None,
+    };
+
     compute_app_entries_chunks(
         next_config,
         &app_entries,
         app_client_references,
         app_client_references_chunks,
+        edge_app_client_references,
+        edge_app_client_references_chunks,
         rsc_chunking_context,
+        edge_rsc_chunking_context,
         client_chunking_context,
         Vc::upcast(ssr_chunking_context),
+        edge_ssr_chunking_context,
         node_root,
         client_relative_path,
+        app_dir_path.as_deref(),
         &app_paths_manifest_dir_path,
         &mut app_build_manifest,
         &mut build_manifest,
         &mut app_paths_manifest,
+        &mut next_font_manifest,
+        &mut middlewares_manifest,
+        &mut server_reference_manifest,
+        &mut all_chunks,
+    )
+    .await?;
+
+    // DYNAMIC CHUNKING
+
+    let mut react_loadable_manifest = ReactLoadableManifest::default();
+    let react_loadable_manifest_path = node_root.join("react-loadable-manifest.json".to_string());
+
+    compute_dynamic_entries_chunks(
+        dynamic_entries,
+        Vc::upcast(client_chunking_context),
+        Vc::upcast(ssr_chunking_context),
+        &client_relative_path_ref,
+        &mut react_loadable_manifest,
         &mut all_chunks,
     )
     .await?;
@@ -360,12 +448,27 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
 
         let ssg_manifest_path = format!("static/{build_id}/_ssgManifest.js");
 
+        // The client uses the SSG manifest to fetch the data of statically generated
+        // pages from their JSON files when navigating.
+        let mut ssg_pages = vec![];
+        for page_entry in page_entries.entries.iter() {
+            let page_entry = page_entry.await?;
+            if page_entry.is_ssg {
+                ssg_pages.push(page_entry.pathname.await?.clone_value());
+            }
+        }
+        ssg_pages.sort();
+
         let ssg_manifest_fs_path = node_root.join(ssg_manifest_path.clone());
         completions.push(
             ssg_manifest_fs_path.write(
                 FileContent::Content(
-                    "self.__SSG_MANIFEST=new Set;self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()"
-                        .into(),
+                    format!(
+                        "self.__SSG_MANIFEST=new \
+                         Set({});self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()",
+                        StringifyJs(&ssg_pages)
+                    )
+                    .into(),
                 )
                 .cell(),
             ),
@@ -428,34 +531,102 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         build_manifest.low_priority_files.push(client_manifest_path);
     }
 
+    let bundle_budgets = next_config.bundle_budgets().await?;
+    if options.full_stats || !bundle_budgets.is_empty() {
+        let build_stats = compute_build_stats(
+            all_chunks.clone(),
+            &node_root_ref,
+            &client_relative_path_ref,
+            BuildStatsManifests {
+                build_manifest: &build_manifest,
+                app_build_manifest: &app_build_manifest,
+                pages_manifest: &pages_manifest,
+                app_paths_manifest: &app_paths_manifest,
+                middlewares_manifest: &middlewares_manifest,
+            },
+        )
+        .await?;
+
+        if !bundle_budgets.is_empty() {
+            let config_path = project_root.join(next_config_ref.config_file_name.clone());
+            // Budgets are meant to fail CI, so exceeding them is an error even
+            // though other errors don't fail the build yet.
+            handle_issues(
+                emit_bundle_budget_issues(check_bundle_budgets(
+                    &bundle_budgets,
+                    &build_stats,
+                    config_path,
+                )),
+                issue_reporter,
+                IssueSeverity::Error.cell(),
+                None,
+                None,
+            )
+            .await?;
+        }
+
+        if options.full_stats {
+            completions.push(write_manifest(
+                build_stats,
+                node_root.join("build-stats.json".to_string()),
+            )?);
+        }
+    }
+
+    let output_standalone = next_config_ref.output == Some(OutputType::Standalone);
+    if next_config_ref.output_file_tracing || output_standalone {
+        let experimental = &next_config_ref.experimental;
+        let (tracing_root, project_relative) = get_tracing_root(
+            &project_dir,
+            experimental.output_file_tracing_root.as_deref(),
+        )?;
+        let tracing_root = tracing_fs(tracing_root, issue_reporter).root();
+        let includes = experimental
+            .output_file_tracing_includes
+            .clone()
+            .unwrap_or_default();
+        completions.push(
+            trace_server_files(
+                all_chunks.clone(),
+                node_root,
+                &pages_manifest,
+                &app_paths_manifest,
+                FileTracingOptions {
+                    tracing_root,
+                    project_root: tracing_root.join(project_relative),
+                    includes: &includes,
+                    ignores: experimental
+                        .output_file_tracing_ignores
+                        .as_deref()
+                        .unwrap_or_default(),
+                },
+            )
+            .await?,
+        );
+    }
+
     completions.push(write_manifest(pages_manifest, pages_manifest_path)?);
     completions.push(write_manifest(app_build_manifest, app_build_manifest_path)?);
     completions.push(write_manifest(app_paths_manifest, app_paths_manifest_path)?);
     completions.push(write_manifest(build_manifest, build_manifest_path)?);
-
-    // Placeholder manifests.
-
-    // TODO(alexkirsz) Proper middleware manifest with all (edge?) routes in it,
-    // experimental-edge pages?
-    completions.push(write_manifest(
-        MiddlewaresManifest::default(),
-        node_root.join("server/middleware-manifest.json".to_string()),
-    )?);
+    completions.push(write_manifest(next_font_manifest, next_font_manifest_path)?);
     completions.push(write_manifest(
-        NextFontManifest::default(),
-        node_root.join("server/next-font-manifest.json".to_string()),
+        MiddlewaresManifest::MiddlewaresManifestV2(middlewares_manifest),
+        middlewares_manifest_path,
     )?);
     completions.push(write_manifest(
-        FontManifest::default(),
-        node_root.join("server/font-manifest.json".to_string()),
+        react_loadable_manifest,
+        react_loadable_manifest_path,
     )?);
     completions.push(write_manifest(
-        ServerReferenceManifest::default(),
-        node_root.join("server/server-reference-manifest.json".to_string()),
+        server_reference_manifest,
+        server_reference_manifest_path,
     )?);
+
+    // Placeholder manifests.
     completions.push(write_manifest(
-        ReactLoadableManifest::default(),
-        node_root.join("react-loadable-manifest.json".to_string()),
+        FontManifest::default(),
+        node_root.join("server/font-manifest.json".to_string()),
     )?);
 
     completions.push(
@@ -471,6 +642,75 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     Ok(Completions::all(completions))
 }
 
+/// Fills the Google Fonts cache with the fonts used by the project.
+#[turbo_tasks::function]
+pub(crate) async fn next_prefetch_google_fonts(
+    project_root: String,
+    workspace_root: String,
+) -> Result<Vc<Completion>> {
+    let log_options = LogOptions {
+        project_dir: PathBuf::from(project_root.clone()),
+        current_dir: current_dir().unwrap(),
+        show_all: true,
+        log_detail: true,
+        log_level: IssueSeverity::Warning,
+    };
+    let issue_reporter: Vc<Box<dyn IssueReporter>> =
+        Vc::upcast(ConsoleUi::new(TransientInstance::new(log_options)));
+    let workspace_fs = workspace_fs(workspace_root.clone(), issue_reporter);
+    let project_root = workspace_fs
+        .root()
+        .join(project_relative_path(&project_root, &workspace_root));
+
+    // Unlike in builds, fonts that can't be fetched fail the prefetch.
+    handle_issues(
+        prefetch_google_fonts(project_root),
+        issue_reporter,
+        IssueSeverity::Warning.cell(),
+        None,
+        None,
+    )
+    .await?;
+
+    Ok(Completion::new())
+}
+
+/// Returns the canonicalized project directory and workspace root. The
+/// workspace root defaults to the project directory.
+pub(crate) fn resolve_roots(
+    dir: Option<&PathBuf>,
+    root: Option<&PathBuf>,
+) -> Result<(String, String)> {
+    let project_root = dir
+        .map(canonicalize)
+        .unwrap_or_else(current_dir)
+        .context("project directory can't be found")?
+        .to_str()
+        .context("project directory contains invalid characters")?
+        .to_string();
+
+    let workspace_root = if let Some(root) = root {
+        canonicalize(root)
+            .context("root directory can't be found")?
+            .to_str()
+            .context("root directory contains invalid characters")?
+            .to_string()
+    } else {
+        project_root.clone()
+    };
+
+    Ok((project_root, workspace_root))
+}
+
+/// The path of the project directory in the workspace file system.
+fn project_relative_path(project_root: &str, workspace_root: &str) -> String {
+    let project_relative = project_root.strip_prefix(workspace_root).unwrap();
+    project_relative
+        .strip_prefix(MAIN_SEPARATOR)
+        .unwrap_or(project_relative)
+        .replace(MAIN_SEPARATOR, "/")
+}
+
 #[turbo_tasks::function]
 async fn workspace_fs(
     workspace_root: String,
@@ -488,6 +728,23 @@ async fn workspace_fs(
     Ok(Vc::upcast(disk_fs))
 }
 
+#[turbo_tasks::function]
+async fn tracing_fs(
+    tracing_root: String,
+    issue_reporter: Vc<Box<dyn IssueReporter>>,
+) -> Result<Vc<Box<dyn FileSystem>>> {
+    let disk_fs = DiskFileSystem::new("tracing".to_string(), tracing_root);
+    handle_issues(
+        disk_fs,
+        issue_reporter,
+        MIN_FAILING_SEVERITY.cell(),
+        None,
+        None,
+    )
+    .await?;
+    Ok(Vc::upcast(disk_fs))
+}
+
 #[turbo_tasks::function]
 async fn node_fs(
     node_root: String,
@@ -575,7 +832,7 @@ fn emit_rebase(
 /// Walks the asset graph from multiple assets and collect all referenced
 /// assets.
 #[turbo_tasks::function]
-async fn all_assets_from_entries(entries: Vc<OutputAssets>) -> Result<Vc<OutputAssets>> {
+pub(crate) async fn all_assets_from_entries(entries: Vc<OutputAssets>) -> Result<Vc<OutputAssets>> {
     Ok(Vc::cell(
         AdjacencyMap::new()
             .skip_duplicates()
@@ -606,7 +863,10 @@ async fn get_referenced_assets(
 
 /// Writes a manifest to disk. This consumes the manifest to ensure we don't
 /// write to it afterwards.
-fn write_manifest<T>(manifest: T, manifest_path: Vc<FileSystemPath>) -> Result<Vc<Completion>>
+pub(crate) fn write_manifest<T>(
+    manifest: T,
+    manifest_path: Vc<FileSystemPath>,
+) -> Result<Vc<Completion>>
 where
     T: Serialize,
 {
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use next_core::{
+    next_dynamic::NextDynamicEntries,
+    next_manifests::{ReactLoadableManifest, ReactLoadableManifestEntry},
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{chunk::ChunkingContext, output::OutputAsset},
+};
+
+/// Computes the client chunks of `next/dynamic` imports, adds them to the
+/// React Loadable Manifest, and pushes the assets to the `all_chunks` vec.
+///
+/// The manifest is keyed by the ids the dynamic entry modules get in
+/// `server_chunking_context`, which the server-side `next/dynamic` transform
+/// imports, so that server rendering can preload the chunks of the dynamic
+/// imports it encountered.
+pub(crate) async fn compute_dynamic_entries_chunks(
+    dynamic_entries: Vc<NextDynamicEntries>,
+    client_chunking_context: Vc<Box<dyn ChunkingContext>>,
+    server_chunking_context: Vc<Box<dyn ChunkingContext>>,
+    client_relative_path: &FileSystemPath,
+    react_loadable_manifest: &mut ReactLoadableManifest,
+    all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
+) -> Result<()> {
+    for &dynamic_entry in dynamic_entries.await?.iter() {
+        let client_chunks = dynamic_entry.client_chunks(client_chunking_context).await?;
+        all_chunks.extend(client_chunks.iter().copied());
+
+        let mut files = vec![];
+        for chunk in client_chunks.iter() {
+            let chunk_path = chunk.ident().path().await?;
+            if let Some(file) = client_relative_path.get_path_to(&chunk_path) {
+                files.push(file.to_string());
+            }
+        }
+
+        let client_module_id = dynamic_entry
+            .client_module_id(client_chunking_context)
+            .await?;
+        react_loadable_manifest.manifest.insert(
+            dynamic_entry
+                .manifest_key(server_chunking_context)
+                .await?
+                .clone_value(),
+            ReactLoadableManifestEntry {
+                id: (&*client_module_id).into(),
+                files,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::{
+        next_dynamic::NextDynamicEntryModule, next_server::get_server_chunking_context,
+    };
+    use turbo_tasks::{Value, ValueToString, Vc};
+    use turbopack_binding::{
+        turbo::{
+            tasks::{run_once, TurboTasks},
+            tasks_fs::{FileSystem, VirtualFileSystem},
+            tasks_memory::MemoryBackend,
+        },
+        turbopack::core::{
+            chunk::{ChunkItemExt, ChunkableModule},
+            environment::{Environment, ExecutionEnvironment, NodeJsEnvironment},
+            file_source::FileSource,
+            module::Module,
+            raw_module::RawModule,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_manifest_key_is_server_module_id() {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        run_once(tt, async move {
+            let fs = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new());
+            let project_root = fs.root();
+            let node_root = project_root.join(".next".to_string());
+            let client_root = project_root.join(".next/static".to_string());
+
+            let server_chunking_context = get_server_chunking_context(
+                project_root,
+                node_root,
+                client_root,
+                Vc::cell(None),
+                Environment::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+                    NodeJsEnvironment::default().into(),
+                ))),
+            );
+            let ssr_chunking_context = server_chunking_context.with_layer("ssr".to_string());
+
+            let client_module = RawModule::new(Vc::upcast(FileSource::new(
+                project_root.join("components/hello.js".to_string()),
+            )));
+            let dynamic_entry = NextDynamicEntryModule::new(Vc::upcast(client_module));
+
+            // The server-side `next/dynamic` transform imports the id of the
+            // marker module as it is chunked for the server bundle.
+            let server_module_id = Vc::upcast::<Box<dyn ChunkableModule>>(dynamic_entry)
+                .as_chunk_item(Vc::upcast(ssr_chunking_context))
+                .id()
+                .to_string()
+                .await?;
+            let manifest_key = dynamic_entry
+                .manifest_key(Vc::upcast(ssr_chunking_context))
+                .await?;
+            assert_eq!(*manifest_key, *server_module_id);
+
+            // The id of the client module is reported by the client instead.
+            let client_module_ident = client_module.ident().to_string().await?;
+            assert_ne!(*manifest_key, *client_module_ident);
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use next_core::NextFontFileFlags;
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{asset::Asset, output::OutputAsset},
+};
+
+use crate::next_build::all_assets_from_entries;
+
+/// The font file extensions that are considered for preloading. This matches
+/// the webpack `NextFontManifestPlugin`.
+const FONT_EXTENSIONS: &[&str] = &["woff", "woff2", "eot", "ttf", "otf"];
+
+/// The font files referenced by the stylesheets of a page or app entry.
+#[derive(Default, Debug)]
+pub(crate) struct EntryFontFiles {
+    /// Paths of the preloaded font files, relative to the client root.
+    pub font_files: Vec<String>,
+    /// Whether any font file has a `size-adjust`ed fallback font.
+    pub using_size_adjust: bool,
+}
+
+/// Collects the font files transitively referenced by the given client chunks.
+/// The `next/font` stylesheets end up in the CSS chunks of an entry, and their
+/// `url()`s in turn reference the emitted font files, whose names carry the
+/// [NextFontFileFlags].
+pub(crate) async fn get_entry_font_files(
+    chunks: Vec<Vc<Box<dyn OutputAsset>>>,
+    client_relative_path: &FileSystemPath,
+) -> Result<EntryFontFiles> {
+    let mut entry_font_files = EntryFontFiles::default();
+
+    for asset in all_assets_from_entries(Vc::cell(chunks)).await?.iter() {
+        let asset_path = asset.ident().path().await?;
+        if !matches!(asset_path.extension_ref(), Some(ext) if FONT_EXTENSIONS.contains(&ext)) {
+            continue;
+        }
+        let Some(font_file) = client_relative_path.get_path_to(&asset_path) else {
+            continue;
+        };
+
+        let flags = NextFontFileFlags::from_file_name(asset_path.file_name());
+        entry_font_files.using_size_adjust |= flags.has_size_adjust;
+        if flags.preload {
+            entry_font_files.font_files.push(font_file.to_string());
+        }
+    }
+
+    entry_font_files.font_files.sort();
+    entry_font_files.font_files.dedup();
+
+    Ok(entry_font_files)
+}
@@ -0,0 +1,376 @@
+use anyhow::{bail, Context, Result};
+use next_core::{
+    get_edge_resolve_options_context,
+    middleware::{get_middleware_module, middleware_files},
+    mode::NextMode,
+    next_config::{I18NConfig, NextConfig},
+    next_edge::{asset_bindings::get_edge_asset_bindings, entry::wrap_edge_entry},
+    next_manifests::{EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2},
+    next_server::{
+        get_server_module_options_context, get_server_runtime_entries, ServerContextType,
+    },
+    util::{parse_config_from_source, MiddlewareMatcherConfig},
+};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            chunk::{ChunkingContext, EvaluatableAsset, EvaluatableAssets},
+            compile_time_info::CompileTimeInfo,
+            context::AssetContext,
+            file_source::FileSource,
+            issue::{Issue, IssueExt, IssueSeverity},
+            output::OutputAsset,
+            reference_type::{EntryReferenceSubType, ReferenceType},
+            resolve::{find_context_file, FindContextFileResult},
+        },
+        ecmascript::chunk::{EcmascriptChunkPlaceable, EcmascriptChunkingContext},
+        node::execution_context::ExecutionContext,
+        turbopack::ModuleAssetContext,
+    },
+};
+
+use super::path_to_regexp::path_to_regexp_source;
+
+/// The middleware of the project, compiled for the edge runtime.
+#[turbo_tasks::value]
+pub struct MiddlewareEntry {
+    /// The edge entry module wrapping the userland middleware.
+    pub module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    /// The runtime entries that should be evaluated before the middleware.
+    pub runtime_entries: Vc<EvaluatableAssets>,
+    /// The compiled `config.matcher` of the userland middleware, if any.
+    pub matchers: Option<Vec<CompiledMiddlewareMatcher>>,
+}
+
+/// A middleware matcher with the regular expression Next.js matches requests
+/// against.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct CompiledMiddlewareMatcher {
+    pub regexp: String,
+    pub config: MiddlewareMatcherConfig,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionMiddlewareEntry(Option<Vc<MiddlewareEntry>>);
+
+/// Finds the `middleware` file of the project, if any, and creates its edge
+/// entry module.
+#[turbo_tasks::function]
+pub async fn get_middleware_entry(
+    project_root: Vc<FileSystemPath>,
+    execution_context: Vc<ExecutionContext>,
+    edge_compile_time_info: Vc<CompileTimeInfo>,
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<OptionMiddlewareEntry>> {
+    let FindContextFileResult::Found(middleware_path, _) = *find_context_file(
+        project_root,
+        middleware_files(next_config.page_extensions()),
+    )
+    .await?
+    else {
+        return Ok(Vc::cell(None));
+    };
+
+    let mode = NextMode::Build;
+    let ty = Value::new(ServerContextType::Middleware);
+
+    let context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
+        Default::default(),
+        edge_compile_time_info,
+        get_server_module_options_context(project_root, execution_context, ty, mode, next_config),
+        get_edge_resolve_options_context(project_root, ty, mode, next_config, execution_context),
+    ));
+
+    let userland_module = context.process(
+        Vc::upcast(FileSource::new(middleware_path)),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Middleware)),
+    );
+
+    let module = get_middleware_module(context, project_root, userland_module);
+    let module = wrap_edge_entry(context, project_root, module, "middleware".to_string());
+
+    let Some(module) =
+        Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+    else {
+        bail!("Entry module must be evaluatable");
+    };
+
+    let runtime_entries = get_server_runtime_entries(ty, mode).resolve_entries(context);
+
+    let next_config = next_config.await?;
+    let matchers = parse_config_from_source(userland_module)
+        .await?
+        .matcher
+        .as_ref()
+        .map(|matchers| {
+            matchers
+                .iter()
+                .filter_map(|matcher| {
+                    match compile_middleware_matcher(
+                        &matcher.source,
+                        matcher.locale,
+                        next_config.i18n.as_ref(),
+                        next_config.base_path.as_deref(),
+                    ) {
+                        Ok(regexp) => Some(CompiledMiddlewareMatcher {
+                            regexp,
+                            config: matcher.clone(),
+                        }),
+                        Err(err) => {
+                            MiddlewareMatcherIssue {
+                                path: middleware_path,
+                                source: matcher.source.clone(),
+                                error: format!("{err:#}"),
+                            }
+                            .cell()
+                            .emit();
+                            None
+                        }
+                    }
+                })
+                .collect()
+        });
+
+    Ok(Vc::cell(Some(
+        MiddlewareEntry {
+            module,
+            runtime_entries,
+            matchers,
+        }
+        .cell(),
+    )))
+}
+
+/// Computes the chunks of the middleware entry, adds it to the middleware
+/// manifest, and pushes the assets to the `all_chunks` vec.
+pub async fn compute_middleware_entry_chunks(
+    middleware_entry: &MiddlewareEntry,
+    middleware_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    node_root: Vc<FileSystemPath>,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
+    all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
+) -> Result<()> {
+    let Some(evaluatable) =
+        Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(middleware_entry.module).await?
+    else {
+        bail!("Entry module must be evaluatable");
+    };
+
+    let files = middleware_chunking_context.evaluated_chunk_group(
+        middleware_entry.module.ident(),
+        middleware_entry.runtime_entries.with_entry(evaluatable),
+    );
+    let bindings = get_edge_asset_bindings(files, node_root).await?;
+    let files = files.await?;
+    all_chunks.extend(files.iter().copied());
+    all_chunks.extend(bindings.output_assets);
+
+    let matchers = match &middleware_entry.matchers {
+        Some(matchers) => matchers
+            .iter()
+            .map(
+                |CompiledMiddlewareMatcher { regexp, config }| MiddlewareMatcher {
+                    regexp: Some(regexp.clone()),
+                    locale: config.locale,
+                    has: (!config.has.is_empty()).then(|| config.has.clone()),
+                    missing: (!config.missing.is_empty()).then(|| config.missing.clone()),
+                    original_source: config.source.clone(),
+                },
+            )
+            .collect(),
+        None => vec![MiddlewareMatcher {
+            regexp: Some("^/.*$".to_string()),
+            locale: true,
+            original_source: "/:path*".to_string(),
+            ..Default::default()
+        }],
+    };
+
+    middlewares_manifest.sorted_middleware.push("/".to_string());
+    middlewares_manifest.middleware.insert(
+        "/".to_string(),
+        EdgeFunctionDefinition {
+            files: get_edge_files_paths(&files, &*node_root.await?).await?,
+            name: "middleware".to_string(),
+            page: "/".to_string(),
+            matchers,
+            wasm: Some(bindings.wasm),
+            assets: Some(bindings.assets),
+            regions: None,
+        },
+    );
+
+    Ok(())
+}
+
+/// Returns the paths of edge chunks relative to the node root, which is how
+/// the middleware manifest references them.
+pub(crate) async fn get_edge_files_paths(
+    files: &[Vc<Box<dyn OutputAsset>>],
+    node_root: &FileSystemPath,
+) -> Result<Vec<String>> {
+    files
+        .iter()
+        .map(|&file| async move {
+            Ok(node_root
+                .get_path_to(&*file.ident().path().await?)
+                .context("edge file path must be inside the node root")?
+                .to_string())
+        })
+        .try_join()
+        .await
+}
+
+/// Returns the chunk of an edge chunk group which evaluates its entries. The
+/// chunking context emits it after the chunks it loads, so it is the last
+/// JavaScript chunk of the group.
+pub(crate) async fn get_edge_entry_chunk(
+    files: &[Vc<Box<dyn OutputAsset>>],
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    for &file in files.iter().rev() {
+        if file.ident().path().await?.extension_ref() == Some("js") {
+            return Ok(file);
+        }
+    }
+    bail!("edge chunk group must contain an entry chunk")
+}
+
+/// Compiles the source of a middleware matcher to the regular expression
+/// Next.js matches requests against, like `getMiddlewareMatchers` of
+/// `next/dist/build/analysis/get-page-static-info`.
+fn compile_middleware_matcher(
+    source: &str,
+    locale: bool,
+    i18n: Option<&I18NConfig>,
+    base_path: Option<&str>,
+) -> Result<String> {
+    if !source.starts_with('/') {
+        bail!("`source` does not start with /");
+    }
+
+    let is_root = source == "/";
+    let mut source = source.to_string();
+
+    if i18n.is_some() && locale {
+        source = format!(
+            "/:nextInternalLocale((?!_next/)[^/.]{{1,}}){}",
+            if is_root { "" } else { &source }
+        );
+    }
+
+    source = format!(
+        "/:nextData(_next/data/[^/]{{1,}})?{source}{}",
+        if is_root {
+            format!(
+                "({}/?index|/?index\\.json)?",
+                if i18n.is_some() { "|\\.json|" } else { "" }
+            )
+        } else {
+            "(.json)?".to_string()
+        }
+    );
+
+    if let Some(base_path) = base_path {
+        source = format!("{base_path}{source}");
+    }
+
+    path_to_regexp_source(&source).with_context(|| format!("Invalid source: {source}"))
+}
+
+/// A `config.matcher` of the middleware that can't be compiled.
+#[turbo_tasks::value(shared)]
+struct MiddlewareMatcherIssue {
+    path: Vc<FileSystemPath>,
+    source: String,
+    error: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for MiddlewareMatcherIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Invalid middleware matcher `{}`, the middleware won't run for it",
+            self.source
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.error.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::next_config::I18NConfig;
+
+    use super::compile_middleware_matcher;
+
+    #[test]
+    fn test_named_parameters() {
+        assert_eq!(
+            compile_middleware_matcher("/about/:path*", true, None, None).unwrap(),
+            r"^(?:\/(_next\/data\/[^/]{1,}))?\/about(?:\/((?:[^\/#\?]+?)(?:\/(?:[^\/#\?]+?))*))?(.json)?[\/#\?]?$"
+        );
+    }
+
+    #[test]
+    fn test_negative_lookahead() {
+        assert_eq!(
+            compile_middleware_matcher("/((?!api|_next/static).*)", true, None, None).unwrap(),
+            r"^(?:\/(_next\/data\/[^/]{1,}))?(?:\/((?!api|_next\/static).*))(.json)?[\/#\?]?$"
+        );
+    }
+
+    #[test]
+    fn test_root() {
+        assert_eq!(
+            compile_middleware_matcher("/", true, None, None).unwrap(),
+            r"^(?:\/(_next\/data\/[^/]{1,}))?(?:\/(\/?index|\/?index\.json))?[\/#\?]?$"
+        );
+    }
+
+    #[test]
+    fn test_locale_and_base_path() {
+        let i18n = I18NConfig {
+            default_locale: "en".to_string(),
+            domains: None,
+            locale_detection: None,
+            locales: vec!["en".to_string(), "de".to_string()],
+        };
+        assert_eq!(
+            compile_middleware_matcher("/about", true, Some(&i18n), Some("/docs")).unwrap(),
+            r"^\/docs(?:\/(_next\/data\/[^/]{1,}))?(?:\/((?!_next\/)[^/.]{1,}))\/about(.json)?[\/#\?]?$"
+        );
+        assert_eq!(
+            compile_middleware_matcher("/about", false, Some(&i18n), None).unwrap(),
+            r"^(?:\/(_next\/data\/[^/]{1,}))?\/about(.json)?[\/#\?]?$"
+        );
+    }
+
+    #[test]
+    fn test_invalid_source() {
+        assert!(compile_middleware_matcher("about", true, None, None).is_err());
+        assert!(compile_middleware_matcher("/(api)/:path*", true, None, None).is_ok());
+        assert!(compile_middleware_matcher("/((api))", true, None, None).is_err());
+    }
+}
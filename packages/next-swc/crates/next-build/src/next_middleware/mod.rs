@@ -0,0 +1,2 @@
+pub(crate) mod middleware_entry;
+mod path_to_regexp;
@@ -0,0 +1,394 @@
+//! A port of the parts of `path-to-regexp` v6.1.0, which Next.js compiles
+//! middleware matchers with, to compile them at build time. Only `parse` and
+//! `tokensToRegexp` with their default options are supported, which is what
+//! `tryToParsePath` of `next/dist/lib/try-to-parse-path` uses.
+
+use anyhow::{bail, Result};
+
+const DEFAULT_DELIMITER: &str = "/#?";
+const DEFAULT_PREFIXES: &str = "./";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LexTokenKind {
+    Modifier,
+    EscapedChar,
+    Open,
+    Close,
+    Name,
+    Pattern,
+    Char,
+    End,
+}
+
+impl LexTokenKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            LexTokenKind::Modifier => "MODIFIER",
+            LexTokenKind::EscapedChar => "ESCAPED_CHAR",
+            LexTokenKind::Open => "OPEN",
+            LexTokenKind::Close => "CLOSE",
+            LexTokenKind::Name => "NAME",
+            LexTokenKind::Pattern => "PATTERN",
+            LexTokenKind::Char => "CHAR",
+            LexTokenKind::End => "END",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LexToken {
+    kind: LexTokenKind,
+    index: usize,
+    value: String,
+}
+
+fn lexer(source: &str) -> Result<Vec<LexToken>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    let mut push = |kind, index, value: String| tokens.push(LexToken { kind, index, value });
+
+    while i < chars.len() {
+        let char = chars[i];
+        match char {
+            '*' | '+' | '?' => {
+                push(LexTokenKind::Modifier, i, char.to_string());
+                i += 1;
+            }
+            '\\' => {
+                let value = chars.get(i + 1).map(|c| c.to_string()).unwrap_or_default();
+                push(LexTokenKind::EscapedChar, i, value);
+                i += 2;
+            }
+            '{' => {
+                push(LexTokenKind::Open, i, char.to_string());
+                i += 1;
+            }
+            '}' => {
+                push(LexTokenKind::Close, i, char.to_string());
+                i += 1;
+            }
+            ':' => {
+                let name: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                    .collect();
+                if name.is_empty() {
+                    bail!("Missing parameter name at {}", i);
+                }
+                let next = i + 1 + name.len();
+                push(LexTokenKind::Name, i, name);
+                i = next;
+            }
+            '(' => {
+                let mut count = 1;
+                let mut pattern = String::new();
+                let mut j = i + 1;
+
+                if chars.get(j) == Some(&'?') {
+                    bail!("Pattern cannot start with \"?\" at {}", j);
+                }
+
+                while j < chars.len() {
+                    if chars[j] == '\\' {
+                        pattern.extend(chars[j..].iter().take(2));
+                        j += 2;
+                        continue;
+                    }
+
+                    if chars[j] == ')' {
+                        count -= 1;
+                        if count == 0 {
+                            j += 1;
+                            break;
+                        }
+                    } else if chars[j] == '(' {
+                        count += 1;
+                        if chars.get(j + 1) != Some(&'?') {
+                            bail!("Capturing groups are not allowed at {}", j);
+                        }
+                    }
+
+                    pattern.push(chars[j]);
+                    j += 1;
+                }
+
+                if count > 0 {
+                    bail!("Unbalanced pattern at {}", i);
+                }
+                if pattern.is_empty() {
+                    bail!("Missing pattern at {}", i);
+                }
+
+                push(LexTokenKind::Pattern, i, pattern);
+                i = j;
+            }
+            _ => {
+                push(LexTokenKind::Char, i, char.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    push(LexTokenKind::End, i, String::new());
+
+    Ok(tokens)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Key {
+        prefix: String,
+        suffix: String,
+        pattern: String,
+        modifier: String,
+    },
+}
+
+struct Parser {
+    tokens: Vec<LexToken>,
+    i: usize,
+}
+
+impl Parser {
+    fn try_consume(&mut self, kind: LexTokenKind) -> Option<String> {
+        let value = self
+            .tokens
+            .get(self.i)
+            .filter(|token| token.kind == kind)?
+            .value
+            .clone();
+        self.i += 1;
+        Some(value)
+    }
+
+    fn must_consume(&mut self, kind: LexTokenKind) -> Result<String> {
+        if let Some(value) = self.try_consume(kind) {
+            return Ok(value);
+        }
+        let token = &self.tokens[self.i];
+        bail!(
+            "Unexpected {} at {}, expected {}",
+            token.kind.as_str(),
+            token.index,
+            kind.as_str()
+        )
+    }
+
+    fn consume_text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(value) = self
+            .try_consume(LexTokenKind::Char)
+            .or_else(|| self.try_consume(LexTokenKind::EscapedChar))
+        {
+            text.push_str(&value);
+        }
+        text
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Token>> {
+    let mut parser = Parser {
+        tokens: lexer(source)?,
+        i: 0,
+    };
+    let default_pattern = format!("[^{}]+?", escape_string(DEFAULT_DELIMITER));
+    let mut result = vec![];
+    let mut path = String::new();
+
+    while parser.i < parser.tokens.len() {
+        let char = parser.try_consume(LexTokenKind::Char);
+        let name = parser.try_consume(LexTokenKind::Name);
+        let pattern = parser.try_consume(LexTokenKind::Pattern);
+
+        if name.is_some() || pattern.is_some() {
+            let mut prefix = char.unwrap_or_default();
+
+            if !prefix.is_empty() && !DEFAULT_PREFIXES.contains(prefix.as_str()) {
+                path.push_str(&prefix);
+                prefix = String::new();
+            }
+
+            if !path.is_empty() {
+                result.push(Token::Text(std::mem::take(&mut path)));
+            }
+
+            result.push(Token::Key {
+                prefix,
+                suffix: String::new(),
+                pattern: pattern.unwrap_or_else(|| default_pattern.clone()),
+                modifier: parser
+                    .try_consume(LexTokenKind::Modifier)
+                    .unwrap_or_default(),
+            });
+            continue;
+        }
+
+        if let Some(value) = char.or_else(|| parser.try_consume(LexTokenKind::EscapedChar)) {
+            path.push_str(&value);
+            continue;
+        }
+
+        if !path.is_empty() {
+            result.push(Token::Text(std::mem::take(&mut path)));
+        }
+
+        if parser.try_consume(LexTokenKind::Open).is_some() {
+            let prefix = parser.consume_text();
+            let name = parser.try_consume(LexTokenKind::Name);
+            let pattern = parser.try_consume(LexTokenKind::Pattern);
+            let suffix = parser.consume_text();
+
+            parser.must_consume(LexTokenKind::Close)?;
+
+            result.push(Token::Key {
+                pattern: match (name, pattern) {
+                    (Some(_), None) => default_pattern.clone(),
+                    (_, pattern) => pattern.unwrap_or_default(),
+                },
+                prefix,
+                suffix,
+                modifier: parser
+                    .try_consume(LexTokenKind::Modifier)
+                    .unwrap_or_default(),
+            });
+            continue;
+        }
+
+        parser.must_consume(LexTokenKind::End)?;
+    }
+
+    Ok(result)
+}
+
+fn tokens_to_regexp(tokens: &[Token]) -> String {
+    let mut route = "^".to_string();
+
+    for token in tokens {
+        match token {
+            Token::Text(text) => route.push_str(&escape_string(text)),
+            Token::Key {
+                prefix,
+                suffix,
+                pattern,
+                modifier,
+            } => {
+                let prefix = escape_string(prefix);
+                let suffix = escape_string(suffix);
+                let repeated = modifier == "+" || modifier == "*";
+
+                route.push_str(&if pattern.is_empty() {
+                    format!("(?:{prefix}{suffix}){modifier}")
+                } else if !prefix.is_empty() || !suffix.is_empty() {
+                    if repeated {
+                        let modifier = if modifier == "*" { "?" } else { "" };
+                        format!(
+                            "(?:{prefix}((?:{pattern})(?:{suffix}{prefix}(?:{pattern}))*\
+                             ){suffix}){modifier}"
+                        )
+                    } else {
+                        format!("(?:{prefix}({pattern}){suffix}){modifier}")
+                    }
+                } else if repeated {
+                    format!("((?:{pattern}){modifier})")
+                } else {
+                    format!("({pattern}){modifier}")
+                });
+            }
+        }
+    }
+
+    // Not strict, so a trailing delimiter is allowed.
+    route.push_str(&format!("[{}]?$", escape_string(DEFAULT_DELIMITER)));
+
+    route
+}
+
+fn escape_string(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for char in str.chars() {
+        if ".+*?=^!:${}()[]|/\\".contains(char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// Escapes the forward slashes of a regular expression outside of character
+/// classes, like the `source` of a JavaScript `RegExp` does.
+fn to_regexp_source(regexp: &str) -> String {
+    let mut source = String::with_capacity(regexp.len());
+    let mut chars = regexp.chars();
+    let mut in_class = false;
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => {
+                source.push(char);
+                if let Some(escaped) = chars.next() {
+                    source.push(escaped);
+                }
+                continue;
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => source.push('\\'),
+            _ => {}
+        }
+        source.push(char);
+    }
+    source
+}
+
+/// Compiles a path-to-regexp source to the `source` of the regular expression
+/// Next.js would create from it.
+pub(crate) fn path_to_regexp_source(source: &str) -> Result<String> {
+    Ok(to_regexp_source(&tokens_to_regexp(&parse(source)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::path_to_regexp_source;
+
+    #[test]
+    fn test_named_parameters() {
+        assert_eq!(
+            path_to_regexp_source("/about/:path*").unwrap(),
+            r"^\/about(?:\/((?:[^\/#\?]+?)(?:\/(?:[^\/#\?]+?))*))?[\/#\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp_source("/blog/:slug").unwrap(),
+            r"^\/blog(?:\/([^\/#\?]+?))[\/#\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp_source("/docs/:rest+").unwrap(),
+            r"^\/docs(?:\/((?:[^\/#\?]+?)(?:\/(?:[^\/#\?]+?))*))[\/#\?]?$"
+        );
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        assert_eq!(
+            path_to_regexp_source("/((?!api|_next/static).*)").unwrap(),
+            r"^(?:\/((?!api|_next\/static).*))[\/#\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp_source("/:id(\\d+)").unwrap(),
+            r"^(?:\/(\d+))[\/#\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp_source("/files{-:name}?").unwrap(),
+            r"^\/files(?:-([^\/#\?]+?))?[\/#\?]?$"
+        );
+    }
+
+    #[test]
+    fn test_invalid_sources() {
+        assert!(path_to_regexp_source("/((api))").is_err());
+        assert!(path_to_regexp_source("/(unbalanced").is_err());
+        assert!(path_to_regexp_source("/:").is_err());
+        assert!(path_to_regexp_source("/*").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{bail, Result};
 use next_core::{
     create_page_loader_entry_module, get_asset_path_from_pathname,
+    get_edge_resolve_options_context,
     mode::NextMode,
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
@@ -8,7 +9,14 @@ use next_core::{
     },
     next_config::NextConfig,
     next_dynamic::NextDynamicTransition,
-    next_manifests::{BuildManifest, PagesManifest},
+    next_edge::{
+        asset_bindings::get_edge_asset_bindings, entry::pages_edge_function_name,
+        route_regex::get_named_middleware_regex,
+    },
+    next_manifests::{
+        BuildManifest, EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2,
+        NextFontManifest, PagesManifest,
+    },
     next_pages::create_page_ssr_entry_module,
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
@@ -18,7 +26,7 @@ use next_core::{
         find_pages_structure, PagesDirectoryStructure, PagesStructure, PagesStructureItem,
     },
     pathname_for_path,
-    util::NextRuntime,
+    util::{is_ssg_page, parse_config_from_source, NextRuntime},
     PathType,
 };
 use turbo_tasks::Vc;
@@ -27,7 +35,7 @@ use turbopack_binding::{
     turbopack::{
         build::BuildChunkingContext,
         core::{
-            chunk::{ChunkingContext, EvaluatableAssets},
+            chunk::{ChunkingContext, EvaluatableAsset, EvaluatableAssets},
             compile_time_info::CompileTimeInfo,
             context::AssetContext,
             file_source::FileSource,
@@ -45,10 +53,16 @@ use turbopack_binding::{
     },
 };
 
+use crate::{
+    next_font::get_entry_font_files,
+    next_middleware::middleware_entry::{get_edge_entry_chunk, get_edge_files_paths},
+};
+
 #[turbo_tasks::value]
 pub struct PageEntries {
     pub entries: Vec<Vc<PageEntry>>,
     pub ssr_runtime_entries: Vc<EvaluatableAssets>,
+    pub edge_ssr_runtime_entries: Vc<EvaluatableAssets>,
     pub client_runtime_entries: Vc<EvaluatableAssets>,
 }
 
@@ -60,6 +74,7 @@ pub async fn get_page_entries(
     execution_context: Vc<ExecutionContext>,
     client_compile_time_info: Vc<CompileTimeInfo>,
     server_compile_time_info: Vc<CompileTimeInfo>,
+    edge_compile_time_info: Vc<CompileTimeInfo>,
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<PageEntries>> {
     let pages_structure = find_pages_structure(
@@ -149,11 +164,29 @@ pub async fn get_page_entries(
         ssr_resolve_options_context,
     ));
 
+    // Pages exporting `runtime = 'edge'` are built for the edge runtime instead.
+    let edge_ssr_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        ssr_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_ssr_module_context = Vc::upcast(ModuleAssetContext::new(
+        Default::default(),
+        edge_compile_time_info,
+        ssr_module_options_context,
+        edge_ssr_resolve_options_context,
+    ));
+
     let ssr_runtime_entries = get_server_runtime_entries(ssr_ty, mode);
+    let edge_ssr_runtime_entries = ssr_runtime_entries.resolve_entries(edge_ssr_module_context);
     let ssr_runtime_entries = ssr_runtime_entries.resolve_entries(ssr_module_context);
 
     let entries = get_page_entries_for_root_directory(
         ssr_module_context,
+        edge_ssr_module_context,
         client_module_context,
         pages_structure,
         project_root,
@@ -164,6 +197,7 @@ pub async fn get_page_entries(
     Ok(PageEntries {
         entries,
         ssr_runtime_entries,
+        edge_ssr_runtime_entries,
         client_runtime_entries,
     }
     .cell())
@@ -171,6 +205,7 @@ pub async fn get_page_entries(
 
 async fn get_page_entries_for_root_directory(
     ssr_module_context: Vc<Box<dyn AssetContext>>,
+    edge_ssr_module_context: Vc<Box<dyn AssetContext>>,
     client_module_context: Vc<Box<dyn AssetContext>>,
     pages_structure: Vc<PagesStructure>,
     project_root: Vc<FileSystemPath>,
@@ -190,6 +225,7 @@ async fn get_page_entries_for_root_directory(
     let app = app.await?;
     entries.push(get_page_entry_for_file(
         ssr_module_context,
+        edge_ssr_module_context,
         client_module_context,
         Vc::upcast(FileSource::new(app.project_path)),
         project_root,
@@ -203,6 +239,7 @@ async fn get_page_entries_for_root_directory(
     let document = document.await?;
     entries.push(get_page_entry_for_file(
         ssr_module_context,
+        edge_ssr_module_context,
         client_module_context,
         Vc::upcast(FileSource::new(document.project_path)),
         project_root,
@@ -217,6 +254,7 @@ async fn get_page_entries_for_root_directory(
     let error = error.await?;
     entries.push(get_page_entry_for_file(
         ssr_module_context,
+        edge_ssr_module_context,
         client_module_context,
         Vc::upcast(FileSource::new(error.project_path)),
         project_root,
@@ -229,6 +267,7 @@ async fn get_page_entries_for_root_directory(
     if let Some(api) = api {
         get_page_entries_for_directory(
             ssr_module_context,
+            edge_ssr_module_context,
             client_module_context,
             api,
             project_root,
@@ -242,6 +281,7 @@ async fn get_page_entries_for_root_directory(
     if let Some(pages) = pages {
         get_page_entries_for_directory(
             ssr_module_context,
+            edge_ssr_module_context,
             client_module_context,
             pages,
             project_root,
@@ -258,6 +298,7 @@ async fn get_page_entries_for_root_directory(
 #[async_recursion::async_recursion]
 async fn get_page_entries_for_directory(
     ssr_module_context: Vc<Box<dyn AssetContext>>,
+    edge_ssr_module_context: Vc<Box<dyn AssetContext>>,
     client_module_context: Vc<Box<dyn AssetContext>>,
     pages_structure: Vc<PagesDirectoryStructure>,
     project_root: Vc<FileSystemPath>,
@@ -279,6 +320,7 @@ async fn get_page_entries_for_directory(
         } = *item.await?;
         entries.push(get_page_entry_for_file(
             ssr_module_context,
+            edge_ssr_module_context,
             client_module_context,
             Vc::upcast(FileSource::new(project_path)),
             project_root,
@@ -292,6 +334,7 @@ async fn get_page_entries_for_directory(
     for child in children.iter() {
         get_page_entries_for_directory(
             ssr_module_context,
+            edge_ssr_module_context,
             client_module_context,
             *child,
             project_root,
@@ -310,7 +353,11 @@ async fn get_page_entries_for_directory(
 pub struct PageEntry {
     /// The pathname of the page.
     pub pathname: Vc<String>,
-    /// The Node.js SSR entry module asset.
+    /// The runtime the page is rendered with.
+    pub runtime: NextRuntime,
+    /// Whether the page exports `getStaticProps` or `getStaticPaths`.
+    pub is_ssg: bool,
+    /// The SSR entry module asset, for the runtime of the page.
     pub ssr_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
     /// The client entry module asset.
     pub client_module: Vc<EcmascriptModuleAsset>,
@@ -319,6 +366,7 @@ pub struct PageEntry {
 #[turbo_tasks::function]
 async fn get_page_entry_for_file(
     ssr_module_context: Vc<Box<dyn AssetContext>>,
+    edge_ssr_module_context: Vc<Box<dyn AssetContext>>,
     client_module_context: Vc<Box<dyn AssetContext>>,
     source: Vc<Box<dyn Source>>,
     project_root: Vc<FileSystemPath>,
@@ -336,14 +384,22 @@ async fn get_page_entry_for_file(
     let pathname = pathname_for_path(next_router_root, next_router_path, path_type);
     let original_name = next_original_path.await?.path.clone();
 
+    let userland_module = ssr_module_context.process(source, reference_type.clone());
+    let config = parse_config_from_source(userland_module).await?;
+    let runtime = config.runtime;
+    let is_ssg = *is_ssg_page(userland_module).await?;
+
     let ssr_module = create_page_ssr_entry_module(
         pathname,
         reference_type,
         project_root,
-        ssr_module_context,
+        match runtime {
+            NextRuntime::NodeJs => ssr_module_context,
+            NextRuntime::Edge => edge_ssr_module_context,
+        },
         source,
         Vc::cell(original_name),
-        NextRuntime::NodeJs,
+        runtime,
     );
 
     let client_module = create_page_loader_entry_module(client_module_context, source, pathname);
@@ -356,6 +412,8 @@ async fn get_page_entry_for_file(
 
     Ok(PageEntry {
         pathname,
+        runtime,
+        is_ssg,
         ssr_module,
         client_module,
     }
@@ -375,24 +433,70 @@ pub async fn compute_page_entries_chunks(
     page_entries: &PageEntries,
     client_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     ssr_chunking_context: Vc<BuildChunkingContext>,
+    edge_ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     node_root: Vc<FileSystemPath>,
     pages_manifest_dir_path: &FileSystemPath,
     client_relative_path: &FileSystemPath,
     pages_manifest: &mut PagesManifest,
     build_manifest: &mut BuildManifest,
+    next_font_manifest: &mut NextFontManifest,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
     all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
 ) -> Result<()> {
+    let node_root_ref = node_root.await?;
     for page_entry in page_entries.entries.iter() {
         let page_entry = page_entry.await?;
         let pathname = page_entry.pathname.await?;
         let asset_path: String = get_asset_path_from_pathname(&pathname, ".js");
 
-        let ssr_entry_chunk = ssr_chunking_context.entry_chunk_group(
-            node_root.join(format!("server/pages/{asset_path}")),
-            Vc::upcast(page_entry.ssr_module),
-            page_entries.ssr_runtime_entries,
-        );
-        all_chunks.push(ssr_entry_chunk);
+        let ssr_entry_chunk = match page_entry.runtime {
+            NextRuntime::NodeJs => {
+                let ssr_entry_chunk = ssr_chunking_context.entry_chunk_group(
+                    node_root.join(format!("server/pages/{asset_path}")),
+                    Vc::upcast(page_entry.ssr_module),
+                    page_entries.ssr_runtime_entries,
+                );
+                all_chunks.push(ssr_entry_chunk);
+                ssr_entry_chunk
+            }
+            NextRuntime::Edge => {
+                let Some(evaluatable) =
+                    Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(page_entry.ssr_module)
+                        .await?
+                else {
+                    bail!("Entry module must be evaluatable");
+                };
+                let edge_files = edge_ssr_chunking_context.evaluated_chunk_group(
+                    page_entry.ssr_module.ident(),
+                    page_entries
+                        .edge_ssr_runtime_entries
+                        .with_entry(evaluatable),
+                );
+                let bindings = get_edge_asset_bindings(edge_files, node_root).await?;
+                let edge_files = edge_files.await?;
+                all_chunks.extend(edge_files.iter().copied());
+                all_chunks.extend(bindings.output_assets);
+
+                middlewares_manifest.functions.insert(
+                    pathname.clone_value(),
+                    EdgeFunctionDefinition {
+                        files: get_edge_files_paths(&edge_files, &node_root_ref).await?,
+                        name: pages_edge_function_name(&pathname),
+                        page: pathname.clone_value(),
+                        matchers: vec![MiddlewareMatcher {
+                            regexp: Some(get_named_middleware_regex(&pathname)),
+                            original_source: pathname.clone_value(),
+                            ..Default::default()
+                        }],
+                        wasm: Some(bindings.wasm),
+                        assets: Some(bindings.assets),
+                        regions: None,
+                    },
+                );
+
+                get_edge_entry_chunk(&edge_files).await?
+            }
+        };
 
         let chunk_path = ssr_entry_chunk.ident().path().await?;
         if let Some(asset_path) = pages_manifest_dir_path.get_path_to(&chunk_path) {
@@ -420,6 +524,15 @@ pub async fn compute_page_entries_chunks(
                 build_manifest_pages_entry.push(asset_path.to_string());
             }
         }
+
+        let entry_font_files =
+            get_entry_font_files(client_chunks.await?.clone_value(), client_relative_path).await?;
+        next_font_manifest.pages_using_size_adjust |= entry_font_files.using_size_adjust;
+        if !entry_font_files.font_files.is_empty() {
+            next_font_manifest
+                .pages
+                .insert(pathname.clone_value(), entry_font_files.font_files);
+        }
     }
     Ok(())
 }
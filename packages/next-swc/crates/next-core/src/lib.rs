@@ -49,6 +49,7 @@ pub use emit::{all_assets_from_entries, emit_all_assets, emit_assets, emit_clien
 pub use next_edge::context::{
     get_edge_chunking_context, get_edge_compile_time_info, get_edge_resolve_options_context,
 };
+pub use next_font::{font_file::NextFontFileFlags, google::prefetch::prefetch_google_fonts};
 pub use page_loader::{create_page_loader_entry_module, PageLoaderAsset};
 pub use turbopack_binding::{turbopack::node::source_map, *};
 pub use util::{get_asset_path_from_pathname, pathname_for_path, PathType};
@@ -293,6 +293,7 @@ impl LoaderTreeBuilder {
             Vc::upcast(StructuredImageModuleType::create_module(
                 Vc::upcast(FileSource::new(path)),
                 BlurPlaceholderMode::None,
+                None,
                 self.context,
             )),
         );
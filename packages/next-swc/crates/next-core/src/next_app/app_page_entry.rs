@@ -25,6 +25,7 @@ use crate::{
     loader_tree::LoaderTreeModule,
     mode::NextMode,
     next_app::{AppPage, AppPath},
+    next_edge::entry::{app_edge_function_name, wrap_edge_entry},
     next_server_component::NextServerComponentTransition,
     parse_segment_config_from_loader_tree,
     util::{file_content_rope, load_next_js_template, NextRuntime},
@@ -108,7 +109,13 @@ pub async fn get_app_page_entry(
     );
 
     if is_edge {
-        rsc_entry = wrap_edge_entry(context, project_root, rsc_entry).await?;
+        rsc_entry = wrap_edge_page(context, project_root, rsc_entry).await?;
+        rsc_entry = wrap_edge_entry(
+            Vc::upcast(context),
+            project_root,
+            rsc_entry,
+            app_edge_function_name(&original_name),
+        );
     };
 
     let Some(rsc_entry) =
@@ -126,7 +133,7 @@ pub async fn get_app_page_entry(
     .cell())
 }
 
-async fn wrap_edge_entry(
+async fn wrap_edge_page(
     context: Vc<ModuleAssetContext>,
     project_root: Vc<FileSystemPath>,
     entry: Vc<Box<dyn Module>>,
@@ -22,6 +22,7 @@ use turbopack_binding::{
 
 use crate::{
     next_app::{AppEntry, AppPage, AppPath},
+    next_edge::entry::{app_edge_function_name, edge_entry_key},
     parse_segment_config_from_source,
     util::{load_next_js_template, NextRuntime},
 };
@@ -91,7 +92,12 @@ pub async fn get_app_route_entry(
     );
 
     if is_edge {
-        rsc_entry = wrap_edge_entry(context, project_root, rsc_entry, pathname.clone());
+        rsc_entry = wrap_edge_entry(
+            context,
+            project_root,
+            rsc_entry,
+            app_edge_function_name(&original_name),
+        );
     }
 
     let Some(rsc_entry) =
@@ -114,7 +120,7 @@ pub async fn wrap_edge_entry(
     context: Vc<ModuleAssetContext>,
     project_root: Vc<FileSystemPath>,
     entry: Vc<Box<dyn Module>>,
-    pathname: String,
+    name: String,
 ) -> Result<Vc<Box<dyn Module>>> {
     let mut source = RopeBuilder::default();
     writedoc!(
@@ -129,7 +135,7 @@ pub async fn wrap_edge_entry(
                 default: EdgeRouteModuleWrapper.wrap(module.routeModule),
             }}
         "#,
-        StringifyJs(&format_args!("middleware_{}", pathname))
+        StringifyJs(&edge_entry_key(&name))
     )?;
     let file = File::from(source.build());
     // TODO(alexkirsz) Figure out how to name this virtual asset.
@@ -4,6 +4,7 @@ pub mod app_entry;
 pub mod app_page_entry;
 pub mod app_route_entry;
 pub mod metadata;
+pub mod server_actions;
 
 use std::{
     fmt::{Display, Formatter, Write},
@@ -22,6 +23,7 @@ pub use crate::next_app::{
     app_entry::AppEntry,
     app_page_entry::get_app_page_entry,
     app_route_entry::get_app_route_entry,
+    server_actions::{create_server_actions_manifest, get_server_actions},
 };
 
 /// See [AppPage].
@@ -3,10 +3,6 @@ use std::io::Write;
 use anyhow::{bail, Result};
 use indexmap::IndexMap;
 use indoc::writedoc;
-use next_core::{
-    next_manifests::{ActionLayer, ActionManifestWorkerEntry, ServerReferenceManifest},
-    util::{get_asset_prefix_from_pathname, NextRuntime},
-};
 use next_swc::server_actions::parse_server_actions;
 use turbo_tasks::{
     graph::{GraphTraversal, NonDeterministic},
@@ -34,6 +30,11 @@ use turbopack_binding::{
     },
 };
 
+use crate::{
+    next_manifests::{ActionLayer, ActionManifestWorkerEntry, ServerReferenceManifest},
+    util::{get_asset_prefix_from_pathname, NextRuntime},
+};
+
 /// Scans the RSC entry point's full module graph looking for exported Server
 /// Actions (identifiable by a magic comment in the transformed module's
 /// output), and constructs a evaluatable "action loader" entry point and
@@ -41,7 +42,7 @@ use turbopack_binding::{
 ///
 /// If Server Actions are not enabled, this returns an empty manifest and a None
 /// loader.
-pub(crate) async fn create_server_actions_manifest(
+pub async fn create_server_actions_manifest(
     entry: Vc<Box<dyn EcmascriptChunkPlaceable>>,
     node_root: Vc<FileSystemPath>,
     pathname: &str,
@@ -53,14 +54,48 @@ pub(crate) async fn create_server_actions_manifest(
 ) -> Result<(
     Option<Vc<Box<dyn EvaluatableAsset>>>,
     Vc<Box<dyn OutputAsset>>,
+)> {
+    let (loader, manifest) = get_server_actions(
+        entry,
+        node_root,
+        page_name,
+        runtime,
+        asset_context,
+        chunking_context,
+        enable_server_actions,
+    )
+    .await?;
+
+    let manifest_path_prefix = get_asset_prefix_from_pathname(pathname);
+    let manifest_path = node_root.join(format!(
+        "server/app{manifest_path_prefix}/page/server-reference-manifest.json",
+    ));
+    let manifest = Vc::upcast(VirtualOutputAsset::new(
+        manifest_path,
+        AssetContent::file(File::from(serde_json::to_string_pretty(&manifest)?).into()),
+    ));
+    Ok((loader, manifest))
+}
+
+/// Like [create_server_actions_manifest], but returns the manifest of the page
+/// itself, so that the manifests of several pages can be merged into one.
+pub async fn get_server_actions(
+    entry: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    node_root: Vc<FileSystemPath>,
+    page_name: &str,
+    runtime: NextRuntime,
+    asset_context: Vc<Box<dyn AssetContext>>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    enable_server_actions: Vc<bool>,
+) -> Result<(
+    Option<Vc<Box<dyn EvaluatableAsset>>>,
+    ServerReferenceManifest,
 )> {
     // If actions aren't enabled, then there's no need to scan the module graph. We
     // still need to generate an empty manifest so that the TS side can merge
     // the manifest later on.
     if !*enable_server_actions.await? {
         let manifest = build_manifest(
-            node_root,
-            pathname,
             page_name,
             runtime,
             ModuleActionMap::empty(),
@@ -81,8 +116,7 @@ pub(crate) async fn create_server_actions_manifest(
         .as_chunk_item(Vc::upcast(chunking_context))
         .id()
         .to_string();
-    let manifest =
-        build_manifest(node_root, pathname, page_name, runtime, actions, loader_id).await?;
+    let manifest = build_manifest(page_name, runtime, actions, loader_id).await?;
     Ok((Some(evaluable), manifest))
 }
 
@@ -142,17 +176,11 @@ async fn build_server_actions_loader(
 /// Builds a manifest containing every action's hashed id, with an internal
 /// module id which exports a function using that hashed name.
 async fn build_manifest(
-    node_root: Vc<FileSystemPath>,
-    pathname: &str,
     page_name: &str,
     runtime: NextRuntime,
     actions: Vc<ModuleActionMap>,
     loader_id: Vc<String>,
-) -> Result<Vc<Box<dyn OutputAsset>>> {
-    let manifest_path_prefix = get_asset_prefix_from_pathname(pathname);
-    let manifest_path = node_root.join(format!(
-        "server/app{manifest_path_prefix}/page/server-reference-manifest.json",
-    ));
+) -> Result<ServerReferenceManifest> {
     let mut manifest = ServerReferenceManifest {
         ..Default::default()
     };
@@ -178,10 +206,7 @@ async fn build_manifest(
         }
     }
 
-    Ok(Vc::upcast(VirtualOutputAsset::new(
-        manifest_path,
-        AssetContent::file(File::from(serde_json::to_string_pretty(&manifest)?).into()),
-    )))
+    Ok(manifest)
 }
 
 /// Traverses the entire module graph starting from [module], looking for magic
@@ -0,0 +1,192 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use turbo_tasks::Vc;
+use turbopack_binding::turbo::{
+    tasks_env::ProcessEnv,
+    tasks_fs::{FileContent, FileJsonContent, FileSystemPath},
+};
+
+use crate::mode::NextMode;
+
+/// The query used when the project doesn't configure browserslist.
+pub const DEFAULT_BROWSERSLIST_QUERY: &str =
+    "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari versions, last 1 Edge versions";
+
+/// The files browserslist reads its configuration from, besides
+/// `package.json`.
+const BROWSERSLIST_CONFIG_FILES: &[&str] = &[".browserslistrc", "browserslist"];
+
+/// Returns the browserslist query of the project, which determines the client
+/// environment.
+///
+/// Like browserslist, the `BROWSERSLIST` env var takes precedence over
+/// configuration files. Otherwise the closest directory with a
+/// `.browserslistrc`/`browserslist` file, or a `package.json` with a
+/// `browserslist` key, is used. The environment section is picked from
+/// `BROWSERSLIST_ENV`, falling back to the `NODE_ENV` of the mode.
+#[turbo_tasks::function]
+pub async fn get_browserslist_query(
+    project_path: Vc<FileSystemPath>,
+    env: Vc<Box<dyn ProcessEnv>>,
+    mode: NextMode,
+) -> Result<Vc<String>> {
+    if let Some(query) = &*env.read("BROWSERSLIST".to_string()).await? {
+        return Ok(Vc::cell(query.clone()));
+    }
+
+    let browserslist_env = env
+        .read("BROWSERSLIST_ENV".to_string())
+        .await?
+        .clone_value()
+        .unwrap_or_else(|| mode.node_env().to_string());
+
+    let mut dir = project_path;
+    loop {
+        for config_file in BROWSERSLIST_CONFIG_FILES {
+            if let FileContent::Content(file) = &*dir.join(config_file.to_string()).read().await? {
+                if let Some(query) =
+                    parse_browserslistrc(file.content().to_str()?.as_ref(), &browserslist_env)
+                {
+                    return Ok(Vc::cell(query));
+                }
+            }
+        }
+
+        if let FileJsonContent::Content(package_json) =
+            &*dir.join("package.json".to_string()).read_json().await?
+        {
+            if let Some(config) = package_json.get("browserslist") {
+                if let Some(query) = parse_package_json_config(config, &browserslist_env) {
+                    return Ok(Vc::cell(query));
+                }
+            }
+        }
+
+        if dir.await?.path.is_empty() {
+            break;
+        }
+        dir = dir.parent();
+    }
+
+    Ok(Vc::cell(DEFAULT_BROWSERSLIST_QUERY.to_string()))
+}
+
+/// Parses a `.browserslistrc` file. Queries outside of any `[env]` section
+/// are the defaults, which are used when there is no section for the
+/// environment.
+fn parse_browserslistrc(content: &str, browserslist_env: &str) -> Option<String> {
+    let mut default_queries = vec![];
+    let mut env_queries: Option<Vec<&str>> = None;
+    // Whether the current section applies: `None` outside of sections.
+    let mut in_env_section = None;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let matches = section
+                .split_whitespace()
+                .any(|env| env == browserslist_env);
+            if matches {
+                env_queries.get_or_insert_with(Vec::new);
+            }
+            in_env_section = Some(matches);
+            continue;
+        }
+
+        let queries = line.split(',').map(str::trim).filter(|q| !q.is_empty());
+        match in_env_section {
+            None => default_queries.extend(queries),
+            Some(true) => env_queries.get_or_insert_with(Vec::new).extend(queries),
+            Some(false) => {}
+        }
+    }
+
+    let queries = env_queries.unwrap_or(default_queries);
+    (!queries.is_empty()).then(|| queries.join(", "))
+}
+
+/// Parses the `browserslist` key of a `package.json`, which is either a
+/// query, a list of queries, or an object of those keyed by environment.
+fn parse_package_json_config(config: &JsonValue, browserslist_env: &str) -> Option<String> {
+    match config {
+        JsonValue::String(query) => Some(query.clone()),
+        JsonValue::Array(queries) => {
+            let queries: Vec<&str> = queries.iter().filter_map(|q| q.as_str()).collect();
+            (!queries.is_empty()).then(|| queries.join(", "))
+        }
+        JsonValue::Object(envs) => envs
+            .get(browserslist_env)
+            .or_else(|| envs.get("defaults"))
+            .and_then(|config| match config {
+                JsonValue::Object(_) => None,
+                config => parse_package_json_config(config, browserslist_env),
+            }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{parse_browserslistrc, parse_package_json_config};
+
+    #[test]
+    fn test_browserslistrc() {
+        let content = "# Browsers we support\n> 0.5%, last 2 versions\nnot dead\n";
+        assert_eq!(
+            parse_browserslistrc(content, "production").as_deref(),
+            Some("> 0.5%, last 2 versions, not dead")
+        );
+        assert_eq!(parse_browserslistrc("# nothing\n", "production"), None);
+    }
+
+    #[test]
+    fn test_browserslistrc_env_sections() {
+        let content = "safari >= 14\n\n[production staging]\n> 1%\nsafari >= \
+                       14\n\n[development]\nlast 1 chrome version\n";
+        assert_eq!(
+            parse_browserslistrc(content, "production").as_deref(),
+            Some("> 1%, safari >= 14")
+        );
+        assert_eq!(
+            parse_browserslistrc(content, "development").as_deref(),
+            Some("last 1 chrome version")
+        );
+        assert_eq!(
+            parse_browserslistrc(content, "test").as_deref(),
+            Some("safari >= 14")
+        );
+    }
+
+    #[test]
+    fn test_package_json_config() {
+        assert_eq!(
+            parse_package_json_config(&json!("safari >= 14"), "production").as_deref(),
+            Some("safari >= 14")
+        );
+        assert_eq!(
+            parse_package_json_config(&json!(["safari >= 14", "not dead"]), "production")
+                .as_deref(),
+            Some("safari >= 14, not dead")
+        );
+
+        let envs = json!({
+            "production": ["safari >= 14"],
+            "development": "last 1 chrome version",
+        });
+        assert_eq!(
+            parse_package_json_config(&envs, "production").as_deref(),
+            Some("safari >= 14")
+        );
+        assert_eq!(
+            parse_package_json_config(&envs, "development").as_deref(),
+            Some("last 1 chrome version")
+        );
+        assert_eq!(parse_package_json_config(&envs, "test"), None);
+    }
+}
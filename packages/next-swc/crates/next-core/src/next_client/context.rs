@@ -112,24 +112,28 @@ async fn next_client_free_vars(
     .cell())
 }
 
+/// `browserslist_query` is usually
+/// [crate::next_client::get_browserslist_query].
 #[turbo_tasks::function]
-pub fn get_client_compile_time_info(
+pub async fn get_client_compile_time_info(
     mode: NextMode,
-    browserslist_query: String,
+    browserslist_query: Vc<String>,
     define_env: Vc<EnvMap>,
-) -> Vc<CompileTimeInfo> {
-    CompileTimeInfo::builder(Environment::new(Value::new(ExecutionEnvironment::Browser(
-        BrowserEnvironment {
-            dom: true,
-            web_worker: false,
-            service_worker: false,
-            browserslist_query: browserslist_query.to_owned(),
-        }
-        .into(),
-    ))))
-    .defines(next_client_defines(mode, define_env))
-    .free_var_references(next_client_free_vars(mode, define_env))
-    .cell()
+) -> Result<Vc<CompileTimeInfo>> {
+    Ok(
+        CompileTimeInfo::builder(Environment::new(Value::new(ExecutionEnvironment::Browser(
+            BrowserEnvironment {
+                dom: true,
+                web_worker: false,
+                service_worker: false,
+                browserslist_query: browserslist_query.await?.clone_value(),
+            }
+            .into(),
+        ))))
+        .defines(next_client_defines(mode, define_env))
+        .free_var_references(next_client_free_vars(mode, define_env))
+        .cell(),
+    )
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -250,7 +254,7 @@ pub async fn get_client_module_options_context(
         *get_relay_transform_plugin(next_config).await?,
         *get_emotion_transform_plugin(next_config).await?,
         *get_styled_components_transform_plugin(next_config).await?,
-        *get_styled_jsx_transform_plugin().await?,
+        *get_styled_jsx_transform_plugin(next_config).await?,
     ]
     .into_iter()
     .flatten()
@@ -1,8 +1,10 @@
+pub(crate) mod browserslist;
 pub(crate) mod context;
 pub(crate) mod runtime_entry;
 pub(crate) mod transforms;
 pub(crate) mod transition;
 
+pub use browserslist::{get_browserslist_query, DEFAULT_BROWSERSLIST_QUERY};
 pub use context::{
     get_client_chunking_context, get_client_compile_time_info, get_client_module_options_context,
     get_client_resolve_options_context, get_client_runtime_entries, ClientContextType,
@@ -7,9 +7,11 @@ use crate::{
     mode::NextMode,
     next_client::context::ClientContextType,
     next_config::NextConfig,
+    next_image::srcset::static_srcset_widths,
     next_shared::transforms::{
         get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
         get_next_modularize_imports_rule, get_next_pages_transforms_rule,
+        get_react_remove_properties_transform_rule, get_remove_console_transform_rule,
         get_server_actions_transform_rule, server_actions::ActionsTransform,
     },
 };
@@ -23,7 +25,8 @@ pub async fn get_next_client_transforms_rules(
 ) -> Result<Vec<ModuleRule>> {
     let mut rules = vec![];
 
-    let modularize_imports_config = &next_config.await?.modularize_imports;
+    let next_config_value = next_config.await?;
+    let modularize_imports_config = &next_config_value.modularize_imports;
     let enable_server_actions = *next_config.enable_server_actions().await?;
     if let Some(modularize_imports_config) = modularize_imports_config {
         rules.push(get_next_modularize_imports_rule(modularize_imports_config));
@@ -31,6 +34,17 @@ pub async fn get_next_client_transforms_rules(
 
     rules.push(get_next_font_transform_rule());
 
+    if let Some(compiler) = &next_config_value.compiler {
+        if let Some(remove_console_config) = &compiler.remove_console {
+            rules.extend(get_remove_console_transform_rule(remove_console_config)?);
+        }
+        if let Some(react_remove_properties_config) = &compiler.react_remove_properties {
+            rules.extend(get_react_remove_properties_transform_rule(
+                react_remove_properties_config,
+            )?);
+        }
+    }
+
     let pages_dir = match context_ty {
         ClientContextType::Pages { pages_dir } => {
             rules.push(
@@ -49,7 +63,10 @@ pub async fn get_next_client_transforms_rules(
 
     rules.push(get_next_dynamic_transform_rule(false, false, pages_dir, mode).await?);
 
-    rules.push(get_next_image_rule());
+    rules.push(get_next_image_rule(static_srcset_widths(
+        &next_config_value,
+        mode,
+    )));
 
     Ok(rules)
 }
@@ -38,6 +38,7 @@ use turbopack_binding::{
         },
     },
 };
+use url::Url;
 
 use crate::{embed_js::next_asset, next_shared::transforms::ModularizeImportPackageConfig};
 
@@ -105,6 +106,8 @@ pub struct NextConfig {
 
     pub optimize_fonts: Option<bool>,
 
+    pub output_file_tracing: bool,
+
     // unsupported
     amp: AmpConfig,
     clean_dist_dir: bool,
@@ -118,7 +121,6 @@ pub struct NextConfig {
     generate_etags: bool,
     http_agent_options: HttpAgentConfig,
     on_demand_entries: OnDemandEntriesConfig,
-    output_file_tracing: bool,
     powered_by_header: bool,
     production_browser_source_maps: bool,
     public_runtime_config: IndexMap<String, serde_json::Value>,
@@ -350,7 +352,7 @@ pub enum ImageLoader {
     Custom,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, TraceRawVcs)]
 pub enum ImageFormat {
     #[serde(rename = "image/webp")]
     Webp,
@@ -422,6 +424,9 @@ pub struct ExperimentalConfig {
     /// For use with `@next/mdx`. Compile MDX files using the new Rust compiler.
     /// @see https://nextjs.org/docs/app/api-reference/next-config-js/mdxRs
     mdx_rs: Option<bool>,
+    /// Moves static styled-jsx styles into CSS files instead of injecting them
+    /// at runtime.
+    extract_styled_jsx_css: Option<bool>,
     /// A list of packages that should be treated as external in the RSC server
     /// build. @see https://nextjs.org/docs/app/api-reference/next-config-js/server_components_external_packages
     pub server_components_external_packages: Option<Vec<String>>,
@@ -446,6 +451,15 @@ pub struct ExperimentalConfig {
     /// Enables server actions. Using this feature will enable the
     /// `react@experimental` for the `app` directory. @see https://nextjs.org/docs/app/api-reference/functions/server-actions
     server_actions: Option<bool>,
+    /// Maximum sizes of the client bundles, checked by production builds.
+    pub bundle_budgets: Option<BundleBudgets>,
+    /// The directory files are traced from, which defaults to the workspace
+    /// root.
+    pub output_file_tracing_root: Option<String>,
+    /// Globs of files to add to the traces of the pages matching the key glob.
+    pub output_file_tracing_includes: Option<IndexMap<String, Vec<String>>>,
+    /// Globs of files, relative to the tracing root, which are not traced.
+    pub output_file_tracing_ignores: Option<Vec<String>>,
 
     // ---
     // UNSUPPORTED
@@ -480,9 +494,6 @@ pub struct ExperimentalConfig {
     /// Automatically apply the "modularize_imports" optimization to imports of
     /// the specified packages.
     optimize_package_imports: Option<Vec<String>>,
-    output_file_tracing_ignores: Option<Vec<String>>,
-    output_file_tracing_includes: Option<serde_json::Value>,
-    output_file_tracing_root: Option<String>,
     /// Using this feature will enable the `react@experimental` for the `app`
     /// directory.
     ppr: Option<bool>,
@@ -509,6 +520,28 @@ pub struct ExperimentalConfig {
     worker_threads: Option<bool>,
 }
 
+/// Maximum sizes in bytes of the client bundles. Sizes are compared gzipped,
+/// like the sizes reported by `next build`. A build exceeding a budget fails.
+#[turbo_tasks::value(eq = "manual")]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleBudgets {
+    /// The JavaScript a route loads when it is first visited.
+    pub max_first_load_js_size: Option<usize>,
+    /// Each client JavaScript chunk.
+    pub max_chunk_size: Option<usize>,
+    /// All client CSS chunks together.
+    pub max_total_css_size: Option<usize>,
+}
+
+impl BundleBudgets {
+    pub fn is_empty(&self) -> bool {
+        self.max_first_load_js_size.is_none()
+            && self.max_chunk_size.is_none()
+            && self.max_total_css_size.is_none()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(untagged)]
 enum SizeLimit {
@@ -540,7 +573,7 @@ pub enum StyledComponentsTransformOptionsOrBoolean {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct CompilerConfig {
-    pub react_remove_properties: Option<bool>,
+    pub react_remove_properties: Option<ReactRemoveProperties>,
     pub relay: Option<RelayConfig>,
     pub emotion: Option<EmotionTransformOptionsOrBoolean>,
     pub remove_console: Option<RemoveConsoleConfig>,
@@ -551,14 +584,20 @@ pub struct CompilerConfig {
 #[serde(untagged, rename_all = "camelCase")]
 pub enum ReactRemoveProperties {
     Boolean(bool),
-    Config { properties: Option<Vec<String>> },
+    Config {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        properties: Option<Vec<String>>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(untagged)]
 pub enum RemoveConsoleConfig {
     Boolean(bool),
-    Config { exclude: Option<Vec<String>> },
+    Config {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exclude: Option<Vec<String>>,
+    },
 }
 
 #[turbo_tasks::value_impl]
@@ -613,6 +652,17 @@ impl NextConfig {
         Ok(self.await?.images.clone().cell())
     }
 
+    #[turbo_tasks::function]
+    pub async fn bundle_budgets(self: Vc<Self>) -> Result<Vc<BundleBudgets>> {
+        Ok(self
+            .await?
+            .experimental
+            .bundle_budgets
+            .clone()
+            .unwrap_or_default()
+            .cell())
+    }
+
     #[turbo_tasks::function]
     pub async fn page_extensions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
         Ok(Vc::cell(self.await?.page_extensions.clone()))
@@ -691,6 +741,16 @@ impl NextConfig {
         Ok(Vc::cell(self.await?.experimental.mdx_rs.unwrap_or(false)))
     }
 
+    #[turbo_tasks::function]
+    pub async fn extract_styled_jsx_css(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .extract_styled_jsx_css
+                .unwrap_or(false),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn sass_config(self: Vc<Self>) -> Result<Vc<JsonValue>> {
         Ok(Vc::cell(
@@ -719,20 +779,17 @@ impl NextConfig {
 
     /// Returns the final asset prefix. If an assetPrefix is set, it's used.
     /// Otherwise, the basePath is used.
+    ///
+    /// The assetPrefix can also be an absolute URL, like
+    /// `https://cdn.example.com/app`, to load client chunks and assets from
+    /// another origin.
     #[turbo_tasks::function]
     pub async fn computed_asset_prefix(self: Vc<Self>) -> Result<Vc<Option<String>>> {
         let this = self.await?;
 
-        Ok(Vc::cell(Some(format!(
-            "{}/_next/",
-            if let Some(asset_prefix) = &this.asset_prefix {
-                asset_prefix
-            } else if let Some(base_path) = &this.base_path {
-                base_path
-            } else {
-                ""
-            }
-            .trim_end_matches('/')
+        Ok(Vc::cell(Some(compute_asset_prefix(
+            this.asset_prefix.as_deref(),
+            this.base_path.as_deref(),
         ))))
     }
 
@@ -744,6 +801,26 @@ impl NextConfig {
     }
 }
 
+fn compute_asset_prefix(asset_prefix: Option<&str>, base_path: Option<&str>) -> String {
+    let prefix = asset_prefix
+        .filter(|asset_prefix| !asset_prefix.is_empty())
+        .or(base_path)
+        .unwrap_or_default()
+        .trim_end_matches('/');
+
+    if prefix.is_empty() || prefix.starts_with('/') || is_absolute_url(prefix) {
+        format!("{prefix}/_next/")
+    } else {
+        format!("/{prefix}/_next/")
+    }
+}
+
+/// Whether the asset prefix points to another origin. Protocol-relative URLs
+/// like `//cdn.example.com` are absolute too.
+fn is_absolute_url(prefix: &str) -> bool {
+    prefix.starts_with("//") || Url::parse(prefix).map_or(false, |url| url.has_host())
+}
+
 fn next_configs() -> Vc<Vec<String>> {
     Vc::cell(
         ["next.config.mjs", "next.config.js"]
@@ -954,3 +1031,38 @@ impl Issue for OutdatedConfigIssue {
         Vc::cell(self.description.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::compute_asset_prefix;
+
+    #[test]
+    fn test_compute_asset_prefix() {
+        assert_eq!(compute_asset_prefix(None, None), "/_next/");
+        assert_eq!(
+            compute_asset_prefix(Some(""), Some("/docs")),
+            "/docs/_next/"
+        );
+        assert_eq!(
+            compute_asset_prefix(Some("/cdn/"), Some("/docs")),
+            "/cdn/_next/"
+        );
+        assert_eq!(compute_asset_prefix(Some("cdn"), None), "/cdn/_next/");
+    }
+
+    #[test]
+    fn test_compute_asset_prefix_url() {
+        assert_eq!(
+            compute_asset_prefix(Some("https://cdn.example.com/app"), Some("/docs")),
+            "https://cdn.example.com/app/_next/"
+        );
+        assert_eq!(
+            compute_asset_prefix(Some("https://cdn.example.com/"), None),
+            "https://cdn.example.com/_next/"
+        );
+        assert_eq!(
+            compute_asset_prefix(Some("//cdn.example.com/app"), None),
+            "//cdn.example.com/app/_next/"
+        );
+    }
+}
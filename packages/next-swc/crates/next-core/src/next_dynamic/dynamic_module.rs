@@ -1,12 +1,22 @@
-use anyhow::{bail, Result};
-use turbo_tasks::Vc;
-use turbopack_binding::turbopack::core::{
-    asset::{Asset, AssetContent},
-    chunk::{ChunkableModule, ChunkingContext, ChunkingContextExt},
-    ident::AssetIdent,
-    module::Module,
-    output::OutputAssets,
-    reference::ModuleReferences,
+use anyhow::{bail, Context, Result};
+use turbo_tasks::{ValueToString, Vc};
+use turbopack_binding::turbopack::{
+    core::{
+        asset::{Asset, AssetContent},
+        chunk::{
+            ChunkItem, ChunkItemExt, ChunkType, ChunkableModule, ChunkingContext,
+            ChunkingContextExt, ModuleId,
+        },
+        ident::AssetIdent,
+        module::Module,
+        output::OutputAssets,
+        reference::ModuleReferences,
+    },
+    ecmascript::chunk::EcmascriptChunkType,
+    turbopack::ecmascript::chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkingContext, EcmascriptExports,
+    },
 };
 
 /// A [`NextDynamicEntryModule`] is a marker asset used to indicate which
@@ -43,6 +53,41 @@ impl NextDynamicEntryModule {
 
         Ok(client_chunking_context.root_chunk_group(client_entry_module))
     }
+
+    /// The id of the client entry module, which the client reports in the
+    /// `dynamicIds` of the page once it is loaded.
+    #[turbo_tasks::function]
+    pub async fn client_module_id(
+        self: Vc<Self>,
+        client_chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<ModuleId>> {
+        let this = self.await?;
+
+        let Some(client_entry_module) =
+            Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(this.client_entry_module).await?
+        else {
+            bail!("dynamic client asset must be chunkable");
+        };
+
+        Ok(client_entry_module
+            .as_chunk_item(client_chunking_context)
+            .id())
+    }
+
+    /// The key of this dynamic import in the React Loadable Manifest. On the
+    /// server, the `next/dynamic` transform imports the id of this marker
+    /// module, so the key is its id in the chunking context of the server
+    /// bundle.
+    #[turbo_tasks::function]
+    pub fn manifest_key(
+        self: Vc<Self>,
+        server_chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Vc<String> {
+        Vc::upcast::<Box<dyn ChunkableModule>>(self)
+            .as_chunk_item(server_chunking_context)
+            .id()
+            .to_string()
+    }
 }
 
 #[turbo_tasks::function]
@@ -73,3 +118,88 @@ impl Asset for NextDynamicEntryModule {
         bail!("NextDynamicEntryModule has no content")
     }
 }
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for NextDynamicEntryModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     NextDynamicEntryModule",
+                )?;
+        Ok(Vc::upcast(
+            NextDynamicEntryChunkItem {
+                context,
+                inner: self,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for NextDynamicEntryModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+/// The server only imports the id of the marker module, so its chunk item
+/// has no exports.
+#[turbo_tasks::value]
+struct NextDynamicEntryChunkItem {
+    context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<NextDynamicEntryModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for NextDynamicEntryChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<EcmascriptChunkItemContent> {
+        EcmascriptChunkItemContent {
+            inner_code: "__turbopack_esm__({});\n".into(),
+            ..Default::default()
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for NextDynamicEntryChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        self.inner.references()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.context)
+    }
+
+    #[turbo_tasks::function]
+    fn ty(&self) -> Vc<Box<dyn ChunkType>> {
+        Vc::upcast(Vc::<EcmascriptChunkType>::default())
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+}
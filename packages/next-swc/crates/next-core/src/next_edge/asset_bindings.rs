@@ -0,0 +1,144 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack_binding::{
+    turbo::tasks_hash::hash_xxh3_hash64,
+    turbopack::core::{
+        asset::{Asset, AssetContent},
+        output::{OutputAsset, OutputAssets},
+        virtual_output::VirtualOutputAsset,
+    },
+};
+
+use crate::{emit::all_assets_from_entries, next_manifests::AssetBinding};
+
+/// The directory bound assets are emitted to when they are not already part
+/// of the node root, relative to the node root.
+const EDGE_ASSETS_DIR: &str = "server/edge/assets";
+
+/// The files of an edge function which the edge runtime provides by name,
+/// instead of loading them as chunks.
+#[derive(Default)]
+pub struct EdgeAssetBindings {
+    /// WebAssembly modules imported by the function.
+    pub wasm: Vec<AssetBinding>,
+    /// Assets referenced with `new URL(..., import.meta.url)`.
+    pub assets: Vec<AssetBinding>,
+    /// Output assets that need to be emitted in addition to the edge files,
+    /// for bound files that were placed outside of the node root.
+    pub output_assets: Vec<Vc<Box<dyn OutputAsset>>>,
+}
+
+/// Collects the WebAssembly and asset bindings from the module graph of an
+/// edge function, given its files.
+pub async fn get_edge_asset_bindings(
+    edge_files: Vc<OutputAssets>,
+    node_root: Vc<FileSystemPath>,
+) -> Result<EdgeAssetBindings> {
+    let node_root_ref = node_root.await?;
+    let mut bindings = EdgeAssetBindings::default();
+
+    for &asset in all_assets_from_entries(edge_files).await?.iter() {
+        let path = asset.ident().path().await?;
+        let is_wasm = match path.extension_ref() {
+            Some("wasm") => true,
+            // Chunks and their source maps are loaded by the edge runtime itself.
+            Some("js" | "mjs" | "map" | "css") => continue,
+            _ => false,
+        };
+
+        let file_name = path.file_name();
+        let file_path = match node_root_ref.get_path_to(&path) {
+            Some(file_path) => file_path.to_string(),
+            None => {
+                // Assets like `new URL()` references end up in the client assets, but the edge
+                // runtime reads them from the node root. They are flattened into a single
+                // directory, so the content hash keeps assets with the same name apart.
+                let content_hash = asset_content_hash(asset.content()).await?;
+                let file_path = format!(
+                    "{EDGE_ASSETS_DIR}/{}",
+                    hashed_file_name(file_name, &content_hash)
+                );
+                bindings
+                    .output_assets
+                    .push(Vc::upcast(VirtualOutputAsset::new(
+                        node_root.join(file_path.clone()),
+                        asset.content(),
+                    )));
+                file_path
+            }
+        };
+
+        if is_wasm {
+            bindings.wasm.push(AssetBinding {
+                name: wasm_binding_name(&file_path),
+                file_path,
+            });
+        } else {
+            bindings.assets.push(AssetBinding {
+                name: file_name.to_string(),
+                file_path,
+            });
+        }
+    }
+
+    Ok(bindings)
+}
+
+/// The name of the global the Turbopack edge runtime looks up a WebAssembly
+/// module under in `loadWebAssemblyModule`, given the path of the module
+/// relative to the node root.
+fn wasm_binding_name(path: &str) -> String {
+    format!(
+        "wasm_{}",
+        path.replace(
+            |c: char| !c.is_ascii_alphanumeric() && c != '$' && c != '_',
+            "_"
+        )
+    )
+}
+
+async fn asset_content_hash(content: Vc<AssetContent>) -> Result<String> {
+    let hash = match &*content.await? {
+        AssetContent::File(file_content) => match &*file_content.await? {
+            FileContent::Content(file) => hash_xxh3_hash64(file.content().to_bytes()?.as_ref()),
+            FileContent::NotFound => hash_xxh3_hash64(""),
+        },
+        AssetContent::Redirect { target, .. } => hash_xxh3_hash64(target),
+    };
+    Ok(format!("{:016x}", hash)[..8].to_string())
+}
+
+/// Inserts the content hash before the extension, like `logo.png` becomes
+/// `logo.0123abcd.png`.
+fn hashed_file_name(file_name: &str, content_hash: &str) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{stem}.{content_hash}.{extension}")
+        }
+        _ => format!("{file_name}.{content_hash}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hashed_file_name, wasm_binding_name};
+
+    #[test]
+    fn test_wasm_binding_name() {
+        assert_eq!(
+            wasm_binding_name("server/edge/chunks/add.wasm_0a1b2c.wasm"),
+            "wasm_server_edge_chunks_add_wasm_0a1b2c_wasm"
+        );
+    }
+
+    #[test]
+    fn test_hashed_file_name() {
+        assert_eq!(
+            hashed_file_name("logo.png", "0123abcd"),
+            "logo.0123abcd.png"
+        );
+        assert_eq!(hashed_file_name("LICENSE", "0123abcd"), "LICENSE.0123abcd");
+        assert_eq!(hashed_file_name(".env", "0123abcd"), ".env.0123abcd");
+    }
+}
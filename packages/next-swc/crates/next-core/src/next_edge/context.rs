@@ -156,6 +156,7 @@ pub fn get_edge_chunking_context(
     project_path: Vc<FileSystemPath>,
     node_root: Vc<FileSystemPath>,
     client_root: Vc<FileSystemPath>,
+    asset_prefix: Vc<Option<String>>,
     environment: Vc<Environment>,
 ) -> Vc<Box<dyn EcmascriptChunkingContext>> {
     Vc::upcast(
@@ -166,6 +167,8 @@ pub fn get_edge_chunking_context(
             get_client_assets_path(client_root),
             environment,
         )
+        // Assets referenced by edge functions are served with the client ones.
+        .asset_base_path(asset_prefix)
         .reference_chunk_source_maps(should_debug("edge"))
         .build(),
     )
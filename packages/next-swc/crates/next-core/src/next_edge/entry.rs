@@ -11,12 +11,41 @@ use turbopack_binding::turbopack::{
     ecmascript::utils::StringifyJs,
 };
 
+use crate::util::get_asset_prefix_from_pathname;
+
+/// Returns the name of the edge function of a pages entry, e.g.
+/// `pages/api/hello`. This is the name of the function in the middleware
+/// manifest.
+pub fn pages_edge_function_name(pathname: &str) -> String {
+    format!("pages{}", get_asset_prefix_from_pathname(pathname))
+}
+
+/// Returns the name of the edge function of an app entry, given its original
+/// name, e.g. `app/foo/page`. This is the name of the function in the
+/// middleware manifest.
+pub fn app_edge_function_name(original_name: &str) -> String {
+    format!("app{original_name}")
+}
+
+/// Returns the global variable the edge sandbox reads the entry of an edge
+/// function from, given the name of the function.
+pub fn edge_entry_global_var_name(name: &str) -> String {
+    format!("_ENTRIES.{}", edge_entry_key(name))
+}
+
+/// Returns the key of an edge function in `self._ENTRIES`.
+pub(crate) fn edge_entry_key(name: &str) -> String {
+    format!("middleware_{name}")
+}
+
+/// Registers the entry of the edge function with the given name in
+/// `self._ENTRIES`, where the edge sandbox looks it up.
 #[turbo_tasks::function]
 pub async fn wrap_edge_entry(
     context: Vc<Box<dyn AssetContext>>,
     project_root: Vc<FileSystemPath>,
     entry: Vc<Box<dyn Module>>,
-    pathname: String,
+    name: String,
 ) -> Result<Vc<Box<dyn Module>>> {
     let source = formatdoc!(
         r#"
@@ -25,7 +54,7 @@ pub async fn wrap_edge_entry(
             self._ENTRIES ||= {{}}
             self._ENTRIES[{}] = module
         "#,
-        StringifyJs(&format_args!("middleware_{}", pathname))
+        StringifyJs(&edge_entry_key(&name))
     );
     let file = File::from(source);
 
@@ -1,3 +1,4 @@
+pub mod asset_bindings;
 pub mod context;
 pub mod entry;
 pub mod page_transition;
@@ -35,6 +35,9 @@ pub(crate) struct AutomaticFontFallback {
     /// The name of font locally, used in `src: local("{}")`
     pub local_font_family: Vc<String>,
     pub adjustment: Option<FontAdjustment>,
+    /// Adjustments for specific weights of the main webfont. When present,
+    /// these replace `adjustment` with one `@font-face` rule per entry.
+    pub weighted_adjustments: Vec<WeightedFontAdjustment>,
 }
 
 #[derive(Debug)]
@@ -68,3 +71,14 @@ pub(crate) struct FontAdjustment {
 // Necessary since floating points in this struct don't implement Eq, but it's
 // required for turbo tasks values.
 impl Eq for FontAdjustment {}
+
+/// A [[FontAdjustment]] that only applies to a range of weights of the main
+/// webfont, e.g. its bold faces.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub(crate) struct WeightedFontAdjustment {
+    /// The css `font-weight` descriptor, e.g. `700` or `100 549`
+    pub weight: String,
+    /// The css `font-style` descriptor, if any
+    pub style: Option<String>,
+    pub adjustment: FontAdjustment,
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::Vc;
+
+use super::font_fallback::FontFallback;
+
+/// How a font file emitted by next/font is used. Like next-font-loader does
+/// for webpack, the flags are encoded in the name of the emitted file, where
+/// the font manifest picks them up: `[hash]-s.p.[ext]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextFontFileFlags {
+    /// Whether the font file is preloaded, i.e. the font call didn't set
+    /// `preload: false`.
+    pub preload: bool,
+    /// Whether the fallback font of the font file uses `size-adjust`.
+    pub has_size_adjust: bool,
+}
+
+impl NextFontFileFlags {
+    /// Returns the name the font file with these flags is emitted as.
+    pub(crate) fn file_name(&self, stem: &str, ext: &str) -> String {
+        format!(
+            "{}{}{}.{}",
+            stem,
+            if self.has_size_adjust { "-s" } else { "" },
+            if self.preload { ".p" } else { "" },
+            ext
+        )
+    }
+
+    /// Reads the flags from the name of an emitted font file. The name may
+    /// contain a content hash, e.g. `0123abcd-s.p.89ef4567.woff2`.
+    pub fn from_file_name(file_name: &str) -> Self {
+        let mut segments = file_name.split('.');
+        let stem = segments.next().unwrap_or_default();
+        let mut segments: Vec<_> = segments.collect();
+        // The extension
+        segments.pop();
+
+        NextFontFileFlags {
+            preload: segments.contains(&"p"),
+            has_size_adjust: stem.ends_with("-s"),
+        }
+    }
+}
+
+/// Whether one of the fallbacks is adjusted to the metrics of the webfont,
+/// which makes it use `size-adjust`.
+pub(crate) async fn has_size_adjust(
+    fallbacks: impl IntoIterator<Item = Vc<FontFallback>>,
+) -> Result<bool> {
+    for fallback in fallbacks {
+        if let FontFallback::Automatic(fallback) = &*fallback.await? {
+            let fallback = fallback.await?;
+            if fallback.adjustment.is_some() || !fallback.weighted_adjustments.is_empty() {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NextFontFileFlags;
+
+    #[test]
+    fn test_file_name_round_trip() {
+        for preload in [false, true] {
+            for has_size_adjust in [false, true] {
+                let flags = NextFontFileFlags {
+                    preload,
+                    has_size_adjust,
+                };
+                let file_name = flags.file_name("0123abcd", "woff2");
+                assert_eq!(NextFontFileFlags::from_file_name(&file_name), flags);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_emitted_file_name() {
+        assert_eq!(
+            NextFontFileFlags::from_file_name("0123abcd-s.p.89ef4567.woff2"),
+            NextFontFileFlags {
+                preload: true,
+                has_size_adjust: true,
+            }
+        );
+        assert_eq!(
+            NextFontFileFlags::from_file_name("0123abcd.89ef4567.ttf"),
+            NextFontFileFlags::default()
+        );
+        // The extension is never a flag.
+        assert_eq!(
+            NextFontFileFlags::from_file_name("font.p"),
+            NextFontFileFlags::default()
+        );
+    }
+}
@@ -0,0 +1,486 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{ValueToString, Vc};
+use turbopack_binding::{
+    turbo::{
+        tasks_env::{CommandLineProcessEnv, ProcessEnv},
+        tasks_fetch::{fetch, HttpResponseBody},
+        tasks_fs::{
+            json::parse_json_with_source_context, rope::Rope, DiskFileSystem, File, FileContent,
+            FileSystem, FileSystemPath,
+        },
+        tasks_hash::hash_xxh3_hash64,
+    },
+    turbopack::core::{
+        asset::AssetContent,
+        issue::{IssueExt, IssueSeverity},
+        resolve::{
+            options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+            parse::Request,
+            ResolveResult,
+        },
+        virtual_source::VirtualSource,
+    },
+};
+
+use super::USER_AGENT_FOR_GOOGLE_FONTS;
+use crate::{
+    embed_js::next_js_file_path,
+    next_font::{font_file::NextFontFileFlags, issue::NextFontIssue},
+};
+
+/// The environment variable pointing to a persistent directory that Google
+/// Fonts stylesheets and font files are cached in.
+///
+/// `next experimental-prefetch-fonts` fills it with the fonts of the project,
+/// and every build with network access stores what it fetches there as well.
+/// Relative paths are resolved against the project directory.
+pub const NEXT_FONT_GOOGLE_CACHE_DIR_ENV: &str = "NEXT_FONT_GOOGLE_CACHE_DIR";
+
+/// The request path used in `url()`s of stylesheets for self-hosted font files.
+/// Intercepted by [NextFontGoogleFontFileReplacer].
+pub const NEXT_FONT_GOOGLE_FONT_FILE_REQUEST: &str =
+    "@vercel/turbopack-next/internal/font/google/font";
+
+#[turbo_tasks::value(transparent)]
+pub(super) struct GoogleFontsCache(Option<Vc<FileSystemPath>>);
+
+/// Returns the root of the Google Fonts cache, when one is configured. A
+/// relative directory is resolved against the project directory.
+#[turbo_tasks::function]
+pub(super) async fn google_fonts_cache(
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<GoogleFontsCache>> {
+    let env = Vc::upcast::<Box<dyn ProcessEnv>>(CommandLineProcessEnv::new());
+    let Some(cache_dir) = &*env.read(NEXT_FONT_GOOGLE_CACHE_DIR_ENV.to_string()).await? else {
+        return Ok(Vc::cell(None));
+    };
+
+    if Path::new(cache_dir).is_absolute() {
+        let fs = DiskFileSystem::new("next-font-google-cache".to_string(), cache_dir.clone());
+        return Ok(Vc::cell(Some(Vc::upcast::<Box<dyn FileSystem>>(fs).root())));
+    }
+
+    let Some(cache) = *project_path.try_join(cache_dir.replace('\\', "/")).await? else {
+        bail!(
+            "{} must be an absolute path or a path inside of the workspace, got `{}`",
+            NEXT_FONT_GOOGLE_CACHE_DIR_ENV,
+            cache_dir
+        );
+    };
+    Ok(Vc::cell(Some(cache)))
+}
+
+/// Where the font files referenced by Google Fonts stylesheets are read from.
+pub(super) enum FontFileSource {
+    /// Integration tests mock Google Fonts with the responses in
+    /// `NEXT_FONT_GOOGLE_MOCKED_RESPONSES`. Like for next-font-loader, a font
+    /// file is read from disk when its url is an absolute path, and consists of
+    /// its url otherwise.
+    Mocked(String),
+    /// The persistent cache in [NEXT_FONT_GOOGLE_CACHE_DIR_ENV].
+    Cache(Vc<FileSystemPath>),
+    /// Google Fonts, with the responses only kept in memory.
+    Fetch,
+}
+
+pub(super) async fn get_font_file_source(
+    project_path: Vc<FileSystemPath>,
+) -> Result<FontFileSource> {
+    let env = Vc::upcast::<Box<dyn ProcessEnv>>(CommandLineProcessEnv::new());
+    if let Some(mocked_responses_path) = &*env
+        .read("NEXT_FONT_GOOGLE_MOCKED_RESPONSES".to_string())
+        .await?
+    {
+        return Ok(FontFileSource::Mocked(mocked_responses_path.clone()));
+    }
+
+    Ok(match *google_fonts_cache(project_path).await? {
+        Some(cache) => FontFileSource::Cache(cache),
+        None => FontFileSource::Fetch,
+    })
+}
+
+fn stylesheet_cache_path(cache: Vc<FileSystemPath>, stylesheet_url: &str) -> Vc<FileSystemPath> {
+    cache.join(format!(
+        "stylesheets/{:016x}.css",
+        hash_xxh3_hash64(stylesheet_url)
+    ))
+}
+
+/// The file name of a self-hosted font file before its flags are added, e.g.
+/// `0123456789abcdef.woff2`.
+fn font_file_cache_name(font_url: &str) -> String {
+    let ext = font_url
+        .rsplit('/')
+        .next()
+        .and_then(|file_name| file_name.rsplit_once('.'))
+        .map(|(_, ext)| ext)
+        .unwrap_or("woff2");
+
+    format!("{:016x}.{}", hash_xxh3_hash64(font_url), ext)
+}
+
+async fn read_cached_text(path: Vc<FileSystemPath>) -> Result<Option<String>> {
+    Ok(match &*path.read().await? {
+        FileContent::Content(file) => Some(file.content().to_str()?.into_owned()),
+        FileContent::NotFound => None,
+    })
+}
+
+/// Reads the stylesheet at `stylesheet_url` from the cache, falling back to
+/// fetching it (and storing it in the cache) on a cache miss.
+///
+/// A cache miss without network access is reported as an issue, but isn't
+/// fatal: the font's fallback is used instead.
+pub(super) async fn get_cached_stylesheet(
+    cache: Vc<FileSystemPath>,
+    stylesheet_url: Vc<String>,
+    css_virtual_path: Vc<FileSystemPath>,
+) -> Result<Option<Vc<String>>> {
+    let url = stylesheet_url.await?;
+    let cache_path = stylesheet_cache_path(cache, &url);
+
+    let stylesheet = match read_cached_text(cache_path).await? {
+        Some(stylesheet) => stylesheet,
+        None => {
+            let response = fetch(
+                stylesheet_url,
+                Vc::cell(Some(USER_AGENT_FOR_GOOGLE_FONTS.to_owned())),
+            )
+            .await?;
+
+            let response = match &*response {
+                Ok(response) => response.await?,
+                Err(_) => {
+                    NextFontIssue {
+                        path: css_virtual_path,
+                        title: Vc::cell("Google Fonts stylesheet not found in cache".to_owned()),
+                        description: Vc::cell(format!(
+                            "`{}` is not cached in `{}` and could not be fetched. Run `next \
+                             experimental-prefetch-fonts` with network access and `{}` set to \
+                             fill the cache. Using the fallback font instead.",
+                            url,
+                            cache.to_string().await?,
+                            NEXT_FONT_GOOGLE_CACHE_DIR_ENV,
+                        )),
+                        severity: IssueSeverity::Warning.cell(),
+                    }
+                    .cell()
+                    .emit();
+
+                    return Ok(None);
+                }
+            };
+            if response.status != 200 {
+                emit_unexpected_status_issue(css_virtual_path, &url, response.status);
+                return Ok(None);
+            }
+
+            let stylesheet = response.body.to_string().await?.clone_value();
+            cache_path
+                .write(FileContent::Content(File::from(stylesheet.clone())).cell())
+                .await?;
+            stylesheet
+        }
+    };
+
+    Ok(Some(Vc::cell(stylesheet)))
+}
+
+/// How the font files of a stylesheet are used, see [NextFontFileFlags].
+#[derive(Clone, Copy, Default)]
+pub(super) struct StylesheetFontFiles<'a> {
+    /// The subsets whose font files are preloaded. Google Fonts names the
+    /// subset of each `@font-face` rule in a comment above it.
+    pub preloaded_subsets: &'a [String],
+    pub has_size_adjust: bool,
+}
+
+/// The query of a [NEXT_FONT_GOOGLE_FONT_FILE_REQUEST].
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FontFileRequest {
+    /// The url of the font file at Google Fonts.
+    url: String,
+    #[serde(flatten)]
+    flags: NextFontFileFlags,
+}
+
+/// Points the `url()`s of a Google Fonts stylesheet to font files emitted with
+/// the build, which carry their [NextFontFileFlags] in their names. Font files
+/// that can't be loaded keep their remote url.
+pub(super) async fn self_host_font_files(
+    source: &FontFileSource,
+    stylesheet: &str,
+    css_virtual_path: Vc<FileSystemPath>,
+    font_files: StylesheetFontFiles<'_>,
+) -> Result<String> {
+    let regex = lazy_regex::regex!(r"url\(((?:https://|/)[^)]+)\)");
+    let subset_comments: Vec<_> = lazy_regex::regex!(r"/\* (.+?) \*/")
+        .captures_iter(stylesheet)
+        .filter_map(|captures| Some((captures.get(0)?.start(), captures.get(1)?.as_str())))
+        .collect();
+
+    let mut updated = String::with_capacity(stylesheet.len());
+    let mut last_end = 0;
+    for captures in regex.captures_iter(stylesheet) {
+        let (Some(whole), Some(font_url)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+
+        updated.push_str(&stylesheet[last_end..whole.start()]);
+        last_end = whole.end();
+
+        let font_url = font_url.as_str();
+        let is_available = match source {
+            FontFileSource::Mocked(_) => true,
+            FontFileSource::Cache(cache) => {
+                cache_font_file(
+                    *cache,
+                    font_url,
+                    &font_file_cache_name(font_url),
+                    css_virtual_path,
+                )
+                .await?
+            }
+            FontFileSource::Fetch => fetch_font_file(
+                font_url,
+                css_virtual_path,
+                format!(
+                    "`{}` could not be fetched. The font file will be loaded from Google Fonts at \
+                     runtime.",
+                    font_url
+                ),
+            )
+            .await?
+            .is_some(),
+        };
+        if is_available {
+            let subset = subset_comments
+                .iter()
+                .take_while(|(start, _)| *start < whole.start())
+                .last()
+                .map(|(_, subset)| *subset);
+            let request = serde_json::to_string(&FontFileRequest {
+                url: font_url.to_owned(),
+                flags: NextFontFileFlags {
+                    preload: subset.map_or(false, |subset| {
+                        font_files.preloaded_subsets.iter().any(|s| s == subset)
+                    }),
+                    has_size_adjust: font_files.has_size_adjust,
+                },
+            })?;
+            updated.push_str(&format!(
+                "url({}?{})",
+                NEXT_FONT_GOOGLE_FONT_FILE_REQUEST,
+                qstring::QString::new(vec![(request, "")])
+            ));
+        } else {
+            updated.push_str(whole.as_str());
+        }
+    }
+    updated.push_str(&stylesheet[last_end..]);
+
+    Ok(updated)
+}
+
+/// Returns whether the font file is available in the cache after the call.
+async fn cache_font_file(
+    cache: Vc<FileSystemPath>,
+    font_url: &str,
+    file_name: &str,
+    css_virtual_path: Vc<FileSystemPath>,
+) -> Result<bool> {
+    let cache_path = cache.join(format!("fonts/{}", file_name));
+    if let FileContent::Content(_) = &*cache_path.read().await? {
+        return Ok(true);
+    }
+
+    let description = format!(
+        "`{}` is not cached in `{}` and could not be fetched. The font file will be loaded from \
+         Google Fonts at runtime.",
+        font_url,
+        cache.to_string().await?,
+    );
+    let Some(body) = fetch_font_file(font_url, css_virtual_path, description).await? else {
+        return Ok(false);
+    };
+
+    let body = body.await?;
+    cache_path
+        .write(FileContent::Content(File::from(Rope::from(body.0.clone()))).cell())
+        .await?;
+    Ok(true)
+}
+
+/// Fetches a font file from Google Fonts. Failures are reported as issues,
+/// with `description` explaining the consequences of a failed request.
+async fn fetch_font_file(
+    font_url: &str,
+    css_virtual_path: Vc<FileSystemPath>,
+    description: String,
+) -> Result<Option<Vc<HttpResponseBody>>> {
+    let response = fetch(
+        Vc::cell(font_url.to_owned()),
+        Vc::cell(Some(USER_AGENT_FOR_GOOGLE_FONTS.to_owned())),
+    )
+    .await?;
+
+    let response = match &*response {
+        Ok(response) => response.await?,
+        Err(_) => {
+            NextFontIssue {
+                path: css_virtual_path,
+                title: Vc::cell("Google Fonts font file could not be fetched".to_owned()),
+                description: Vc::cell(description),
+                severity: IssueSeverity::Warning.cell(),
+            }
+            .cell()
+            .emit();
+
+            return Ok(None);
+        }
+    };
+    if response.status != 200 {
+        emit_unexpected_status_issue(css_virtual_path, font_url, response.status);
+        return Ok(None);
+    }
+
+    Ok(Some(response.body))
+}
+
+/// Google Fonts was reachable, but didn't return the requested file. Unlike a
+/// cache miss without network, filling the cache won't help here.
+fn emit_unexpected_status_issue(css_virtual_path: Vc<FileSystemPath>, url: &str, status: u16) {
+    NextFontIssue {
+        path: css_virtual_path,
+        title: Vc::cell("Failed to fetch from Google Fonts".to_owned()),
+        description: Vc::cell(format!(
+            "Google Fonts responded to `{}` with status {}. Check that the font options are valid.",
+            url, status
+        )),
+        severity: IssueSeverity::Warning.cell(),
+    }
+    .cell()
+    .emit();
+}
+
+#[turbo_tasks::value(shared)]
+pub struct NextFontGoogleFontFileReplacer {
+    project_path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl NextFontGoogleFontFileReplacer {
+    #[turbo_tasks::function]
+    pub fn new(project_path: Vc<FileSystemPath>) -> Vc<Self> {
+        Self::cell(NextFontGoogleFontFileReplacer { project_path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NextFontGoogleFontFileReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    /// Intercepts `url()` requests for self-hosted font files made by Google
+    /// Fonts stylesheets and resolves them to the font file, renamed to carry
+    /// its flags.
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        _context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module {
+            module: _,
+            path: _,
+            query: query_vc,
+        } = &*request.await?
+        else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let query_map = qstring::QString::from(&**query_vc.await?);
+        let Some((json, _)) = query_map.into_iter().next() else {
+            bail!("Expected one entry");
+        };
+        let FontFileRequest { url, flags } = parse_json_with_source_context(&json)?;
+
+        let file_name = font_file_cache_name(&url);
+        let (stem, ext) = file_name.rsplit_once('.').unwrap_or((&file_name, ""));
+        let emitted_path = |dir: Vc<FileSystemPath>| dir.join(flags.file_name(stem, ext));
+        let fonts_dir = next_js_file_path("internal/font/google/fonts".to_string());
+
+        let (path, content) = match get_font_file_source(self.project_path).await? {
+            FontFileSource::Mocked(_) => {
+                let content = match Path::new(&url).parent().zip(Path::new(&url).file_name()) {
+                    Some((dir, file_name)) if url.starts_with('/') => {
+                        let fs = DiskFileSystem::new(
+                            "next-font-google-mock".to_string(),
+                            dir.to_string_lossy().into_owned(),
+                        );
+                        Vc::upcast::<Box<dyn FileSystem>>(fs)
+                            .root()
+                            .join(file_name.to_string_lossy().into_owned())
+                            .read()
+                    }
+                    _ => FileContent::Content(File::from(url.clone())).cell(),
+                };
+                (emitted_path(fonts_dir), content)
+            }
+            // The file is read from the cache as-is.
+            FontFileSource::Cache(cache) => (
+                emitted_path(cache.join("fonts".to_string())),
+                cache.join(format!("fonts/{}", file_name)).read(),
+            ),
+            FontFileSource::Fetch => {
+                let response = fetch(
+                    Vc::cell(url.clone()),
+                    Vc::cell(Some(USER_AGENT_FOR_GOOGLE_FONTS.to_owned())),
+                )
+                .await?;
+                let Ok(response) = &*response else {
+                    bail!("Failed to fetch the Google Fonts font file `{}`", url);
+                };
+                let body = response.await?.body.await?;
+                (
+                    emitted_path(fonts_dir),
+                    FileContent::Content(File::from(Rope::from(body.0.clone()))).cell(),
+                )
+            }
+        };
+
+        let font_file = VirtualSource::new(path, AssetContent::file(content));
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(font_file)).into()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::font_file_cache_name;
+
+    #[test]
+    fn test_font_file_cache_name_keeps_extension() {
+        let name = font_file_cache_name(
+            "https://fonts.gstatic.com/s/inter/v12/UcC73FwrK3iLTeHuS_fvQtMwCp50KnMa1ZL7.woff2",
+        );
+        assert!(name.ends_with(".woff2"));
+        assert_eq!(name.len(), "0123456789abcdef.woff2".len());
+    }
+
+    #[test]
+    fn test_font_file_cache_name_is_stable() {
+        let url = "https://fonts.gstatic.com/s/roboto/v30/KFOmCnqEu92Fr1Mu4mxK.woff2";
+        assert_eq!(font_file_cache_name(url), font_file_cache_name(url));
+        assert_ne!(
+            font_file_cache_name(url),
+            font_file_cache_name("https://fonts.gstatic.com/s/roboto/v30/other.woff2")
+        );
+    }
+}
@@ -75,6 +75,7 @@ pub(super) async fn get_font_fallback(
                         ),
                         local_font_family: Vc::cell(fallback.font_family),
                         adjustment: fallback.adjustment,
+                        weighted_adjustments: vec![],
                     }
                     .cell(),
                 )
@@ -1,7 +1,6 @@
 use std::{collections::HashMap, path::Path};
 
 use anyhow::{bail, Context, Result};
-use futures::FutureExt;
 use indexmap::IndexMap;
 use indoc::formatdoc;
 use turbo_tasks::Vc;
@@ -9,7 +8,6 @@ use turbopack_binding::{
     turbo::{
         tasks::{Completion, Value},
         tasks_bytes::stream::SingleValue,
-        tasks_env::{CommandLineProcessEnv, ProcessEnv},
         tasks_fetch::fetch,
         tasks_fs::{
             json::parse_json_with_source_context, DiskFileSystem, File, FileContent, FileSystem,
@@ -36,6 +34,10 @@ use turbopack_binding::{
 };
 
 use self::{
+    cache::{
+        get_cached_stylesheet, get_font_file_source, self_host_font_files, FontFileSource,
+        StylesheetFontFiles,
+    },
     font_fallback::get_font_fallback,
     options::{options_from_request, FontDataEntry, FontWeights, NextFontGoogleOptions},
     stylesheet::build_stylesheet,
@@ -43,14 +45,17 @@ use self::{
 };
 use super::{
     font_fallback::FontFallback,
+    font_file::has_size_adjust,
     util::{
         get_request_hash, get_request_id, get_scoped_font_family, FontCssProperties, FontFamilyType,
     },
 };
 use crate::{embed_js::next_js_file_path, util::load_next_js_templateon};
 
+pub mod cache;
 pub mod font_fallback;
 pub mod options;
+pub mod prefetch;
 pub mod request;
 pub mod stylesheet;
 pub mod util;
@@ -218,20 +223,43 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
             get_request_id(options.font_family(), request_hash).await?
         ));
 
+        let font_fallback = get_font_fallback(self.project_path, options, request_hash);
+
+        let options_ref = options.await?;
+        let preloaded_subsets = match &options_ref.subsets {
+            Some(subsets) if options_ref.preload => subsets.as_slice(),
+            _ => &[],
+        };
+        let font_files = StylesheetFontFiles {
+            preloaded_subsets,
+            has_size_adjust: has_size_adjust([font_fallback]).await?,
+        };
+
         // When running Next.js integration tests, use the mock data available in
         // process.env.NEXT_FONT_GOOGLE_MOCKED_RESPONSES instead of making real
         // requests to Google Fonts.
-        let env = Vc::upcast::<Box<dyn ProcessEnv>>(CommandLineProcessEnv::new());
-        let mocked_responses_path = &*env
-            .read("NEXT_FONT_GOOGLE_MOCKED_RESPONSES".to_string())
-            .await?;
-        let stylesheet_str = mocked_responses_path
-            .as_ref()
-            .map_or_else(
-                || fetch_real_stylesheet(stylesheet_url, css_virtual_path).boxed(),
-                |p| get_mock_stylesheet(stylesheet_url, p, self.execution_context).boxed(),
-            )
-            .await?;
+        let font_file_source = get_font_file_source(self.project_path).await?;
+        let stylesheet_str = match &font_file_source {
+            FontFileSource::Mocked(p) => {
+                get_mock_stylesheet(stylesheet_url, p, self.execution_context).await?
+            }
+            // Stylesheets and font files are read from (and stored in) the persistent cache
+            // when one is configured, which allows building without network access.
+            FontFileSource::Cache(cache) => {
+                get_cached_stylesheet(*cache, stylesheet_url, css_virtual_path).await?
+            }
+            FontFileSource::Fetch => {
+                fetch_real_stylesheet(stylesheet_url, css_virtual_path).await?
+            }
+        };
+        // The font files are emitted with the build, like next-font-loader does.
+        let stylesheet_str = match stylesheet_str {
+            Some(s) => Some(Vc::cell(
+                self_host_font_files(&font_file_source, &s.await?, css_virtual_path, font_files)
+                    .await?,
+            )),
+            None => None,
+        };
 
         let stylesheet = match stylesheet_str {
             Some(s) => Some(
@@ -242,7 +270,6 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
             None => None,
         };
 
-        let font_fallback = get_font_fallback(self.project_path, options, request_hash);
         let css_asset = VirtualSource::new(
             css_virtual_path,
             AssetContent::file(
@@ -0,0 +1,146 @@
+use anyhow::{bail, Result};
+use swc_core::ecma::ast::{ModuleDecl, ModuleItem, Program};
+use turbo_tasks::{Completion, Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{DirectoryContent, DirectoryEntry, FileSystemPath},
+    turbopack::{
+        core::file_source::FileSource,
+        ecmascript::{
+            parse::{parse, ParseResult},
+            EcmascriptModuleAssetType,
+        },
+    },
+};
+
+use super::{
+    cache::{
+        get_cached_stylesheet, google_fonts_cache, self_host_font_files, FontFileSource,
+        StylesheetFontFiles, NEXT_FONT_GOOGLE_CACHE_DIR_ENV,
+    },
+    font_options_from_query_map, get_stylesheet_url_from_options, load_font_data,
+};
+use crate::{
+    embed_js::next_js_file_path,
+    next_font::util::{get_request_hash, get_request_id},
+    next_shared::transforms::next_font::get_next_font_transform,
+};
+
+const SOURCE_EXTENSIONS: &[&str] = &[".js", ".jsx", ".mjs", ".cjs", ".ts", ".tsx", ".mts", ".cts"];
+
+/// Fetches the stylesheets and font files of every `next/font/google` call in
+/// the project into the Google Fonts cache, so that later builds don't need
+/// network access.
+#[turbo_tasks::function]
+pub async fn prefetch_google_fonts(project_path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+    let Some(cache) = *google_fonts_cache(project_path).await? else {
+        bail!(
+            "{} must be set to prefetch Google Fonts",
+            NEXT_FONT_GOOGLE_CACHE_DIR_ENV
+        );
+    };
+
+    let font_data = load_font_data(project_path);
+    for source_file in find_source_files(project_path).await? {
+        for query in google_font_queries(source_file).await?.iter() {
+            let query = Vc::cell(query.clone());
+            let options = font_options_from_query_map(query, font_data);
+            let stylesheet_url = get_stylesheet_url_from_options(options, font_data);
+            let css_virtual_path =
+                next_js_file_path("internal/font/google".to_string()).join(format!(
+                    "/{}.module.css",
+                    get_request_id(options.font_family(), get_request_hash(query)).await?
+                ));
+            let Some(stylesheet) =
+                get_cached_stylesheet(cache, stylesheet_url, css_virtual_path).await?
+            else {
+                continue;
+            };
+            // Only the cache is filled, so how the font files are used doesn't matter.
+            self_host_font_files(
+                &FontFileSource::Cache(cache),
+                &stylesheet.await?,
+                css_virtual_path,
+                StylesheetFontFiles::default(),
+            )
+            .await?;
+        }
+    }
+
+    Ok(Completion::new())
+}
+
+/// Finds the JavaScript and TypeScript files of the project, skipping
+/// `node_modules` and hidden directories like `.next`.
+async fn find_source_files(project_path: Vc<FileSystemPath>) -> Result<Vec<Vc<FileSystemPath>>> {
+    let mut files = vec![];
+    let mut queue = vec![project_path];
+    while let Some(dir) = queue.pop() {
+        let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+            continue;
+        };
+        for (name, entry) in entries.iter() {
+            match *entry {
+                DirectoryEntry::File(path)
+                    if SOURCE_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) =>
+                {
+                    files.push(path)
+                }
+                DirectoryEntry::Directory(path)
+                    if !name.starts_with('.') && name != "node_modules" =>
+                {
+                    queue.push(path)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+#[turbo_tasks::value(transparent)]
+struct GoogleFontQueries(Vec<String>);
+
+/// Returns the queries of the `next/font/google/target.css` imports the font
+/// transform generates for the font loader calls in `path`.
+#[turbo_tasks::function]
+async fn google_font_queries(path: Vc<FileSystemPath>) -> Result<Vc<GoogleFontQueries>> {
+    let file_path = &path.await?.path;
+    let ty = if [".ts", ".tsx", ".mts", ".cts"]
+        .iter()
+        .any(|ext| file_path.ends_with(ext))
+    {
+        EcmascriptModuleAssetType::Typescript
+    } else {
+        EcmascriptModuleAssetType::Ecmascript
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module),
+        ..
+    } = &*parse(
+        Vc::upcast(FileSource::new(path)),
+        Value::new(ty),
+        Vc::cell(vec![get_next_font_transform()]),
+    )
+    .await?
+    else {
+        return Ok(Vc::cell(vec![]));
+    };
+
+    Ok(Vc::cell(
+        module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => Some(&*import.src.value),
+                _ => None,
+            })
+            .filter_map(|src| {
+                src.strip_prefix("next/font/google/target.css")
+                    .or_else(|| src.strip_prefix("@next/font/google/target.css"))
+            })
+            .map(ToOwned::to_owned)
+            .collect(),
+    ))
+}
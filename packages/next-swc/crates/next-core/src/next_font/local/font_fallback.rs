@@ -1,8 +1,10 @@
 use allsorts::{
+    binary::read::ReadScope,
     font_data::{DynamicFontTableProvider, FontData},
+    tables::{variable_fonts::fvar::FvarTable, Fixed, FontTableProvider},
     Font,
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use turbo_tasks::Vc;
 use turbopack_binding::turbo::tasks_fs::{FileContent, FileSystemPath};
 
@@ -13,7 +15,7 @@ use super::{
 use crate::next_font::{
     font_fallback::{
         AutomaticFontFallback, DefaultFallbackFont, FontAdjustment, FontFallback, FontFallbacks,
-        DEFAULT_SANS_SERIF_FONT, DEFAULT_SERIF_FONT,
+        WeightedFontAdjustment, DEFAULT_SANS_SERIF_FONT, DEFAULT_SERIF_FONT,
     },
     util::{get_scoped_font_family, FontFamilyType},
 };
@@ -47,6 +49,12 @@ pub(super) async fn get_font_fallbacks(
                     adjustment: Some(
                         get_font_adjustment(context, options_vc, &DEFAULT_SANS_SERIF_FONT).await?,
                     ),
+                    weighted_adjustments: get_weighted_font_adjustments(
+                        context,
+                        options_vc,
+                        &DEFAULT_SANS_SERIF_FONT,
+                    )
+                    .await?,
                 }
                 .cell(),
             )
@@ -60,6 +68,12 @@ pub(super) async fn get_font_fallbacks(
                     adjustment: Some(
                         get_font_adjustment(context, options_vc, &DEFAULT_SERIF_FONT).await?,
                     ),
+                    weighted_adjustments: get_weighted_font_adjustments(
+                        context,
+                        options_vc,
+                        &DEFAULT_SERIF_FONT,
+                    )
+                    .await?,
                 }
                 .cell(),
             )
@@ -82,17 +96,171 @@ async fn get_font_adjustment(
 ) -> Result<FontAdjustment> {
     let options = &*options.await?;
     let main_descriptor = pick_font_for_fallback_generation(&options.fonts)?;
-    let font_file = &*context.join(main_descriptor.path.clone()).read().await?;
+    let weight = main_descriptor
+        .weight
+        .as_ref()
+        .or(options.default_weight.as_ref())
+        .map(|weight| weight_closest_to(weight, NORMAL_WEIGHT))
+        .transpose()?;
+
+    get_descriptor_adjustment(context, main_descriptor, weight, fallback_font).await
+}
+
+/// Computes one adjustment per declared weight range, so that e.g. bold
+/// headings get a fallback matching the bold font. Returns nothing when all
+/// fonts share the same weight, in which case the single adjustment from
+/// [get_font_adjustment] is used.
+async fn get_weighted_font_adjustments(
+    context: Vc<FileSystemPath>,
+    options: Vc<NextFontLocalOptions>,
+    fallback_font: &DefaultFallbackFont,
+) -> Result<Vec<WeightedFontAdjustment>> {
+    let options = &*options.await?;
+    let fonts = match &options.fonts {
+        FontDescriptors::One(descriptor) => vec![descriptor],
+        FontDescriptors::Many(descriptors) => descriptors.iter().collect(),
+    };
+
+    let mut ranges = vec![];
+    for font in fonts {
+        let Some(weight) = font.weight.as_ref().or(options.default_weight.as_ref()) else {
+            continue;
+        };
+        for range in fallback_weight_ranges(weight)? {
+            ranges.push((font, range));
+        }
+    }
+
+    if ranges.len() < 2 {
+        return Ok(vec![]);
+    }
+
+    let mut adjustments = Vec::with_capacity(ranges.len());
+    for (font, range) in ranges {
+        adjustments.push(WeightedFontAdjustment {
+            weight: range.weight,
+            style: font.style.clone().or_else(|| options.default_style.clone()),
+            adjustment: get_descriptor_adjustment(
+                context,
+                font,
+                Some(range.measured_at),
+                fallback_font,
+            )
+            .await?,
+        });
+    }
+
+    Ok(adjustments)
+}
+
+/// A range of weights of a declared font that gets its own fallback rule.
+#[derive(Debug, PartialEq)]
+struct FallbackWeightRange {
+    /// The css `font-weight` descriptor of the fallback rule
+    weight: String,
+    /// The weight the font metrics are read at
+    measured_at: f64,
+}
+
+/// Fixed weights map to a single range. Variable weight ranges spanning both
+/// normal and bold text are split in two, so that bold text gets metrics read
+/// at the bold instance of the font.
+fn fallback_weight_ranges(weight: &FontWeight) -> Result<Vec<FallbackWeightRange>> {
+    Ok(match weight {
+        FontWeight::Fixed(val) => vec![FallbackWeightRange {
+            weight: val.clone(),
+            measured_at: parse_weight_string(val)?,
+        }],
+        FontWeight::Variable(start, end) => {
+            let start = parse_weight_string(start)?;
+            let end = parse_weight_string(end)?;
+            let split_at = ((NORMAL_WEIGHT + BOLD_WEIGHT) / 2.0).round();
+
+            if start <= NORMAL_WEIGHT && end >= BOLD_WEIGHT {
+                vec![
+                    FallbackWeightRange {
+                        weight: format!("{} {}", start, split_at - 1.0),
+                        measured_at: NORMAL_WEIGHT,
+                    },
+                    FallbackWeightRange {
+                        weight: format!("{} {}", split_at, end),
+                        measured_at: BOLD_WEIGHT,
+                    },
+                ]
+            } else {
+                vec![FallbackWeightRange {
+                    weight: format!("{} {}", start, end),
+                    measured_at: NORMAL_WEIGHT.max(start).min(end),
+                }]
+            }
+        }
+    })
+}
+
+/// Returns the weight within `weight` that is closest to `target`.
+fn weight_closest_to(weight: &FontWeight, target: f64) -> Result<f64> {
+    Ok(match weight {
+        FontWeight::Fixed(val) => parse_weight_string(val)?,
+        FontWeight::Variable(start, end) => target
+            .max(parse_weight_string(start)?)
+            .min(parse_weight_string(end)?),
+    })
+}
+
+/// Reads the metrics of a font file and computes the adjustment for the
+/// fallback font. For variable fonts, the metrics are read at the instance
+/// for `weight`.
+async fn get_descriptor_adjustment(
+    context: Vc<FileSystemPath>,
+    descriptor: &FontDescriptor,
+    weight: Option<f64>,
+    fallback_font: &DefaultFallbackFont,
+) -> Result<FontAdjustment> {
+    let font_file = &*context.join(descriptor.path.clone()).read().await?;
     let font_file_rope = match font_file {
         FileContent::NotFound => bail!("Expected font file content"),
         FileContent::Content(file) => file.content(),
     };
 
     let font_file_binary = font_file_rope.to_bytes()?;
-    let scope = allsorts::binary::read::ReadScope::new(&font_file_binary);
+    let instance_binary = match weight {
+        Some(weight) => instance_variable_font(&font_file_binary, weight, &descriptor.path)?,
+        None => None,
+    };
+    let metrics = read_font_metrics(
+        instance_binary.as_deref().unwrap_or(&font_file_binary),
+        &descriptor.path,
+    )?;
+
+    let fallback_avg_width = fallback_font.az_avg_width / fallback_font.units_per_em as f64;
+    // TODO: Use xWidthAvg like next/google.
+    //       JS implementation: https://github.com/seek-oss/capsize/blob/42d6dc39d58247bc6b9e013a4b1c4463bf287dca/packages/unpack/src/index.ts#L7-L83
+    let size_adjust = match metrics.az_avg_width {
+        Some(az_avg_width) => az_avg_width as f64 / metrics.units_per_em / fallback_avg_width,
+        None => 1.0,
+    };
+
+    Ok(FontAdjustment {
+        ascent: metrics.ascender / (metrics.units_per_em * size_adjust),
+        descent: metrics.descender / (metrics.units_per_em * size_adjust),
+        line_gap: metrics.line_gap / (metrics.units_per_em * size_adjust),
+        size_adjust,
+    })
+}
+
+struct FontMetrics {
+    az_avg_width: Option<f32>,
+    units_per_em: f64,
+    ascender: f64,
+    descender: f64,
+    line_gap: f64,
+}
+
+fn read_font_metrics(font_binary: &[u8], path: &str) -> Result<FontMetrics> {
+    let scope = ReadScope::new(font_binary);
     let mut font = Font::new(scope.read::<FontData>()?.table_provider(0)?)?.context(format!(
         "Unable to read font metrics from font file at {}",
-        &main_descriptor.path,
+        path,
     ))?;
 
     let az_avg_width = calc_average_width(&mut font);
@@ -100,24 +268,77 @@ async fn get_font_adjustment(
         .head_table()?
         .context(format!(
             "Unable to read font scale from font file at {}",
-            &main_descriptor.path
+            path
         ))?
         .units_per_em as f64;
 
-    let fallback_avg_width = fallback_font.az_avg_width / fallback_font.units_per_em as f64;
-    // TODO: Use xWidthAvg like next/google.
-    //       JS implementation: https://github.com/seek-oss/capsize/blob/42d6dc39d58247bc6b9e013a4b1c4463bf287dca/packages/unpack/src/index.ts#L7-L83
-    let size_adjust = match az_avg_width {
-        Some(az_avg_width) => az_avg_width as f64 / units_per_em / fallback_avg_width,
-        None => 1.0,
+    Ok(FontMetrics {
+        az_avg_width,
+        units_per_em,
+        ascender: font.hhea_table.ascender as f64,
+        descender: font.hhea_table.descender as f64,
+        line_gap: font.hhea_table.line_gap as f64,
+    })
+}
+
+/// Creates a static instance of a variable font at the given weight, with
+/// every other axis at its default except for `wdth`, which is set to the
+/// normal width like the css `font-stretch` default. The instancing applies
+/// the `avar` mappings and the metrics variations of the font. Returns `None`
+/// for fonts without an `fvar` table.
+fn instance_variable_font(font_binary: &[u8], weight: f64, path: &str) -> Result<Option<Vec<u8>>> {
+    let scope = ReadScope::new(font_binary);
+    let font_data = scope.read::<FontData>()?;
+    let provider = font_data.table_provider(0)?;
+    let Some(fvar_data) = provider.table_data(FVAR_TAG)? else {
+        return Ok(None);
     };
 
-    Ok(FontAdjustment {
-        ascent: font.hhea_table.ascender as f64 / (units_per_em * size_adjust),
-        descent: font.hhea_table.descender as f64 / (units_per_em * size_adjust),
-        line_gap: font.hhea_table.line_gap as f64 / (units_per_em * size_adjust),
-        size_adjust,
-    })
+    let user_instance = parse_fvar_axes(&fvar_data)?
+        .iter()
+        .map(|axis| {
+            let value = match axis.tag {
+                WGHT_TAG => weight,
+                WDTH_TAG => NORMAL_WIDTH,
+                _ => axis.default_value,
+            };
+            Fixed::from(value.max(axis.min_value).min(axis.max_value) as f32)
+        })
+        .collect::<Vec<_>>();
+
+    let (instance, _) = allsorts::variations::instance(&provider, &user_instance)
+        .map_err(|err| anyhow!("Unable to instance variable font at {}: {}", path, err))?;
+
+    Ok(Some(instance))
+}
+
+const FVAR_TAG: u32 = u32::from_be_bytes(*b"fvar");
+const WGHT_TAG: u32 = u32::from_be_bytes(*b"wght");
+const WDTH_TAG: u32 = u32::from_be_bytes(*b"wdth");
+static NORMAL_WIDTH: f64 = 100.0;
+
+/// A variation axis, as declared in the `fvar` table.
+#[derive(Debug, PartialEq)]
+struct VariationAxis {
+    tag: u32,
+    min_value: f64,
+    default_value: f64,
+    max_value: f64,
+}
+
+/// Reads the variation axes from an `fvar` table.
+fn parse_fvar_axes(fvar: &[u8]) -> Result<Vec<VariationAxis>> {
+    let fvar = ReadScope::new(fvar).read::<FvarTable<'_>>()?;
+
+    Ok(fvar
+        .axes()
+        .map(|axis| VariationAxis {
+            tag: axis.axis_tag,
+            min_value: f32::from(axis.min_value) as f64,
+            default_value: f32::from(axis.default_value) as f64,
+            max_value: f32::from(axis.max_value) as f64,
+        })
+        .collect())
 }
 
 fn calc_average_width(font: &mut Font<DynamicFontTableProvider>) -> Option<f32> {
@@ -262,7 +483,10 @@ mod tests {
     use anyhow::Result;
 
     use crate::next_font::local::{
-        font_fallback::pick_font_for_fallback_generation,
+        font_fallback::{
+            fallback_weight_ranges, parse_fvar_axes, pick_font_for_fallback_generation,
+            FallbackWeightRange, VariationAxis, WDTH_TAG, WGHT_TAG,
+        },
         options::{FontDescriptor, FontDescriptors, FontWeight},
     };
 
@@ -435,4 +659,88 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_fixed_weight_has_single_fallback_range() -> Result<()> {
+        assert_eq!(
+            fallback_weight_ranges(&FontWeight::Fixed("bold".to_owned()))?,
+            vec![FallbackWeightRange {
+                weight: "bold".to_owned(),
+                measured_at: 700.0,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_splits_variable_weight_spanning_normal_and_bold() -> Result<()> {
+        assert_eq!(
+            fallback_weight_ranges(&FontWeight::Variable("100".to_owned(), "900".to_owned()))?,
+            vec![
+                FallbackWeightRange {
+                    weight: "100 549".to_owned(),
+                    measured_at: 400.0,
+                },
+                FallbackWeightRange {
+                    weight: "550 900".to_owned(),
+                    measured_at: 700.0,
+                }
+            ]
+        );
+
+        assert_eq!(
+            fallback_weight_ranges(&FontWeight::Variable("500".to_owned(), "600".to_owned()))?,
+            vec![FallbackWeightRange {
+                weight: "500 600".to_owned(),
+                measured_at: 500.0,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parses_fvar_axes() -> Result<()> {
+        fn fixed(value: i32) -> [u8; 4] {
+            (value << 16).to_be_bytes()
+        }
+
+        // Header: version 1.0, axes at offset 16, 2 axes of 20 bytes, no instances
+        // of 12 bytes.
+        let mut fvar = vec![0, 1, 0, 0, 0, 16, 0, 2, 0, 2, 0, 20, 0, 0, 0, 12];
+        for (tag, min, default, max) in [(b"wght", 100, 400, 900), (b"wdth", 75, 100, 125)] {
+            fvar.extend_from_slice(tag);
+            fvar.extend_from_slice(&fixed(min));
+            fvar.extend_from_slice(&fixed(default));
+            fvar.extend_from_slice(&fixed(max));
+            // Flags and axis name id.
+            fvar.extend_from_slice(&[0, 0, 1, 0]);
+        }
+
+        assert_eq!(
+            parse_fvar_axes(&fvar)?,
+            vec![
+                VariationAxis {
+                    tag: WGHT_TAG,
+                    min_value: 100.0,
+                    default_value: 400.0,
+                    max_value: 900.0,
+                },
+                VariationAxis {
+                    tag: WDTH_TAG,
+                    min_value: 75.0,
+                    default_value: 100.0,
+                    max_value: 125.0,
+                }
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_on_truncated_fvar() {
+        assert!(parse_fvar_axes(&[0, 1, 0, 0, 0, 16, 0, 2, 0, 1, 0, 20]).is_err());
+    }
 }
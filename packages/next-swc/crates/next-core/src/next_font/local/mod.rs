@@ -1,10 +1,12 @@
 use anyhow::{bail, Result};
 use indoc::formatdoc;
+use serde::{Deserialize, Serialize};
 use turbo_tasks::Vc;
 use turbopack_binding::{
     turbo::{
         tasks::Value,
         tasks_fs::{json::parse_json_with_source_context, FileContent, FileSystemPath},
+        tasks_hash::hash_xxh3_hash64,
     },
     turbopack::core::{
         asset::AssetContent,
@@ -25,6 +27,7 @@ use self::{
 };
 use super::{font_fallback::FontFallbacks, util::FontCssProperties};
 use crate::next_font::{
+    font_file::NextFontFileFlags,
     local::options::FontWeight,
     util::{get_request_hash, get_request_id},
 };
@@ -33,8 +36,13 @@ pub mod font_fallback;
 pub mod options;
 pub mod request;
 pub mod stylesheet;
+pub mod subset;
 pub mod util;
 
+/// The request path used in `url()`s of `@font-face` rules for font files that
+/// are used as-is. Intercepted by [NextFontLocalFileReplacer].
+pub const NEXT_FONT_LOCAL_FILE_REQUEST: &str = "@vercel/turbopack-next/internal/font/local/file";
+
 #[turbo_tasks::value(shared)]
 pub(crate) struct NextFontLocalReplacer {
     project_path: Vc<FileSystemPath>,
@@ -188,6 +196,92 @@ impl ImportMappingReplacement for NextFontLocalCssModuleReplacer {
     }
 }
 
+/// The query of a [NEXT_FONT_LOCAL_FILE_REQUEST].
+#[derive(Debug, Deserialize, Serialize)]
+struct FontFileRequest {
+    /// The path of the font file, relative to the module calling `localFont`.
+    path: String,
+    #[serde(flatten)]
+    flags: NextFontFileFlags,
+}
+
+/// Returns the `url()` the font file at `path` is loaded from.
+fn get_font_file_url(path: &str, flags: NextFontFileFlags) -> Result<String> {
+    let request = serde_json::to_string(&FontFileRequest {
+        path: path.to_owned(),
+        flags,
+    })?;
+
+    Ok(format!(
+        "{}?{}",
+        NEXT_FONT_LOCAL_FILE_REQUEST,
+        qstring::QString::new(vec![(request, "")])
+    ))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct NextFontLocalFileReplacer;
+
+#[turbo_tasks::value_impl]
+impl NextFontLocalFileReplacer {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        Self::cell(NextFontLocalFileReplacer)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NextFontLocalFileReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    /// Intercepts `url()` requests made by the `@font-face` rules of a local
+    /// font. Returns the font file under a name carrying its
+    /// [NextFontFileFlags].
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module {
+            module: _,
+            path: _,
+            query: query_vc,
+        } = &*request.await?
+        else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let query_map = qstring::QString::from(&**query_vc.await?);
+        let Some((json, _)) = query_map.into_iter().next() else {
+            bail!("Expected one entry");
+        };
+        let font_file_request: FontFileRequest = parse_json_with_source_context(&json)?;
+
+        let font_file = context.join(font_file_request.path.clone());
+        if let FileContent::NotFound = &*font_file.read().await? {
+            return Ok(ImportMapResult::NoEntry.into());
+        }
+
+        let ext = font_file_request
+            .path
+            .rsplit_once('.')
+            .map_or("", |(_, ext)| ext);
+        let font_asset = VirtualSource::new(
+            context.join(font_file_request.flags.file_name(
+                &format!("{:x}", hash_xxh3_hash64(&font_file_request.path)),
+                ext,
+            )),
+            AssetContent::file(font_file.read()),
+        );
+
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(font_asset)).into()).into())
+    }
+}
+
 #[turbo_tasks::function]
 async fn get_font_css_properties(
     options_vc: Vc<NextFontLocalOptions>,
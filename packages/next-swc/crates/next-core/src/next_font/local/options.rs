@@ -1,13 +1,16 @@
 use std::{fmt::Display, str::FromStr};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use turbo_tasks::Vc;
 use turbopack_binding::turbo::tasks::{trace::TraceRawVcs, Value};
 
-use super::request::{
-    AdjustFontFallback, NextFontLocalRequest, NextFontLocalRequestArguments, SrcDescriptor,
-    SrcRequest,
+use super::{
+    request::{
+        AdjustFontFallback, NextFontLocalRequest, NextFontLocalRequestArguments, SrcDescriptor,
+        SrcRequest, SubsetRequest,
+    },
+    subset::UnicodeRange,
 };
 
 /// A normalized, Vc-friendly struct derived from validating and transforming
@@ -32,6 +35,9 @@ pub(super) struct NextFontLocalOptions {
     /// The name of the variable assigned to the results of calling the
     /// `localFont` function. This is used as the font family's base name.
     pub variable_name: String,
+    /// The unicode ranges each font file is subset to. When set, every font
+    /// file is emitted once per range instead of as-is.
+    pub subsets: Option<Vec<UnicodeRange>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -140,6 +146,7 @@ pub(super) fn options_from_request(request: &NextFontLocalRequest) -> Result<Nex
         src,
         adjust_font_fallback,
         variable,
+        subset,
     } = &request.arguments.0;
 
     let fonts = match src {
@@ -168,9 +175,26 @@ pub(super) fn options_from_request(request: &NextFontLocalRequest) -> Result<Nex
         variable_name: request.variable_name.to_owned(),
         default_weight: weight.as_ref().and_then(|s| s.parse().ok()),
         default_style: style.to_owned(),
+        subsets: subset.as_ref().map(subsets_from_request).transpose()?,
     })
 }
 
+fn subsets_from_request(subset: &SubsetRequest) -> Result<Vec<UnicodeRange>> {
+    let mut subsets = vec![];
+    if let Some(text) = &subset.text {
+        subsets.push(UnicodeRange::from_text(text)?);
+    }
+    for unicode_range in subset.unicode_ranges.iter().flatten() {
+        subsets.push(UnicodeRange::parse(unicode_range)?);
+    }
+
+    if subsets.is_empty() {
+        bail!("Expected `subset` to declare either `text` or `unicodeRanges`");
+    }
+
+    Ok(subsets)
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -180,6 +204,7 @@ mod tests {
     use crate::next_font::local::{
         options::{FontDescriptor, FontDescriptors, FontWeight},
         request::{AdjustFontFallback, NextFontLocalRequest},
+        subset::UnicodeRange,
     };
 
     #[test]
@@ -213,7 +238,8 @@ mod tests {
                 fallback: None,
                 adjust_font_fallback: AdjustFontFallback::Arial,
                 variable: None,
-                variable_name: "myFont".to_owned()
+                variable_name: "myFont".to_owned(),
+                subsets: None,
             },
         );
 
@@ -268,7 +294,8 @@ mod tests {
                 fallback: None,
                 adjust_font_fallback: AdjustFontFallback::Arial,
                 variable: None,
-                variable_name: "myFont".to_owned()
+                variable_name: "myFont".to_owned(),
+                subsets: None,
             },
         );
 
@@ -341,10 +368,62 @@ mod tests {
                 fallback: Some(vec!["Fallback".to_owned()]),
                 adjust_font_fallback: AdjustFontFallback::TimesNewRoman,
                 variable: Some("myvar".to_owned()),
-                variable_name: "myFont".to_owned()
+                variable_name: "myFont".to_owned(),
+                subsets: None,
             },
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_subset() -> Result<()> {
+        let request: NextFontLocalRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": [{
+                    "src": "./NotoSansSC-Regular.ttf",
+                    "subset": {
+                        "text": "你好",
+                        "unicodeRanges": ["U+0000-00FF, U+0131"]
+                    }
+                }]
+            }
+        "#,
+        )?;
+
+        assert_eq!(
+            options_from_request(&request)?.subsets,
+            Some(vec![
+                UnicodeRange::parse("U+4F60, U+597D")?,
+                UnicodeRange::parse("U+0-FF, U+131")?,
+            ]),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_subset_fails() -> Result<()> {
+        let request: NextFontLocalRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "",
+                "path": "index.js",
+                "variableName": "myFont",
+                "arguments": [{
+                    "src": "./NotoSansSC-Regular.ttf",
+                    "subset": {}
+                }]
+            }
+        "#,
+        )?;
+
+        assert!(options_from_request(&request).is_err());
+
+        Ok(())
+    }
 }
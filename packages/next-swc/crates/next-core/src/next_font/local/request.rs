@@ -28,6 +28,18 @@ pub(super) struct NextFontLocalRequestArguments {
     )]
     pub adjust_font_fallback: AdjustFontFallback,
     pub variable: Option<String>,
+    pub subset: Option<SubsetRequest>,
+}
+
+/// Reduces the font files to the given characters and/or unicode ranges at
+/// build time. Each entry is emitted as its own font file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SubsetRequest {
+    /// The characters the font is used for, e.g. a page's headings.
+    pub text: Option<String>,
+    /// css `unicode-range` values, e.g. `U+0000-00FF` or `U+4E00-9FFF`.
+    pub unicode_ranges: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
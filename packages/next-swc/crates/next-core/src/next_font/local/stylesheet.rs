@@ -2,9 +2,14 @@ use anyhow::{bail, Result};
 use indoc::formatdoc;
 use turbo_tasks::Vc;
 
-use super::options::{FontDescriptors, NextFontLocalOptions};
+use super::{
+    get_font_file_url,
+    options::{FontDescriptors, NextFontLocalOptions},
+    subset::get_subset_url,
+};
 use crate::next_font::{
     font_fallback::FontFallbacks,
+    font_file::{has_size_adjust, NextFontFileFlags},
     stylesheet::{build_fallback_definition, build_font_class_rules},
     util::{get_scoped_font_family, FontCssProperties, FontFamilyType},
 };
@@ -28,7 +33,7 @@ pub(super) async fn build_stylesheet(
         {}
         {}
     "#,
-        *build_font_face_definitions(scoped_font_family, options).await?,
+        *build_font_face_definitions(scoped_font_family, options, fallbacks).await?,
         (*build_fallback_definition(fallbacks).await?),
         *build_font_class_rules(css_properties).await?
     )))
@@ -39,8 +44,13 @@ pub(super) async fn build_stylesheet(
 pub(super) async fn build_font_face_definitions(
     scoped_font_family: Vc<String>,
     options: Vc<NextFontLocalOptions>,
+    fallbacks: Vc<FontFallbacks>,
 ) -> Result<Vc<String>> {
     let options = &*options.await?;
+    let flags = NextFontFileFlags {
+        preload: options.preload,
+        has_size_adjust: has_size_adjust(fallbacks.await?.iter().copied()).await?,
+    };
 
     let mut definitions = String::new();
     let fonts = match &options.fonts {
@@ -49,30 +59,58 @@ pub(super) async fn build_font_face_definitions(
     };
 
     for font in fonts {
-        definitions.push_str(&formatdoc!(
-            r#"
-            @font-face {{
-                font-family: '{}';
-                src: url('{}') format('{}');
-                font-display: {};
-                {}{}
-            }}
-        "#,
-            *scoped_font_family.await?,
-            &font.path,
-            ext_to_format(&font.ext)?,
-            options.display,
-            &font
-                .weight
-                .as_ref()
-                .or(options.default_weight.as_ref())
-                .map_or_else(|| "".to_owned(), |w| format!("font-weight: {};", w)),
-            &font
-                .style
-                .as_ref()
-                .or(options.default_style.as_ref())
-                .map_or_else(|| "".to_owned(), |s| format!("font-style: {};", s)),
-        ));
+        let weight = font
+            .weight
+            .as_ref()
+            .or(options.default_weight.as_ref())
+            .map_or_else(|| "".to_owned(), |w| format!("font-weight: {};", w));
+        let style = font
+            .style
+            .as_ref()
+            .or(options.default_style.as_ref())
+            .map_or_else(|| "".to_owned(), |s| format!("font-style: {};", s));
+
+        let Some(subsets) = &options.subsets else {
+            definitions.push_str(&formatdoc!(
+                r#"
+                @font-face {{
+                    font-family: '{}';
+                    src: url('{}') format('{}');
+                    font-display: {};
+                    {}{}
+                }}
+            "#,
+                *scoped_font_family.await?,
+                get_font_file_url(&font.path, flags)?,
+                ext_to_format(&font.ext)?,
+                options.display,
+                weight,
+                style,
+            ));
+            continue;
+        };
+
+        // Each subset is its own font file, which browsers only download when
+        // the page uses a character in its range.
+        for unicode_range in subsets {
+            definitions.push_str(&formatdoc!(
+                r#"
+                @font-face {{
+                    font-family: '{}';
+                    src: url('{}') format('woff2');
+                    font-display: {};
+                    unicode-range: {};
+                    {}{}
+                }}
+            "#,
+                *scoped_font_family.await?,
+                get_subset_url(&font.path, unicode_range, flags)?,
+                options.display,
+                unicode_range,
+                weight,
+                style,
+            ));
+        }
     }
 
     Ok(Vc::cell(definitions))
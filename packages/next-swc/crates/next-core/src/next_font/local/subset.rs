@@ -0,0 +1,474 @@
+use std::{collections::BTreeSet, fmt::Display, io::Write};
+
+use allsorts::{
+    binary::read::ReadScope, font_data::FontData, subset::subset, tables::cmap::CmapSubtable, Font,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::{
+        tasks::trace::TraceRawVcs,
+        tasks_fs::{
+            json::parse_json_with_source_context, rope::Rope, File, FileContent, FileSystemPath,
+        },
+    },
+    turbopack::core::{
+        asset::AssetContent,
+        resolve::{
+            options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+            parse::Request,
+            ResolveResult,
+        },
+        virtual_source::VirtualSource,
+    },
+};
+
+use crate::next_font::{font_file::NextFontFileFlags, util::get_request_hash};
+
+/// The request path used in `url()`s of `@font-face` rules for subsetted font
+/// files. Intercepted by [NextFontLocalSubsetReplacer].
+pub const NEXT_FONT_LOCAL_SUBSET_REQUEST: &str =
+    "@vercel/turbopack-next/internal/font/local/subset.woff2";
+
+/// A set of codepoints a font file is reduced to. Each subset is emitted as a
+/// separate font file, with a matching `unicode-range` descriptor.
+#[derive(
+    Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, TraceRawVcs,
+)]
+pub(super) struct UnicodeRange(Vec<(u32, u32)>);
+
+impl UnicodeRange {
+    /// Builds a range covering every character in `text`.
+    pub fn from_text(text: &str) -> Result<Self> {
+        let mut codepoints: Vec<u32> = text.chars().map(|c| c as u32).collect();
+        codepoints.sort_unstable();
+        codepoints.dedup();
+
+        let mut ranges: Vec<(u32, u32)> = vec![];
+        for codepoint in codepoints {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == codepoint => *end = codepoint,
+                _ => ranges.push((codepoint, codepoint)),
+            }
+        }
+
+        if ranges.is_empty() {
+            bail!("Expected `subset.text` to contain at least one character");
+        }
+
+        Ok(Self(ranges))
+    }
+
+    /// Parses a css `unicode-range` value, e.g. `U+0000-00FF, U+0131, U+4??`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let ranges = value
+            .split(',')
+            .map(|range| parse_range(range.trim()))
+            .collect::<Result<Vec<_>>>()?;
+
+        if ranges.is_empty() {
+            bail!("Expected a non-empty unicode range");
+        }
+
+        Ok(Self(ranges))
+    }
+
+    fn contains(&self, codepoint: u32) -> bool {
+        self.0
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&codepoint))
+    }
+}
+
+fn parse_range(range: &str) -> Result<(u32, u32)> {
+    let Some(range) = range
+        .strip_prefix("U+")
+        .or_else(|| range.strip_prefix("u+"))
+    else {
+        bail!("Invalid unicode range `{}`: expected `U+` prefix", range);
+    };
+
+    let parse_codepoint = |codepoint: &str| {
+        u32::from_str_radix(codepoint, 16)
+            .with_context(|| format!("Invalid codepoint in unicode range `U+{}`", range))
+    };
+
+    let (start, end) = if let Some((start, end)) = range.split_once('-') {
+        (parse_codepoint(start)?, parse_codepoint(end)?)
+    } else if range.contains('?') {
+        (
+            parse_codepoint(&range.replace('?', "0"))?,
+            parse_codepoint(&range.replace('?', "F"))?,
+        )
+    } else {
+        let codepoint = parse_codepoint(range)?;
+        (codepoint, codepoint)
+    };
+
+    if start > end || end > 0x10FFFF {
+        bail!("Invalid unicode range `U+{}`", range);
+    }
+
+    Ok((start, end))
+}
+
+impl Display for UnicodeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranges = self
+            .0
+            .iter()
+            .map(|(start, end)| {
+                if start == end {
+                    format!("U+{:X}", start)
+                } else {
+                    format!("U+{:X}-{:X}", start, end)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", ranges.join(", "))
+    }
+}
+
+/// The query of a [NEXT_FONT_LOCAL_SUBSET_REQUEST].
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubsetFontRequest {
+    /// The path of the original font file, relative to the module calling
+    /// `localFont`.
+    path: String,
+    /// The `unicode-range` the font is subset to.
+    unicode_range: String,
+    #[serde(flatten)]
+    flags: NextFontFileFlags,
+}
+
+/// Returns the `url()` a subset of the font at `path` is loaded from.
+pub(super) fn get_subset_url(
+    path: &str,
+    unicode_range: &UnicodeRange,
+    flags: NextFontFileFlags,
+) -> Result<String> {
+    let request = serde_json::to_string(&SubsetFontRequest {
+        path: path.to_owned(),
+        unicode_range: unicode_range.to_string(),
+        flags,
+    })?;
+
+    Ok(format!(
+        "{}?{}",
+        NEXT_FONT_LOCAL_SUBSET_REQUEST,
+        qstring::QString::new(vec![(request, "")])
+    ))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct NextFontLocalSubsetReplacer {
+    project_path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl NextFontLocalSubsetReplacer {
+    #[turbo_tasks::function]
+    pub fn new(project_path: Vc<FileSystemPath>) -> Vc<Self> {
+        Self::cell(NextFontLocalSubsetReplacer { project_path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NextFontLocalSubsetReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    /// Intercepts `url()` requests made by the `@font-face` rules of a
+    /// subsetted local font. Returns a VirtualSource of the font file reduced
+    /// to the requested unicode range.
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module {
+            module: _,
+            path: _,
+            query: query_vc,
+        } = &*request.await?
+        else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let query_map = qstring::QString::from(&**query_vc.await?);
+        let Some((json, _)) = query_map.into_iter().next() else {
+            bail!("Expected one entry");
+        };
+        let subset_request: SubsetFontRequest = parse_json_with_source_context(&json)?;
+        let unicode_range = UnicodeRange::parse(&subset_request.unicode_range)?;
+
+        let font_file = &*context.join(subset_request.path.clone()).read().await?;
+        let font_file_binary = match font_file {
+            FileContent::NotFound => bail!(
+                "Expected font file content at {} to subset",
+                subset_request.path
+            ),
+            FileContent::Content(file) => file.content().to_bytes()?,
+        };
+
+        let subset_binary = subset_font(&font_file_binary, &unicode_range)
+            .with_context(|| format!("Unable to subset font file at {}", subset_request.path))?;
+        let woff2_binary = encode_woff2(&subset_binary)?;
+
+        let font_asset = VirtualSource::new(
+            context.join(subset_request.flags.file_name(
+                &format!(
+                    "__next_font_subset_{:x?}",
+                    get_request_hash(*query_vc).await?
+                ),
+                "woff2",
+            )),
+            AssetContent::file(FileContent::Content(File::from(Rope::from(woff2_binary))).into()),
+        );
+
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(font_asset)).into()).into())
+    }
+}
+
+/// Reduces the font to the glyphs needed to render `unicode_range`. Returns
+/// the subsetted font as an sfnt (TrueType/OpenType) binary.
+fn subset_font(font_binary: &[u8], unicode_range: &UnicodeRange) -> Result<Vec<u8>> {
+    let scope = ReadScope::new(font_binary);
+    let font_data = scope.read::<FontData>()?;
+    let font = Font::new(font_data.table_provider(0)?)?
+        .context("Unable to read font tables from font file")?;
+    let cmap_subtable = ReadScope::new(&font.cmap_table)
+        .offset(font.cmap_subtable_offset)
+        .read::<CmapSubtable<'_>>()?;
+
+    // Only the characters the font maps are looked up, as ranges can span all
+    // of unicode. The first glyph is always `.notdef`, which subsets have to
+    // keep.
+    let mut glyph_ids = BTreeSet::from([0]);
+    cmap_subtable.mappings_fn(|codepoint, glyph_id| {
+        if glyph_id != 0 && unicode_range.contains(codepoint) {
+            glyph_ids.insert(glyph_id);
+        }
+    })?;
+
+    Ok(subset(
+        &font_data.table_provider(0)?,
+        &glyph_ids.into_iter().collect::<Vec<_>>(),
+    )?)
+}
+
+/// The tags of the WOFF2 known table tags, indexed by their flag value.
+const WOFF2_KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// Wraps an sfnt font binary in a WOFF2 container, with all tables
+/// compressed as a single brotli stream.
+///
+/// Tables are stored untransformed, which the format allows for every table
+/// and which keeps the `glyf` and `loca` tables byte for byte.
+fn encode_woff2(sfnt: &[u8]) -> Result<Vec<u8>> {
+    const WOFF2_HEADER_SIZE: usize = 48;
+    const SFNT_HEADER_SIZE: usize = 12;
+    const SFNT_TABLE_RECORD_SIZE: usize = 16;
+    /// Flag bits of the null transform of `glyf` and `loca`. It's the
+    /// default for all other tables.
+    const NULL_TRANSFORM_GLYF_LOCA: u8 = 3 << 6;
+    const ARBITRARY_TAG: u8 = 63;
+
+    let read_u16 = |offset: usize| -> Result<u16> {
+        Ok(u16::from_be_bytes(
+            sfnt.get(offset..offset + 2)
+                .context("Unexpected end of font file")?
+                .try_into()?,
+        ))
+    };
+    let read_u32 = |offset: usize| -> Result<u32> {
+        Ok(u32::from_be_bytes(
+            sfnt.get(offset..offset + 4)
+                .context("Unexpected end of font file")?
+                .try_into()?,
+        ))
+    };
+    let pad4 = |len: usize| (len + 3) & !3;
+
+    let flavor = read_u32(0)?;
+    let num_tables = read_u16(4)? as usize;
+
+    let mut directory = vec![];
+    let mut data = vec![];
+    let mut total_sfnt_size = SFNT_HEADER_SIZE + num_tables * SFNT_TABLE_RECORD_SIZE;
+
+    for i in 0..num_tables {
+        let record = SFNT_HEADER_SIZE + i * SFNT_TABLE_RECORD_SIZE;
+        let tag = read_u32(record)?.to_be_bytes();
+        let offset = read_u32(record + 8)? as usize;
+        let length = read_u32(record + 12)? as usize;
+        let table = sfnt
+            .get(offset..offset + length)
+            .context("Font table extends past the end of the font file")?;
+
+        let known_tag = WOFF2_KNOWN_TAGS.iter().position(|known| **known == tag);
+        let mut flags = known_tag.map_or(ARBITRARY_TAG, |index| index as u8);
+        if &tag == b"glyf" || &tag == b"loca" {
+            flags |= NULL_TRANSFORM_GLYF_LOCA;
+        }
+        directory.push(flags);
+        if known_tag.is_none() {
+            directory.extend_from_slice(&tag);
+        }
+        write_uint_base128(&mut directory, length as u32);
+
+        data.extend_from_slice(table);
+        total_sfnt_size += pad4(length);
+    }
+
+    let mut encoder = brotli::CompressorWriter::new(vec![], 4096, 11, 22);
+    encoder.write_all(&data)?;
+    let compressed = encoder.into_inner();
+
+    let length = pad4(WOFF2_HEADER_SIZE + directory.len() + compressed.len());
+    let mut woff2 = Vec::with_capacity(length);
+    woff2.extend_from_slice(b"wOF2");
+    woff2.extend_from_slice(&flavor.to_be_bytes());
+    woff2.extend_from_slice(&(length as u32).to_be_bytes());
+    woff2.extend_from_slice(&(num_tables as u16).to_be_bytes());
+    // reserved
+    woff2.extend_from_slice(&0u16.to_be_bytes());
+    woff2.extend_from_slice(&(total_sfnt_size as u32).to_be_bytes());
+    woff2.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+    // majorVersion, minorVersion
+    woff2.extend_from_slice(&1u16.to_be_bytes());
+    woff2.extend_from_slice(&0u16.to_be_bytes());
+    // metaOffset, metaLength, metaOrigLength, privOffset, privLength
+    woff2.extend_from_slice(&[0; 20]);
+    woff2.extend_from_slice(&directory);
+    woff2.extend_from_slice(&compressed);
+    woff2.resize(length, 0);
+
+    Ok(woff2)
+}
+
+/// Writes a WOFF2 `UIntBase128`: big-endian groups of 7 bits, with the high
+/// bit set on all bytes but the last.
+fn write_uint_base128(out: &mut Vec<u8>, value: u32) {
+    let mut len = 1;
+    while len < 5 && value >> (7 * len) != 0 {
+        len += 1;
+    }
+    for i in (0..len).rev() {
+        let byte = ((value >> (7 * i)) & 0x7f) as u8;
+        out.push(if i == 0 { byte } else { byte | 0x80 });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{encode_woff2, write_uint_base128, UnicodeRange};
+
+    #[test]
+    fn test_unicode_range_from_text() -> Result<()> {
+        assert_eq!(
+            UnicodeRange::from_text("abcx你好a")?.to_string(),
+            "U+61-63, U+78, U+4F60, U+597D"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unicode_range_from_empty_text() {
+        assert!(UnicodeRange::from_text("").is_err());
+    }
+
+    #[test]
+    fn test_parse_unicode_range() -> Result<()> {
+        assert_eq!(
+            UnicodeRange::parse("U+0000-00FF, U+0131,u+4??")?,
+            UnicodeRange(vec![(0x0, 0xFF), (0x131, 0x131), (0x400, 0x4FF)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_unicode_range() {
+        assert!(UnicodeRange::parse("0000-00FF").is_err());
+        assert!(UnicodeRange::parse("U+00FF-0000").is_err());
+        assert!(UnicodeRange::parse("U+ZZZZ").is_err());
+        assert!(UnicodeRange::parse("U+110000").is_err());
+    }
+
+    #[test]
+    fn test_unicode_range_contains() -> Result<()> {
+        let range = UnicodeRange::parse("U+0000-00FF, U+4E00-9FFF")?;
+        assert!(range.contains(0x61));
+        assert!(range.contains(0x4F60));
+        assert!(!range.contains(0x131));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_uint_base128() {
+        let encode = |value| {
+            let mut out = vec![];
+            write_uint_base128(&mut out, value);
+            out
+        };
+        assert_eq!(encode(0), [0]);
+        assert_eq!(encode(127), [0x7f]);
+        assert_eq!(encode(128), [0x81, 0x00]);
+        assert_eq!(encode(63), [63]);
+        assert_eq!(encode(u32::MAX), [0x8f, 0xff, 0xff, 0xff, 0x7f]);
+    }
+
+    #[test]
+    fn test_encode_woff2() -> Result<()> {
+        // An sfnt with a 4-byte `glyf` table and a 2-byte table with an unknown tag.
+        let mut sfnt = vec![];
+        sfnt.extend_from_slice(&0x00010000u32.to_be_bytes());
+        sfnt.extend_from_slice(&2u16.to_be_bytes());
+        sfnt.extend_from_slice(&[0; 6]);
+        for (tag, offset, length) in [(b"glyf", 44u32, 4u32), (b"test", 48, 2)] {
+            sfnt.extend_from_slice(tag);
+            sfnt.extend_from_slice(&0u32.to_be_bytes());
+            sfnt.extend_from_slice(&offset.to_be_bytes());
+            sfnt.extend_from_slice(&length.to_be_bytes());
+        }
+        sfnt.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+
+        let woff2 = encode_woff2(&sfnt)?;
+
+        assert_eq!(&woff2[0..4], b"wOF2");
+        assert_eq!(&woff2[4..8], &0x00010000u32.to_be_bytes());
+        // length
+        assert_eq!(&woff2[8..12], &(woff2.len() as u32).to_be_bytes());
+        assert_eq!(woff2.len() % 4, 0);
+        // numTables
+        assert_eq!(&woff2[12..14], &2u16.to_be_bytes());
+        // totalSfntSize
+        assert_eq!(&woff2[16..20], &(12 + 2 * 16 + 4 + 4u32).to_be_bytes());
+        // table directory: `glyf` with the null transform, then the unknown tag
+        assert_eq!(&woff2[48..50], &[10 | 3 << 6, 4]);
+        assert_eq!(&woff2[50..56], &[63, b't', b'e', b's', b't', 2]);
+
+        let compressed_size = u32::from_be_bytes(woff2[20..24].try_into()?) as usize;
+        let mut data = vec![];
+        brotli::BrotliDecompress(&mut &woff2[56..56 + compressed_size], &mut data)?;
+        assert_eq!(data, [1, 2, 3, 4, 5, 6]);
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 pub(crate) mod font_fallback;
+pub(crate) mod font_file;
 pub(crate) mod google;
 pub(crate) mod issue;
 pub(crate) mod local;
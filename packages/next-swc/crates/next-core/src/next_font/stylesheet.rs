@@ -3,7 +3,7 @@ use indoc::formatdoc;
 use turbo_tasks::Vc;
 
 use super::{
-    font_fallback::{FontFallback, FontFallbacks},
+    font_fallback::{FontAdjustment, FontFallback, FontFallbacks},
     util::FontCssProperties,
 };
 
@@ -15,34 +15,50 @@ pub(crate) async fn build_fallback_definition(fallbacks: Vc<FontFallbacks>) -> R
         if let FontFallback::Automatic(fallback) = &*fallback_vc.await? {
             let fallback = fallback.await?;
 
-            let override_properties = match &fallback.adjustment {
-                None => "".to_owned(),
-                Some(adjustment) => formatdoc!(
+            let scoped_font_family = fallback.scoped_font_family.await?;
+            let local_font_family = fallback.local_font_family.await?;
+
+            if fallback.weighted_adjustments.is_empty() {
+                res.push_str(&formatdoc!(
                     r#"
-                    ascent-override: {}%;
-                    descent-override: {}%;
-                    line-gap-override: {}%;
-                    size-adjust: {}%;
+                    @font-face {{
+                        font-family: '{}';
+                        src: local("{}");
+                        {}
+                    }}
                 "#,
-                    format_fixed_percentage(adjustment.ascent),
-                    format_fixed_percentage(adjustment.descent.abs()),
-                    format_fixed_percentage(adjustment.line_gap),
-                    format_fixed_percentage(adjustment.size_adjust)
-                ),
-            };
+                    scoped_font_family,
+                    local_font_family,
+                    fallback
+                        .adjustment
+                        .as_ref()
+                        .map(format_override_properties)
+                        .unwrap_or_default()
+                ));
+                continue;
+            }
 
-            res.push_str(&formatdoc!(
-                r#"
-                @font-face {{
-                    font-family: '{}';
-                    src: local("{}");
-                    {}
-                }}
-            "#,
-                fallback.scoped_font_family.await?,
-                fallback.local_font_family.await?,
-                override_properties
-            ));
+            for weighted in &fallback.weighted_adjustments {
+                res.push_str(&formatdoc!(
+                    r#"
+                    @font-face {{
+                        font-family: '{}';
+                        src: local("{}");
+                        font-weight: {};
+                        {}{}
+                    }}
+                "#,
+                    scoped_font_family,
+                    local_font_family,
+                    weighted.weight,
+                    weighted
+                        .style
+                        .as_ref()
+                        .map(|s| format!("font-style: {};\n", s))
+                        .unwrap_or_default(),
+                    format_override_properties(&weighted.adjustment)
+                ));
+            }
         }
     }
 
@@ -93,6 +109,21 @@ pub(super) async fn build_font_class_rules(
     Ok(Vc::cell(rules))
 }
 
+fn format_override_properties(adjustment: &FontAdjustment) -> String {
+    formatdoc!(
+        r#"
+        ascent-override: {}%;
+        descent-override: {}%;
+        line-gap-override: {}%;
+        size-adjust: {}%;
+    "#,
+        format_fixed_percentage(adjustment.ascent),
+        format_fixed_percentage(adjustment.descent.abs()),
+        format_fixed_percentage(adjustment.line_gap),
+        format_fixed_percentage(adjustment.size_adjust)
+    )
+}
+
 fn format_fixed_percentage(value: f64) -> String {
     format!("{:.2}", value * 100.0)
 }
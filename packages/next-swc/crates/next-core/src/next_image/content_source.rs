@@ -1,33 +1,53 @@
 use anyhow::{bail, Result};
 use turbo_tasks::{Value, Vc};
-use turbo_tasks_fs::FileSystem;
+use turbo_tasks_fs::{FileContent, FileSystem};
 use turbopack_binding::turbopack::{
     core::{
         asset::AssetContent, ident::AssetIdent, introspect::Introspectable,
         server_fs::ServerFileSystem, version::VersionedContent,
     },
     dev_server::source::{
+        headers::HeaderValue,
         query::QueryValue,
         route_tree::{RouteTree, RouteType},
         wrapping_source::{ContentSourceProcessor, WrappedGetContentSourceContent},
         ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataFilter,
-        ContentSourceDataVary, GetContentSourceContent, ProxyResult, RewriteBuilder,
+        ContentSourceDataVary, GetContentSourceContent, HeaderList, ProxyResult, RewriteBuilder,
     },
     image::process::optimize,
 };
+use url::Url;
+
+use super::{
+    format::{negotiate_format, reencode},
+    remote::{fetch_remote_image, RemoteImageError},
+};
+use crate::next_config::{ImageConfig, ImageFormat};
+
+/// The width of blur placeholders requested by `next-image-loader`, which is
+/// allowed in addition to the configured sizes.
+const BLUR_IMG_SIZE: u32 = 8;
 
 /// Serves, resizes, optimizes, and re-encodes images to be used with
 /// next/image.
 #[turbo_tasks::value(shared)]
 pub struct NextImageContentSource {
     asset_source: Vc<Box<dyn ContentSource>>,
+    image_config: Vc<ImageConfig>,
 }
 
 #[turbo_tasks::value_impl]
 impl NextImageContentSource {
     #[turbo_tasks::function]
-    pub fn new(asset_source: Vc<Box<dyn ContentSource>>) -> Vc<NextImageContentSource> {
-        NextImageContentSource { asset_source }.cell()
+    pub fn new(
+        asset_source: Vc<Box<dyn ContentSource>>,
+        image_config: Vc<ImageConfig>,
+    ) -> Vc<NextImageContentSource> {
+        NextImageContentSource {
+            asset_source,
+            image_config,
+        }
+        .cell()
     }
 }
 
@@ -47,6 +67,9 @@ impl GetContentSourceContent for NextImageContentSource {
             query: Some(ContentSourceDataFilter::Subset(
                 ["url".to_string(), "w".to_string(), "q".to_string()].into(),
             )),
+            headers: Some(ContentSourceDataFilter::Subset(
+                ["accept".to_string()].into(),
+            )),
             ..Default::default()
         }
         .cell()
@@ -69,27 +92,71 @@ impl GetContentSourceContent for NextImageContentSource {
         };
 
         let q = match query.get("q") {
-            None => 75,
-            Some(QueryValue::String(s)) => {
-                let Ok(q) = s.parse::<u8>() else {
-                    bail!("invalid q query argument")
-                };
-                q
-            }
+            None => Some(75),
+            Some(QueryValue::String(s)) => parse_int(s),
             _ => bail!("missing q query argument"),
         };
 
         let w = match query.get("w") {
-            Some(QueryValue::String(s)) => {
-                let Ok(w) = s.parse::<u32>() else {
-                    bail!("invalid w query argument")
-                };
-                w
-            }
+            Some(QueryValue::String(s)) => parse_int(s),
             _ => bail!("missing w query argument"),
         };
 
-        // TODO: re-encode into next-gen formats.
+        let image_config = this.image_config.await?;
+
+        let Some(w) = w.filter(|&w| w > 0) else {
+            return Ok(bad_request(
+                "\"w\" parameter (width) must be a number greater than 0".to_string(),
+            ));
+        };
+        let Some(w) = u32::try_from(w)
+            .ok()
+            .filter(|&w| is_allowed_width(&image_config, w))
+        else {
+            return Ok(bad_request(format!(
+                "\"w\" parameter (width) of {} is not allowed",
+                w
+            )));
+        };
+
+        let Some(q) = q
+            .and_then(|q| u8::try_from(q).ok())
+            .filter(|q| (1..=100).contains(q))
+        else {
+            return Ok(bad_request(
+                "\"q\" parameter (quality) must be a number between 1 and 100".to_string(),
+            ));
+        };
+
+        let accept = match data
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get("accept"))
+        {
+            Some(HeaderValue::SingleString(accept)) => accept.as_str(),
+            Some(HeaderValue::MultiStrings(accepts)) => accepts.first().map_or("", |a| a.as_str()),
+            _ => "",
+        };
+        let format = negotiate_format(accept, &image_config.formats);
+
+        if url.starts_with("//") {
+            return Ok(bad_request(
+                "\"url\" parameter cannot be a protocol-relative URL (//)".to_string(),
+            ));
+        }
+
+        let url_path = match url.strip_prefix('/') {
+            Some(_) => url.split(['?', '#']).next().unwrap_or_default().to_string(),
+            None => Url::parse(url)
+                .map(|url| url.path().to_string())
+                .unwrap_or_default(),
+        };
+        let is_svg = mime_guess::from_path(&url_path).first_raw() == Some("image/svg+xml");
+        if is_svg && !image_config.dangerously_allow_svg {
+            return Ok(svg_not_allowed());
+        }
+
+        let headers = response_headers(&image_config);
 
         if let Some(path) = url.strip_prefix('/') {
             let sources = this.asset_source.get_routes().get(path.to_string()).await?;
@@ -98,7 +165,17 @@ impl GetContentSourceContent for NextImageContentSource {
                 .map(|s| {
                     Vc::upcast(WrappedGetContentSourceContent::new(
                         *s,
-                        Vc::upcast(NextImageContentSourceProcessor::new(path.to_string(), w, q)),
+                        Vc::upcast(
+                            NextImageContentSourceProcessor {
+                                path: path.to_string(),
+                                width: w,
+                                quality: q,
+                                format: format.clone(),
+                                headers: headers.clone(),
+                                dangerously_allow_svg: image_config.dangerously_allow_svg,
+                            }
+                            .cell(),
+                        ),
                     ))
                 })
                 .collect();
@@ -108,19 +185,76 @@ impl GetContentSourceContent for NextImageContentSource {
             );
         }
 
-        // TODO: This should be downloaded by the server, and resized, etc.
-        Ok(ContentSourceContent::HttpProxy(
-            ProxyResult {
-                status: 302,
-                headers: vec![("Location".to_string(), url.clone())],
-                body: "".into(),
+        match fetch_remote_image(url, &image_config).await? {
+            Ok((_, file_content))
+                if !image_config.dangerously_allow_svg && is_svg(file_content).await? =>
+            {
+                Ok(svg_not_allowed())
             }
-            .cell(),
-        )
-        .cell())
+            Ok((remote_url, file_content)) => Ok(optimized_image_content(
+                remote_url.path().trim_start_matches('/').to_string(),
+                file_content,
+                w,
+                q,
+                &format,
+                headers,
+            )),
+            Err(RemoteImageError { status, message }) => Ok(ContentSourceContent::HttpProxy(
+                ProxyResult {
+                    status,
+                    headers: vec![],
+                    body: message.into(),
+                }
+                .cell(),
+            )
+            .cell()),
+        }
     }
 }
 
+fn is_allowed_width(image_config: &ImageConfig, width: u32) -> bool {
+    width == BLUR_IMG_SIZE
+        || image_config
+            .device_sizes
+            .iter()
+            .chain(image_config.image_sizes.iter())
+            .any(|&size| size as u32 == width)
+}
+
+fn svg_not_allowed() -> Vc<ContentSourceContent> {
+    bad_request("\"url\" parameter is valid but image type is not allowed".to_string())
+}
+
+/// Parses the leading integer of `value` like `parseInt` does, which the image
+/// optimizer of `next start` uses for the `w` and `q` parameters.
+fn parse_int(value: &str) -> Option<i64> {
+    let value = value.trim_start();
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let digits = &digits[..digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len())];
+    if digits.is_empty() {
+        return None;
+    }
+    // Integers out of range are rejected like any other too large value.
+    Some(sign * digits.parse::<i64>().unwrap_or(i64::MAX))
+}
+
+fn bad_request(message: String) -> Vc<ContentSourceContent> {
+    ContentSourceContent::HttpProxy(
+        ProxyResult {
+            status: 400,
+            headers: vec![],
+            body: message.into(),
+        }
+        .cell(),
+    )
+    .cell()
+}
+
 #[turbo_tasks::value_impl]
 impl Introspectable for NextImageContentSource {
     #[turbo_tasks::function]
@@ -139,19 +273,10 @@ struct NextImageContentSourceProcessor {
     path: String,
     width: u32,
     quality: u8,
-}
-
-#[turbo_tasks::value_impl]
-impl NextImageContentSourceProcessor {
-    #[turbo_tasks::function]
-    pub fn new(path: String, width: u32, quality: u8) -> Vc<NextImageContentSourceProcessor> {
-        NextImageContentSourceProcessor {
-            path,
-            width,
-            quality,
-        }
-        .cell()
-    }
+    /// The negotiated format to re-encode the image into, if any.
+    format: Option<ImageFormat>,
+    headers: Vec<(String, String)>,
+    dangerously_allow_svg: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -166,15 +291,130 @@ impl ContentSourceProcessor for NextImageContentSourceProcessor {
         let AssetContent::File(file_content) = *asset_content else {
             return Ok(content);
         };
-        let optimized_file_content = optimize(
-            AssetIdent::from_path(ServerFileSystem::new().root().join(self.path.clone())),
+        if !self.dangerously_allow_svg && is_svg(file_content).await? {
+            return Ok(svg_not_allowed());
+        }
+        Ok(optimized_image_content(
+            self.path.clone(),
             file_content,
             self.width,
-            u32::MAX,
             self.quality,
-        );
-        Ok(ContentSourceContent::static_content(
-            AssetContent::File(optimized_file_content).into(),
+            &self.format,
+            self.headers.clone(),
         ))
     }
 }
+
+/// Resizes an image and re-encodes it into the negotiated format, if any.
+fn optimized_image_content(
+    path: String,
+    file_content: Vc<FileContent>,
+    width: u32,
+    quality: u8,
+    format: &Option<ImageFormat>,
+    headers: Vec<(String, String)>,
+) -> Vc<ContentSourceContent> {
+    let is_vector_or_animated = is_vector_or_animated(&path);
+    let optimized_file_content = optimize(
+        AssetIdent::from_path(ServerFileSystem::new().root().join(path)),
+        file_content,
+        width,
+        u32::MAX,
+        quality,
+    );
+    // Vector and animated images are served as-is, like in production.
+    let file_content = match format {
+        Some(format) if !is_vector_or_animated => {
+            reencode(optimized_file_content, Value::new(format.clone()), quality)
+        }
+        _ => optimized_file_content,
+    };
+    ContentSourceContent::static_with_headers(
+        AssetContent::File(file_content).into(),
+        200,
+        Vc::<HeaderList>::cell(headers),
+    )
+}
+
+/// The headers of optimized images, like `next start` sends them.
+fn response_headers(image_config: &ImageConfig) -> Vec<(String, String)> {
+    let mut headers = vec![
+        ("Vary".to_string(), "Accept".to_string()),
+        (
+            "Cache-Control".to_string(),
+            format!(
+                "public, max-age={}, must-revalidate",
+                image_config.minimum_cache_ttl
+            ),
+        ),
+    ];
+    if !image_config.content_security_policy.is_empty() {
+        headers.push((
+            "Content-Security-Policy".to_string(),
+            image_config.content_security_policy.clone(),
+        ));
+    }
+    headers
+}
+
+/// Whether the content of an image is an SVG, regardless of its url. Like
+/// `detectContentType` of `next/dist/server/image-optimizer`, this looks at the
+/// leading bytes.
+async fn is_svg(file_content: Vc<FileContent>) -> Result<bool> {
+    let FileContent::Content(file) = &*file_content.await? else {
+        return Ok(false);
+    };
+    Ok(is_svg_content(&file.content().to_bytes()?))
+}
+
+fn is_svg_content(bytes: &[u8]) -> bool {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let bytes = &bytes[start..];
+    bytes.starts_with(b"<?xml") || bytes.starts_with(b"<svg")
+}
+
+fn is_vector_or_animated(path: &str) -> bool {
+    matches!(
+        mime_guess::from_path(path).first_raw(),
+        Some("image/svg+xml" | "image/gif" | "image/apng")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_allowed_width, is_svg_content, parse_int, BLUR_IMG_SIZE};
+    use crate::next_config::ImageConfig;
+
+    #[test]
+    fn test_allowed_widths() {
+        let image_config = ImageConfig::default();
+        assert!(is_allowed_width(&image_config, 640));
+        assert!(is_allowed_width(&image_config, 16));
+        assert!(is_allowed_width(&image_config, BLUR_IMG_SIZE));
+        assert!(!is_allowed_width(&image_config, 641));
+    }
+
+    #[test]
+    fn test_svg_content() {
+        assert!(is_svg_content(b"<?xml version=\"1.0\"?><svg></svg>"));
+        assert!(is_svg_content(
+            b"\n  <svg xmlns=\"http://www.w3.org/2000/svg\"></svg>"
+        ));
+        assert!(!is_svg_content(b"\x89PNG\r\n\x1a\n"));
+        assert!(!is_svg_content(b""));
+    }
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int("75"), Some(75));
+        assert_eq!(parse_int("300"), Some(300));
+        assert_eq!(parse_int(" 640px"), Some(640));
+        assert_eq!(parse_int("-5"), Some(-5));
+        assert_eq!(parse_int("abc"), None);
+        assert_eq!(parse_int(""), None);
+        assert_eq!(parse_int("99999999999999999999"), Some(i64::MAX));
+    }
+}
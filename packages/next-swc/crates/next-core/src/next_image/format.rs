@@ -0,0 +1,140 @@
+use anyhow::{bail, Result};
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{File, FileContent};
+
+use crate::next_config::ImageFormat;
+
+impl ImageFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Avif => "image/avif",
+        }
+    }
+
+    /// Whether this build of Next.js can encode images into the format.
+    fn is_supported(&self) -> bool {
+        match self {
+            ImageFormat::Webp => cfg!(feature = "image-webp"),
+            ImageFormat::Avif => cfg!(feature = "image-avif"),
+        }
+    }
+}
+
+/// Picks the format to re-encode an image into, based on the request's
+/// `Accept` header and the configured `images.formats`.
+///
+/// Like the production image optimizer, the configured order decides between
+/// formats the client accepts, and only formats explicitly listed in the
+/// header are used, as browsers send `*/*` for images anyway.
+pub(super) fn negotiate_format(accept: &str, formats: &[ImageFormat]) -> Option<ImageFormat> {
+    let accepted: Vec<&str> = accept
+        .split(',')
+        .filter_map(|media_range| {
+            let mut params = media_range.split(';').map(str::trim);
+            let mime_type = params.next()?;
+            let refused = params.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .map_or(false, |q| q <= 0.0)
+            });
+            (!refused).then_some(mime_type)
+        })
+        .collect();
+
+    formats
+        .iter()
+        .find(|format| format.is_supported() && accepted.contains(&format.mime_type()))
+        .cloned()
+}
+
+/// Re-encodes an (already optimized) image into the given format.
+#[turbo_tasks::function]
+pub(super) async fn reencode(
+    content: Vc<FileContent>,
+    format: Value<ImageFormat>,
+    quality: u8,
+) -> Result<Vc<FileContent>> {
+    let FileContent::Content(file) = &*content.await? else {
+        return Ok(FileContent::NotFound.cell());
+    };
+    let image = image::load_from_memory(&file.content().to_bytes()?)?;
+    let format = format.into_value();
+    let encoded = encode(image, &format, quality)?;
+
+    let file = File::from(encoded).with_content_type(format.mime_type().parse()?);
+    Ok(FileContent::Content(file).cell())
+}
+
+fn encode(image: image::DynamicImage, format: &ImageFormat, quality: u8) -> Result<Vec<u8>> {
+    match format {
+        #[cfg(feature = "image-webp")]
+        ImageFormat::Webp => {
+            let encoder = webp::Encoder::from_image(&image)
+                .map_err(|err| anyhow::anyhow!("Unable to encode image as WebP: {}", err))?;
+            Ok(encoder.encode(quality as f32).to_vec())
+        }
+        #[cfg(feature = "image-avif")]
+        ImageFormat::Avif => {
+            use image::{codecs::avif::AvifEncoder, ColorType, ImageEncoder};
+
+            let rgba = image.to_rgba8();
+            let mut encoded = Vec::new();
+            AvifEncoder::new_with_speed_quality(&mut encoded, 6, quality).write_image(
+                rgba.as_raw(),
+                rgba.width(),
+                rgba.height(),
+                ColorType::Rgba8,
+            )?;
+            Ok(encoded)
+        }
+        #[allow(unreachable_patterns)]
+        _ => {
+            let _ = (image, quality);
+            bail!("Encoding images as {} is not supported", format.mime_type())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::negotiate_format;
+    use crate::next_config::ImageFormat;
+
+    #[test]
+    fn test_no_format_without_accept() {
+        assert_eq!(negotiate_format("", &[ImageFormat::Webp]), None);
+        assert_eq!(negotiate_format("*/*", &[ImageFormat::Webp]), None);
+    }
+
+    #[cfg(all(feature = "image-webp", feature = "image-avif"))]
+    #[test]
+    fn test_configured_order_wins() {
+        let accept = "image/webp,image/avif,image/*,*/*;q=0.8";
+        assert_eq!(
+            negotiate_format(accept, &[ImageFormat::Avif, ImageFormat::Webp]),
+            Some(ImageFormat::Avif)
+        );
+        assert_eq!(
+            negotiate_format(accept, &[ImageFormat::Webp, ImageFormat::Avif]),
+            Some(ImageFormat::Webp)
+        );
+    }
+
+    #[cfg(feature = "image-webp")]
+    #[test]
+    fn test_skips_refused_formats() {
+        assert_eq!(
+            negotiate_format(
+                "image/avif;q=0, image/webp",
+                &[ImageFormat::Avif, ImageFormat::Webp]
+            ),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(
+            negotiate_format("image/webp;q=0", &[ImageFormat::Webp]),
+            None
+        );
+    }
+}
@@ -1,6 +1,10 @@
 pub(crate) mod content_source;
+pub(crate) mod format;
 pub(crate) mod module;
+pub(crate) mod placeholder;
+pub(crate) mod remote;
 pub(crate) mod source_asset;
+pub(crate) mod srcset;
 
 pub use content_source::NextImageContentSource;
 pub use module::StructuredImageModuleType;
@@ -12,7 +12,7 @@ use turbopack_binding::{
     },
 };
 
-use super::source_asset::StructuredImageFileSource;
+use super::{source_asset::StructuredImageFileSource, srcset::ResizedImageSource};
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, Hash)]
@@ -28,6 +28,33 @@ pub enum BlurPlaceholderMode {
     /// placeholder is shown later than it should be) and should
     /// only be used for development.
     NextImageUrl,
+    /// Export the dominant color of the image as a css color value
+    /// (`placeholderColor`) instead of a blur placeholder.
+    DominantColor,
+    /// Export a base64 encoded ThumbHash of the image (`thumbHash`), which is
+    /// a lot smaller than a data url and decoded on the client.
+    ThumbHash,
+    /// Export a BlurHash of the image (`blurHash`), which is a lot smaller than
+    /// a data url and decoded on the client.
+    BlurHash,
+}
+
+impl BlurPlaceholderMode {
+    /// Reads the placeholder mode requested by an import like
+    /// `./image.png?placeholder=color`.
+    pub(crate) fn from_query(query: &str) -> Option<Self> {
+        let placeholder = qstring::QString::from(query)
+            .get("placeholder")?
+            .to_string();
+        Some(match placeholder.as_str() {
+            "none" | "empty" => BlurPlaceholderMode::None,
+            "blur" => BlurPlaceholderMode::DataUrl,
+            "color" => BlurPlaceholderMode::DominantColor,
+            "thumbhash" => BlurPlaceholderMode::ThumbHash,
+            "blurhash" => BlurPlaceholderMode::BlurHash,
+            _ => return None,
+        })
+    }
 }
 
 /// Module type that analyzes images and offers some meta information like
@@ -35,26 +62,43 @@ pub enum BlurPlaceholderMode {
 #[turbo_tasks::value]
 pub struct StructuredImageModuleType {
     pub blur_placeholder_mode: BlurPlaceholderMode,
+    /// When set, the image is pre-resized to these widths and the module
+    /// exports a `srcSet` for them.
+    pub srcset_widths: Option<Vec<u32>>,
 }
 
 impl StructuredImageModuleType {
     pub(crate) fn create_module(
         source: Vc<Box<dyn Source>>,
         blur_placeholder_mode: BlurPlaceholderMode,
+        srcset_widths: Option<Vec<u32>>,
         context: Vc<ModuleAssetContext>,
     ) -> Vc<Box<dyn Module>> {
         let static_asset = StaticModuleAsset::new(source, Vc::upcast(context));
+        let mut inner_assets = indexmap!(
+            "IMAGE".to_string() => Vc::upcast(static_asset)
+        );
+        // Only the variants smaller than the image are imported, the others are
+        // never emitted.
+        for &width in srcset_widths.iter().flatten() {
+            inner_assets.insert(
+                format!("IMAGE_{width}"),
+                Vc::upcast(StaticModuleAsset::new(
+                    Vc::upcast(ResizedImageSource::new(source, width)),
+                    Vc::upcast(context),
+                )),
+            );
+        }
         context.process(
             Vc::upcast(
                 StructuredImageFileSource {
                     image: source,
                     blur_placeholder_mode,
+                    srcset_widths,
                 }
                 .cell(),
             ),
-            Value::new(ReferenceType::Internal(Vc::cell(indexmap!(
-                "IMAGE".to_string() => Vc::upcast(static_asset)
-            )))),
+            Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
         )
     }
 }
@@ -62,9 +106,13 @@ impl StructuredImageModuleType {
 #[turbo_tasks::value_impl]
 impl StructuredImageModuleType {
     #[turbo_tasks::function]
-    pub fn new(blur_placeholder_mode: Value<BlurPlaceholderMode>) -> Vc<Self> {
+    pub fn new(
+        blur_placeholder_mode: Value<BlurPlaceholderMode>,
+        srcset_widths: Option<Vec<u32>>,
+    ) -> Vc<Self> {
         StructuredImageModuleType::cell(StructuredImageModuleType {
             blur_placeholder_mode: blur_placeholder_mode.into_value(),
+            srcset_widths,
         })
     }
 }
@@ -78,6 +126,11 @@ impl CustomModuleType for StructuredImageModuleType {
         context: Vc<ModuleAssetContext>,
         _part: Option<Vc<ModulePart>>,
     ) -> Vc<Box<dyn Module>> {
-        StructuredImageModuleType::create_module(source, self.blur_placeholder_mode, context)
+        StructuredImageModuleType::create_module(
+            source,
+            self.blur_placeholder_mode,
+            self.srcset_widths.clone(),
+            context,
+        )
     }
 }
@@ -0,0 +1,278 @@
+use std::f32::consts::PI;
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+
+/// ThumbHash only encodes images up to 100x100 pixels.
+const THUMB_HASH_MAX_SIZE: u32 = 100;
+/// The number of BlurHash components along the longer side of the image.
+const BLUR_HASH_COMPONENTS: u32 = 4;
+/// Images are scaled down to this size to find their dominant color.
+const DOMINANT_COLOR_SAMPLE_SIZE: u32 = 64;
+
+/// Returns the most common color of the image as a css `rgb()` value.
+///
+/// Colors are bucketed by their 4 most significant bits per channel, and the
+/// pixels of the most common bucket are averaged, which keeps the color
+/// faithful to the image while ignoring small gradients and noise. Fully
+/// transparent pixels are ignored.
+pub(super) fn dominant_color(image: &DynamicImage) -> String {
+    let (width, height) = image.dimensions();
+    let sample = if width > DOMINANT_COLOR_SAMPLE_SIZE || height > DOMINANT_COLOR_SAMPLE_SIZE {
+        image
+            .resize(
+                DOMINANT_COLOR_SAMPLE_SIZE,
+                DOMINANT_COLOR_SAMPLE_SIZE,
+                FilterType::Triangle,
+            )
+            .to_rgba8()
+    } else {
+        image.to_rgba8()
+    };
+
+    // Sum of red, green, blue and number of pixels per bucket.
+    let mut buckets = vec![[0u32; 4]; 1 << 12];
+    for pixel in sample.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a == 0 {
+            continue;
+        }
+        let bucket =
+            &mut buckets[((r as usize >> 4) << 8) | ((g as usize >> 4) << 4) | (b as usize >> 4)];
+        bucket[0] += r as u32;
+        bucket[1] += g as u32;
+        bucket[2] += b as u32;
+        bucket[3] += 1;
+    }
+
+    match buckets.iter().max_by_key(|bucket| bucket[3]) {
+        Some([r, g, b, count]) if *count > 0 => {
+            format!("rgb({},{},{})", r / count, g / count, b / count)
+        }
+        _ => "transparent".to_string(),
+    }
+}
+
+/// Encodes a ThumbHash of the image, as base64.
+pub(super) fn thumb_hash(image: &DynamicImage) -> String {
+    let thumbnail = image
+        .thumbnail(THUMB_HASH_MAX_SIZE, THUMB_HASH_MAX_SIZE)
+        .to_rgba8();
+    let hash = rgba_to_thumb_hash(
+        thumbnail.width() as usize,
+        thumbnail.height() as usize,
+        thumbnail.as_raw(),
+    );
+    STANDARD.encode(hash)
+}
+
+/// Port of `rgbaToThumbHash` of the reference implementation at
+/// https://github.com/evanw/thumbhash, for images of up to 100x100 pixels.
+fn rgba_to_thumb_hash(w: usize, h: usize, rgba: &[u8]) -> Vec<u8> {
+    debug_assert!(w <= THUMB_HASH_MAX_SIZE as usize && h <= THUMB_HASH_MAX_SIZE as usize);
+    debug_assert_eq!(rgba.len(), w * h * 4);
+
+    // Determine the average color.
+    let (mut avg_r, mut avg_g, mut avg_b, mut avg_a) = (0.0, 0.0, 0.0, 0.0);
+    for pixel in rgba.chunks_exact(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        avg_r += alpha / 255.0 * pixel[0] as f32;
+        avg_g += alpha / 255.0 * pixel[1] as f32;
+        avg_b += alpha / 255.0 * pixel[2] as f32;
+        avg_a += alpha;
+    }
+    if avg_a > 0.0 {
+        avg_r /= avg_a;
+        avg_g /= avg_a;
+        avg_b /= avg_a;
+    }
+
+    let has_alpha = avg_a < (w * h) as f32;
+    // Use fewer luminance bits if there's alpha.
+    let l_limit = if has_alpha { 5 } else { 7 };
+    let lx = 1.max(((l_limit * w) as f32 / w.max(h) as f32).round() as usize);
+    let ly = 1.max(((l_limit * h) as f32 / w.max(h) as f32).round() as usize);
+
+    // Convert the image from RGBA to LPQA, composited atop the average color.
+    let mut l = Vec::with_capacity(w * h);
+    let mut p = Vec::with_capacity(w * h);
+    let mut q = Vec::with_capacity(w * h);
+    let mut a = Vec::with_capacity(w * h);
+    for pixel in rgba.chunks_exact(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        let r = avg_r * (1.0 - alpha) + alpha / 255.0 * pixel[0] as f32;
+        let g = avg_g * (1.0 - alpha) + alpha / 255.0 * pixel[1] as f32;
+        let b = avg_b * (1.0 - alpha) + alpha / 255.0 * pixel[2] as f32;
+        l.push((r + g + b) / 3.0);
+        p.push((r + g) / 2.0 - b);
+        q.push(r - g);
+        a.push(alpha);
+    }
+
+    // Encode using the DCT into DC (constant) and normalized AC (varying) terms.
+    let encode_channel = |channel: &[f32], nx: usize, ny: usize| -> (f32, Vec<f32>, f32) {
+        let mut dc = 0.0;
+        let mut ac = Vec::with_capacity(nx * ny / 2);
+        let mut scale = 0.0f32;
+        let mut fx = vec![0.0f32; w];
+        for cy in 0..ny {
+            let mut cx = 0;
+            while cx * ny < nx * (ny - cy) {
+                for (x, fx) in fx.iter_mut().enumerate() {
+                    *fx = (PI / w as f32 * cx as f32 * (x as f32 + 0.5)).cos();
+                }
+                let mut f = 0.0;
+                for y in 0..h {
+                    let fy = (PI / h as f32 * cy as f32 * (y as f32 + 0.5)).cos();
+                    for (x, fx) in fx.iter().enumerate() {
+                        f += channel[x + y * w] * fx * fy;
+                    }
+                }
+                f /= (w * h) as f32;
+                if cx > 0 || cy > 0 {
+                    ac.push(f);
+                    scale = scale.max(f.abs());
+                } else {
+                    dc = f;
+                }
+                cx += 1;
+            }
+        }
+        if scale > 0.0 {
+            for ac in &mut ac {
+                *ac = 0.5 + 0.5 / scale * *ac;
+            }
+        }
+        (dc, ac, scale)
+    };
+    let (l_dc, l_ac, l_scale) = encode_channel(&l, lx.max(3), ly.max(3));
+    let (p_dc, p_ac, p_scale) = encode_channel(&p, 3, 3);
+    let (q_dc, q_ac, q_scale) = encode_channel(&q, 3, 3);
+    let (a_dc, a_ac, a_scale) = if has_alpha {
+        encode_channel(&a, 5, 5)
+    } else {
+        (1.0, Vec::new(), 1.0)
+    };
+
+    // Write the constants.
+    let is_landscape = w > h;
+    let header24 = (63.0 * l_dc).round() as u32
+        | (((31.5 + 31.5 * p_dc).round() as u32) << 6)
+        | (((31.5 + 31.5 * q_dc).round() as u32) << 12)
+        | (((31.0 * l_scale).round() as u32) << 18)
+        | if has_alpha { 1 << 23 } else { 0 };
+    let header16 = (if is_landscape { ly } else { lx }) as u16
+        | (((63.0 * p_scale).round() as u16) << 3)
+        | (((63.0 * q_scale).round() as u16) << 9)
+        | if is_landscape { 1 << 15 } else { 0 };
+    let mut hash = vec![
+        (header24 & 255) as u8,
+        ((header24 >> 8) & 255) as u8,
+        (header24 >> 16) as u8,
+        (header16 & 255) as u8,
+        (header16 >> 8) as u8,
+    ];
+    if has_alpha {
+        hash.push((15.0 * a_dc).round() as u8 | (((15.0 * a_scale).round() as u8) << 4));
+    }
+
+    // Write the varying factors, two per byte.
+    let mut is_odd = false;
+    for f in [l_ac, p_ac, q_ac, a_ac].into_iter().flatten() {
+        let u = (15.0 * f).round() as u8;
+        if is_odd {
+            *hash.last_mut().unwrap() |= u << 4;
+        } else {
+            hash.push(u);
+        }
+        is_odd = !is_odd;
+    }
+    hash
+}
+
+/// Encodes a BlurHash of the image, with up to 4 components along the longer
+/// side.
+pub(super) fn blur_hash(image: &DynamicImage) -> Result<String> {
+    let (width, height) = image.dimensions();
+    let (components_x, components_y) = if width >= height {
+        (
+            BLUR_HASH_COMPONENTS,
+            (BLUR_HASH_COMPONENTS * height / width.max(1)).clamp(1, BLUR_HASH_COMPONENTS),
+        )
+    } else {
+        (
+            (BLUR_HASH_COMPONENTS * width / height.max(1)).clamp(1, BLUR_HASH_COMPONENTS),
+            BLUR_HASH_COMPONENTS,
+        )
+    };
+
+    // The hash only keeps the low frequencies, so a small version of the image is
+    // enough and much faster to encode.
+    let thumbnail = image.thumbnail(32, 32).to_rgba8();
+    blurhash::encode(
+        components_x,
+        components_y,
+        thumbnail.width(),
+        thumbnail.height(),
+        thumbnail.as_raw(),
+    )
+    .map_err(|err| anyhow!("Unable to compute BlurHash: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    use super::{blur_hash, dominant_color, rgba_to_thumb_hash, thumb_hash};
+
+    fn two_colored_image() -> DynamicImage {
+        // Three quarters red, one quarter blue.
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(40, 40, |x, _| {
+            if x < 30 {
+                Rgba([200, 10, 10, 255])
+            } else {
+                Rgba([10, 10, 200, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_dominant_color() {
+        assert_eq!(dominant_color(&two_colored_image()), "rgb(200,10,10)");
+    }
+
+    #[test]
+    fn test_dominant_color_of_transparent_image() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::new(10, 10));
+        assert_eq!(dominant_color(&image), "transparent");
+    }
+
+    #[test]
+    fn test_thumb_hash_header() {
+        // An opaque, uniformly gray square has no varying factors to speak of, so
+        // only the header depends on the image.
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 10, Rgba([128, 128, 128, 255])));
+        let hash = rgba_to_thumb_hash(10, 10, image.to_rgba8().as_raw());
+        let header24 = hash[0] as u32 | (hash[1] as u32) << 8 | (hash[2] as u32) << 16;
+        let header16 = hash[3] as u16 | (hash[4] as u16) << 8;
+
+        // Luminance of 128/255, neutral chroma and no alpha.
+        assert_eq!(header24 & 63, 32);
+        assert_eq!((header24 >> 6) & 63, 32);
+        assert_eq!((header24 >> 12) & 63, 32);
+        assert_eq!(header24 >> 23, 0);
+        // Square images use 7 luminance components and aren't landscape.
+        assert_eq!(header16 & 7, 7);
+        assert_eq!(header16 >> 15, 0);
+    }
+
+    #[test]
+    fn test_hashes_are_compact() {
+        let image = two_colored_image();
+        assert!(thumb_hash(&image).len() < 40);
+        // 4x4 components encode into 36 characters.
+        assert_eq!(blur_hash(&image).unwrap().len(), 36);
+    }
+}
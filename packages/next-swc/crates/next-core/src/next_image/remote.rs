@@ -0,0 +1,619 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{rope::Rope, File, FileContent};
+use turbopack_binding::turbo::tasks_fetch::fetch;
+use url::Url;
+
+use crate::next_config::{ImageConfig, RemotePattern, RemotePatternProtocal};
+
+/// Why a remote image can't be served, along with the status code to respond
+/// with.
+pub(super) struct RemoteImageError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl RemoteImageError {
+    fn bad_request(message: String) -> Self {
+        RemoteImageError {
+            status: 400,
+            message,
+        }
+    }
+}
+
+/// Validates an absolute image url against the configured `images.domains`
+/// and `images.remotePatterns`, and downloads it when allowed.
+pub(super) async fn fetch_remote_image(
+    url: &str,
+    image_config: &ImageConfig,
+) -> Result<std::result::Result<(Url, Vc<FileContent>), RemoteImageError>> {
+    let Ok(parsed_url) = Url::parse(url) else {
+        return Ok(Err(RemoteImageError::bad_request(
+            "\"url\" parameter is invalid".to_string(),
+        )));
+    };
+
+    if !matches!(parsed_url.scheme(), "http" | "https") {
+        return Ok(Err(RemoteImageError::bad_request(
+            "\"url\" parameter is invalid".to_string(),
+        )));
+    }
+
+    if !is_remote_image_allowed(
+        &parsed_url,
+        &image_config.domains,
+        &image_config.remote_patterns,
+    ) {
+        return Ok(Err(RemoteImageError::bad_request(format!(
+            "\"url\" parameter is not allowed: hostname \"{}\" is not configured under images \
+             in your `next.config.js`. See more info: \
+             https://nextjs.org/docs/messages/next-image-unconfigured-host",
+            parsed_url.host_str().unwrap_or_default()
+        ))));
+    }
+
+    let response = fetch(Vc::cell(url.to_string()), Vc::cell(None)).await?;
+    let response = match &*response {
+        Ok(response) => response.await?,
+        Err(_) => {
+            return Ok(Err(RemoteImageError {
+                status: 500,
+                message: "\"url\" parameter is valid but upstream response is invalid".to_string(),
+            }))
+        }
+    };
+    if response.status != 200 {
+        return Ok(Err(RemoteImageError {
+            status: response.status,
+            message: format!(
+                "\"url\" parameter is valid but upstream response is invalid: {}",
+                response.status
+            ),
+        }));
+    }
+
+    let body = response.body.await?;
+    let content = FileContent::Content(File::from(Rope::from(body.0.clone()))).cell();
+
+    Ok(Ok((parsed_url, content)))
+}
+
+/// Matches like `hasMatch` in `next/dist/shared/lib/match-remote-pattern`.
+fn is_remote_image_allowed(
+    url: &Url,
+    domains: &[String],
+    remote_patterns: &[RemotePattern],
+) -> bool {
+    let hostname = url.host_str().unwrap_or_default();
+    domains.iter().any(|domain| domain == hostname)
+        || remote_patterns
+            .iter()
+            .any(|pattern| matches_remote_pattern(pattern, url))
+}
+
+fn matches_remote_pattern(pattern: &RemotePattern, url: &Url) -> bool {
+    if let Some(protocol) = &pattern.protocol {
+        let expected = match protocol {
+            RemotePatternProtocal::Http => "http",
+            RemotePatternProtocal::Https => "https",
+        };
+        if url.scheme() != expected {
+            return false;
+        }
+    }
+
+    if let Some(port) = &pattern.port {
+        // Like `URL.port`, the default port of the scheme is the empty string.
+        let url_port = url.port().map(|port| port.to_string()).unwrap_or_default();
+        if port != &url_port {
+            return false;
+        }
+    }
+
+    glob_match(&pattern.hostname, url.host_str().unwrap_or_default())
+        && glob_match(pattern.pathname.as_deref().unwrap_or("**"), url.path())
+}
+
+/// Matches `input` like `makeRe(pattern).test(input)` of micromatch does, which
+/// `matchRemotePattern` uses for hostnames and pathnames alike: `**` matches
+/// any number of `/`-separated segments when it is a segment of its own and is
+/// a `*` otherwise, `*` matches within a segment, and `?` matches a single
+/// character of it. Wildcards don't match segments starting with a dot.
+fn glob_match(pattern: &str, input: &str) -> bool {
+    let pattern: Vec<_> = pattern.split('/').collect();
+    let input: Vec<_> = input.split('/').collect();
+    match_segments(&pattern, &input)
+}
+
+fn match_segments(pattern: &[&str], input: &[&str]) -> bool {
+    match (pattern.split_first(), input.split_first()) {
+        (None, _) => input.is_empty(),
+        (Some((&"**", pattern_rest)), _) => (0..=input.len()).any(|matched| {
+            input[..matched]
+                .iter()
+                .all(|segment| !segment.starts_with('.'))
+                && match_segments(pattern_rest, &input[matched..])
+        }),
+        (Some((pattern_segment, pattern_rest)), Some((input_segment, input_rest))) => {
+            match_segment(pattern_segment, input_segment)
+                && match_segments(pattern_rest, input_rest)
+        }
+        (Some(_), None) => false,
+    }
+}
+
+fn match_segment(pattern: &str, input: &str) -> bool {
+    if pattern.starts_with(['*', '?']) && input.starts_with('.') {
+        return false;
+    }
+    // A segment of only wildcards can't be empty.
+    if input.is_empty() && !pattern.is_empty() && pattern.chars().all(|c| c == '*') {
+        return false;
+    }
+    match_wildcards(pattern, input)
+}
+
+fn match_wildcards(pattern: &str, input: &str) -> bool {
+    if let Some(rest) = pattern.strip_prefix('*') {
+        let rest = rest.trim_start_matches('*');
+        return input
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([input.len()])
+            .any(|i| match_wildcards(rest, &input[i..]));
+    }
+
+    let mut pattern_chars = pattern.chars();
+    let mut input_chars = input.chars();
+    match (pattern_chars.next(), input_chars.next()) {
+        (None, None) => true,
+        (Some(p), Some(c)) if p == '?' || p == c => {
+            match_wildcards(pattern_chars.as_str(), input_chars.as_str())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::{SocketAddr, TcpListener},
+        thread,
+    };
+
+    use turbo_tasks::{run_once, TurboTasks};
+    use turbo_tasks_fs::FileContent;
+    use turbopack_binding::turbo::tasks_memory::MemoryBackend;
+    use url::Url;
+
+    use super::{fetch_remote_image, is_remote_image_allowed, matches_remote_pattern};
+    use crate::next_config::{ImageConfig, RemotePattern, RemotePatternProtocal};
+
+    fn allowed(url: &str, remote_patterns: &[RemotePattern]) -> bool {
+        is_remote_image_allowed(&Url::parse(url).unwrap(), &[], remote_patterns)
+    }
+
+    #[test]
+    fn test_domains() {
+        let url = Url::parse("https://example.com/image.png").unwrap();
+        assert!(is_remote_image_allowed(
+            &url,
+            &["example.com".to_string()],
+            &[]
+        ));
+        assert!(!is_remote_image_allowed(
+            &url,
+            &["other.com".to_string()],
+            &[]
+        ));
+        assert!(!is_remote_image_allowed(&url, &[], &[]));
+    }
+
+    #[test]
+    fn test_hostname_globs() {
+        let single = [RemotePattern {
+            hostname: "*.example.com".to_string(),
+            ..Default::default()
+        }];
+        assert!(allowed("https://img.example.com/a.png", &single));
+        // Like micromatch, `*` only stops at slashes.
+        assert!(allowed("https://a.img.example.com/a.png", &single));
+        assert!(!allowed("https://example.com/a.png", &single));
+
+        let any = [RemotePattern {
+            hostname: "**.example.com".to_string(),
+            ..Default::default()
+        }];
+        assert!(allowed("https://a.img.example.com/a.png", &any));
+        assert!(!allowed("https://example.com/a.png", &any));
+        assert!(!allowed("https://example.org/a.png", &any));
+    }
+
+    #[test]
+    fn test_protocol_and_port() {
+        let patterns = [RemotePattern {
+            hostname: "localhost".to_string(),
+            protocol: Some(RemotePatternProtocal::Http),
+            port: Some("3001".to_string()),
+            pathname: None,
+        }];
+        assert!(allowed("http://localhost:3001/a.png", &patterns));
+        assert!(!allowed("https://localhost:3001/a.png", &patterns));
+        assert!(!allowed("http://localhost:3002/a.png", &patterns));
+        assert!(!allowed("http://localhost/a.png", &patterns));
+
+        let default_port = [RemotePattern {
+            hostname: "example.com".to_string(),
+            port: Some("".to_string()),
+            ..Default::default()
+        }];
+        assert!(allowed("https://example.com:443/a.png", &default_port));
+        assert!(!allowed("https://example.com:8443/a.png", &default_port));
+    }
+
+    #[test]
+    fn test_pathname_globs() {
+        let patterns = [RemotePattern {
+            hostname: "example.com".to_string(),
+            pathname: Some("/account123/*".to_string()),
+            ..Default::default()
+        }];
+        assert!(allowed("https://example.com/account123/a.png", &patterns));
+        assert!(!allowed(
+            "https://example.com/account123/nested/a.png",
+            &patterns
+        ));
+        assert!(!allowed("https://example.com/other/a.png", &patterns));
+        assert!(!allowed("https://example.com/account123/", &patterns));
+
+        let nested = [RemotePattern {
+            hostname: "example.com".to_string(),
+            pathname: Some("/account123/**".to_string()),
+            ..Default::default()
+        }];
+        assert!(allowed(
+            "https://example.com/account123/nested/a.png",
+            &nested
+        ));
+        assert!(allowed("https://example.com/account123", &nested));
+        assert!(!allowed("https://example.com/account1234", &nested));
+        assert!(!allowed(
+            "https://example.com/account123/.hidden/a.png",
+            &nested
+        ));
+    }
+
+    /// Asserts the results of `matchRemotePattern` in
+    /// test/unit/image-optimizer/match-remote-pattern.test.ts.
+    fn assert_matches(pattern: RemotePattern, cases: &[(&str, bool)]) {
+        for &(url, expected) in cases {
+            assert_eq!(
+                matches_remote_pattern(&pattern, &Url::parse(url).unwrap()),
+                expected,
+                "{pattern:?} matching {url}"
+            );
+        }
+    }
+
+    fn hostname(hostname: &str) -> RemotePattern {
+        RemotePattern {
+            hostname: hostname.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn pathname(pathname: &str) -> RemotePattern {
+        RemotePattern {
+            hostname: "example.com".to_string(),
+            pathname: Some(pathname.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_match_literal_hostname() {
+        assert_matches(
+            hostname("example.com"),
+            &[
+                ("https://example.com", true),
+                ("https://example.com.uk", false),
+                ("https://example.net", false),
+                ("https://sub.example.com", false),
+                ("https://com", false),
+                ("https://example.com/path", true),
+                ("https://example.com/path/to", true),
+                ("https://example.com/path/to/file", true),
+                ("https://example.com:81/path/to/file", true),
+                ("https://example.com:81/path/to/file?q=1", true),
+                ("http://example.com:81/path/to/file", true),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_match_literal_protocol_hostname_port_and_pathname() {
+        assert_matches(
+            RemotePattern {
+                protocol: Some(RemotePatternProtocal::Https),
+                port: Some("42".to_string()),
+                ..pathname("/path/to/file")
+            },
+            &[
+                ("https://example.com:42", false),
+                ("https://example.com.uk:42", false),
+                ("https://sub.example.com:42", false),
+                ("https://example.com:42/path", false),
+                ("https://example.com:42/path/to", false),
+                ("https://example.com:42/file", false),
+                ("https://example.com:42/path/to/file", true),
+                ("https://example.com:42/path/to/file?q=1", true),
+                ("http://example.com:42/path/to/file", false),
+                ("ftp://example.com:42/path/to/file", false),
+                ("https://example.com", false),
+                ("https://example.com/path/to/file", false),
+                ("https://example.com/path/to/file?q=1", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_match_hostname_with_single_asterisk() {
+        assert_matches(
+            hostname("avatars.*.example.com"),
+            &[
+                ("https://com", false),
+                ("https://example.com", false),
+                ("https://sub.example.com", false),
+                ("https://example.com.uk", false),
+                ("https://sub.example.com.uk", false),
+                ("https://avatars.example.com", false),
+                ("https://avatars.sfo1.example.com", true),
+                ("https://avatars.iad1.example.com", true),
+                ("https://more.avatars.iad1.example.com", false),
+            ],
+        );
+        assert_matches(
+            hostname("avatars.*1.example.com"),
+            &[
+                ("https://avatars.example.com", false),
+                ("https://avatars.sfo1.example.com", true),
+                ("https://avatars.iad1.example.com", true),
+                ("https://more.avatars.iad1.example.com", false),
+                ("https://avatars.sfo2.example.com", false),
+                ("https://avatars.iad2.example.com", false),
+                ("https://avatars.1.example.com", true),
+            ],
+        );
+        assert_matches(
+            hostname("avatars.*a*.example.com"),
+            &[
+                ("https://avatars.example.com", false),
+                ("https://avatars.sfo1.example.com", false),
+                ("https://avatars.iad1.example.com", true),
+                ("https://more.avatars.iad1.example.com", false),
+                ("https://avatars.sfo2.example.com", false),
+                ("https://avatars.iad2.example.com", true),
+                ("https://avatars.a.example.com", true),
+            ],
+        );
+        assert_matches(
+            hostname("avatars.ia*.example.com"),
+            &[
+                ("https://avatars.example.com", false),
+                ("https://avatars.sfo1.example.com", false),
+                ("https://avatars.iad1.example.com", true),
+                ("https://more.avatars.iad1.example.com", false),
+                ("https://avatars.sfo2.example.com", false),
+                ("https://avatars.iad2.example.com", true),
+                ("https://avatars.ia.example.com", true),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_match_hostname_with_double_asterisk() {
+        assert_matches(
+            hostname("**.example.com"),
+            &[
+                ("https://com", false),
+                ("https://example.com", false),
+                ("https://sub.example.com", true),
+                ("https://deep.sub.example.com", true),
+                ("https://example.com.uk", false),
+                ("https://sub.example.com.uk", false),
+                ("https://avatars.example.com", true),
+                ("https://avatars.sfo1.example.com", true),
+                ("https://avatars.iad1.example.com", true),
+                ("https://more.avatars.iad1.example.com", true),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_match_pathname_with_single_asterisk() {
+        assert_matches(
+            pathname("/act123/*/pic.jpg"),
+            &[
+                ("https://com", false),
+                ("https://example.com", false),
+                ("https://sub.example.com", false),
+                ("https://example.com.uk", false),
+                ("https://example.com/act123", false),
+                ("https://example.com/act123/usr4", false),
+                ("https://example.com/act123/usr4/pic", false),
+                ("https://example.com/act123/usr4/picsjpg", false),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/usr5/pic.jpg", true),
+                ("https://example.com/act123/usr6/pic.jpg", true),
+                ("https://example.com/act123/team/pic.jpg", true),
+                ("https://example.com/act456/team/pic.jpg", false),
+                ("https://example.com/team/pic.jpg", false),
+            ],
+        );
+        assert_matches(
+            pathname("/act123/*4/pic.jpg"),
+            &[
+                ("https://example.com/act123/usr4", false),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/usr5/pic.jpg", false),
+                ("https://example.com/act123/team4/pic.jpg", true),
+                ("https://example.com/act456/team5/pic.jpg", false),
+                ("https://example.com/team/pic.jpg", false),
+                ("https://example.com/act123/4/pic.jpg", true),
+            ],
+        );
+        assert_matches(
+            pathname("/act123/*sr*/pic.jpg"),
+            &[
+                ("https://example.com/act123/usr4/picsjpg", false),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/usr5/pic.jpg", true),
+                ("https://example.com/act123/team4/pic.jpg", false),
+                ("https://example.com/act123/team5/pic.jpg", false),
+                ("https://example.com/team/pic.jpg", false),
+                ("https://example.com/act123/sr/pic.jpg", true),
+            ],
+        );
+        assert_matches(
+            pathname("/act123/usr*/pic.jpg"),
+            &[
+                ("https://example.com/act123/usr4/picsjpg", false),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/usr5/pic.jpg", true),
+                ("https://example.com/act123/usr/pic.jpg", true),
+                ("https://example.com/act123/team4/pic.jpg", false),
+                ("https://example.com/act456/team5/pic.jpg", false),
+                ("https://example.com/team/pic.jpg", false),
+                ("https://sub.example.com/act123/usr6/pic.jpg", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_match_pathname_with_double_asterisk() {
+        assert_matches(
+            pathname("/act123/**"),
+            &[
+                ("https://com", false),
+                ("https://example.com", false),
+                ("https://sub.example.com", false),
+                ("https://example.com.uk", false),
+                ("https://example.com/act123", true),
+                ("https://example.com/act123/usr4", true),
+                ("https://example.com/act123/usr4/pic", true),
+                ("https://example.com/act123/usr4/picsjpg", true),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/usr5/pic.jpg", true),
+                ("https://example.com/act123/usr6/pic.jpg", true),
+                ("https://example.com/act123/team/pic.jpg", true),
+                ("https://example.com/act456/team/pic.jpg", false),
+                ("https://example.com/team/pic.jpg", false),
+                ("https://sub.example.com/act123/team/pic.jpg", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_has_match() {
+        let url = Url::parse("https://example.com").unwrap();
+        let has_match = |domains: &[&str], remote_patterns: &[RemotePattern]| {
+            let domains: Vec<_> = domains.iter().map(|d| d.to_string()).collect();
+            is_remote_image_allowed(&url, &domains, remote_patterns)
+        };
+        assert!(!has_match(&[], &[]));
+        assert!(!has_match(&["foo.com"], &[]));
+        assert!(has_match(&["example.com"], &[]));
+        assert!(!has_match(&["**.example.com"], &[]));
+        assert!(!has_match(&["*.example.com"], &[]));
+        assert!(!has_match(&[], &[hostname("foo.com")]));
+        assert!(has_match(
+            &[],
+            &[hostname("foo.com"), hostname("example.com")]
+        ));
+        assert!(!has_match(&[], &[pathname("/act123/**")]));
+        assert!(has_match(&["example.com"], &[pathname("/act123/**")]));
+    }
+
+    /// Serves `/image.png` and responds with a 404 to any other path, for the
+    /// given number of requests.
+    fn serve_image(requests: usize) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                // Drain the headers.
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let (status, body) = if request_line.starts_with("GET /image.png ") {
+                    ("200 OK", "image bytes")
+                } else {
+                    ("404 Not Found", "not found")
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\ncontent-type: image/png\r\ncontent-length: \
+                     {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_fetch_remote_image() {
+        crate::register();
+        let addr = serve_image(2);
+
+        let image_config = ImageConfig {
+            remote_patterns: vec![RemotePattern {
+                hostname: "127.0.0.1".to_string(),
+                protocol: Some(RemotePatternProtocal::Http),
+                port: Some(addr.port().to_string()),
+                pathname: None,
+            }],
+            ..Default::default()
+        };
+
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        run_once(tt, async move {
+            let url = format!("http://{addr}/image.png");
+            let Ok((remote_url, content)) = fetch_remote_image(&url, &image_config).await? else {
+                panic!("expected {url} to be fetched");
+            };
+            assert_eq!(remote_url.path(), "/image.png");
+            let FileContent::Content(file) = &*content.await? else {
+                panic!("expected the image content");
+            };
+            assert_eq!(file.content().to_str()?, "image bytes");
+
+            let url = format!("http://{addr}/missing.png");
+            let Err(error) = fetch_remote_image(&url, &image_config).await? else {
+                panic!("expected {url} to fail");
+            };
+            assert_eq!(error.status, 404);
+
+            // Not matching the port of the pattern, so rejected before fetching.
+            let url = "http://127.0.0.1:1/image.png";
+            let Err(error) = fetch_remote_image(url, &image_config).await? else {
+                panic!("expected {url} to be disallowed");
+            };
+            assert_eq!(error.status, 400);
+            assert!(error.message.contains("\"url\" parameter is not allowed"));
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use std::io::Write;
 
 use anyhow::{bail, Result};
+use image::DynamicImage;
 use turbo_tasks::Vc;
 use turbopack_binding::{
     turbo::tasks_fs::{rope::RopeBuilder, FileContent},
@@ -15,7 +16,11 @@ use turbopack_binding::{
     },
 };
 
-use super::module::BlurPlaceholderMode;
+use super::{
+    module::BlurPlaceholderMode,
+    placeholder::{blur_hash, dominant_color, thumb_hash},
+    srcset::resized_widths,
+};
 
 fn modifier() -> Vc<String> {
     Vc::cell("structured image object".to_string())
@@ -36,6 +41,7 @@ fn blur_options() -> Vc<BlurPlaceholderOptions> {
 pub struct StructuredImageFileSource {
     pub image: Vc<Box<dyn Source>>,
     pub blur_placeholder_mode: BlurPlaceholderMode,
+    pub srcset_widths: Option<Vec<u32>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -59,8 +65,12 @@ impl Asset for StructuredImageFileSource {
         };
         let mut result = RopeBuilder::from("");
         writeln!(result, "import src from \"IMAGE\";",)?;
+        let srcset = self.write_srcset_imports(&mut result, content).await?;
         let blur_options = blur_options();
-        match self.blur_placeholder_mode {
+        let query = self.image.ident().await?.query.await?;
+        let blur_placeholder_mode =
+            BlurPlaceholderMode::from_query(&query).unwrap_or(self.blur_placeholder_mode);
+        match blur_placeholder_mode {
             BlurPlaceholderMode::NextImageUrl => {
                 let info = get_meta_data(self.image.ident(), content, None).await?;
                 let width = info.width;
@@ -81,7 +91,7 @@ impl Asset for StructuredImageFileSource {
                     result,
                     "export default {{ src, width: {width}, height: {height}, blurDataURL: \
                      `/_next/image?w={blur_width}&q={quality}&url=${{encodeURIComponent(src)}}`, \
-                     blurWidth: {blur_width}, blurHeight: {blur_height} }}",
+                     blurWidth: {blur_width}, blurHeight: {blur_height}{srcset} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
                     quality = StringifyJs(&blur_options.quality),
@@ -94,7 +104,8 @@ impl Asset for StructuredImageFileSource {
                 writeln!(
                     result,
                     "export default {{ src, width: {width}, height: {height}, blurDataURL: \
-                     {blur_data_url}, blurWidth: {blur_width}, blurHeight: {blur_height} }}",
+                     {blur_data_url}, blurWidth: {blur_width}, blurHeight: {blur_height}{srcset} \
+                     }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
                     blur_data_url =
@@ -105,11 +116,34 @@ impl Asset for StructuredImageFileSource {
                         StringifyJs(&info.blur_placeholder.as_ref().map_or(0, |p| p.height),),
                 )?;
             }
+            BlurPlaceholderMode::DominantColor
+            | BlurPlaceholderMode::ThumbHash
+            | BlurPlaceholderMode::BlurHash => {
+                let info = get_meta_data(self.image.ident(), content, None).await?;
+                let (key, placeholder): (&str, fn(&DynamicImage) -> Result<String>) =
+                    match blur_placeholder_mode {
+                        BlurPlaceholderMode::DominantColor => {
+                            ("placeholderColor", |image| Ok(dominant_color(image)))
+                        }
+                        BlurPlaceholderMode::ThumbHash => {
+                            ("thumbHash", |image| Ok(thumb_hash(image)))
+                        }
+                        _ => ("blurHash", blur_hash),
+                    };
+                writeln!(
+                    result,
+                    "export default {{ src, width: {width}, height: {height}, {key}: \
+                     {placeholder}{srcset} }}",
+                    width = StringifyJs(&info.width),
+                    height = StringifyJs(&info.height),
+                    placeholder = StringifyJs(&compute_placeholder(content, placeholder).await?),
+                )?;
+            }
             BlurPlaceholderMode::None => {
                 let info = get_meta_data(self.image.ident(), content, None).await?;
                 writeln!(
                     result,
-                    "export default {{ src, width: {width}, height: {height} }}",
+                    "export default {{ src, width: {width}, height: {height}{srcset} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
                 )?;
@@ -118,3 +152,45 @@ impl Asset for StructuredImageFileSource {
         Ok(AssetContent::File(FileContent::Content(result.build().into()).cell()).cell())
     }
 }
+
+impl StructuredImageFileSource {
+    /// Imports the resized variants of the image and returns the `srcSet`
+    /// property to add to the exported object, if any. SVGs are served as is,
+    /// so they have no variants.
+    async fn write_srcset_imports(
+        &self,
+        result: &mut RopeBuilder,
+        content: Vc<FileContent>,
+    ) -> Result<String> {
+        let Some(srcset_widths) = &self.srcset_widths else {
+            return Ok(String::new());
+        };
+        if self.image.ident().path().await?.extension_ref() == Some("svg") {
+            return Ok(String::new());
+        }
+
+        let info = get_meta_data(self.image.ident(), content, None).await?;
+        let mut candidates = Vec::new();
+        for width in resized_widths(srcset_widths, info.width) {
+            writeln!(result, "import src_{width} from \"IMAGE_{width}\";")?;
+            candidates.push(format!("${{src_{width}}} {width}w"));
+        }
+        candidates.push(format!("${{src}} {}w", info.width));
+        Ok(format!(", srcSet: `{}`", candidates.join(", ")))
+    }
+}
+
+/// Decodes the image and computes a placeholder from its pixels. Images that
+/// can't be decoded, like SVGs, have no placeholder.
+async fn compute_placeholder(
+    content: Vc<FileContent>,
+    placeholder: fn(&DynamicImage) -> Result<String>,
+) -> Result<Option<String>> {
+    let FileContent::Content(file) = &*content.await? else {
+        return Ok(None);
+    };
+    let Ok(image) = image::load_from_memory(&file.content().to_bytes()?) else {
+        return Ok(None);
+    };
+    Ok(Some(placeholder(&image)?))
+}
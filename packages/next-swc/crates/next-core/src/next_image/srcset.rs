@@ -0,0 +1,129 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::{
+    core::{
+        asset::{Asset, AssetContent},
+        ident::AssetIdent,
+        source::Source,
+    },
+    image::process::optimize,
+};
+
+use crate::{
+    mode::NextMode,
+    next_config::{NextConfig, OutputType},
+};
+
+/// The quality resized variants are encoded with, the default of `next/image`.
+const SRCSET_QUALITY: u8 = 75;
+
+/// Returns the widths static image imports are pre-resized to, when building
+/// for `output: 'export'`. There is no image optimizer at runtime then, so the
+/// `srcSet` is generated at build time instead.
+pub(crate) fn static_srcset_widths(next_config: &NextConfig, mode: NextMode) -> Option<Vec<u32>> {
+    if !matches!(mode, NextMode::Build) || next_config.output != Some(OutputType::Export) {
+        return None;
+    }
+
+    let mut widths: Vec<u32> = next_config
+        .images
+        .device_sizes
+        .iter()
+        .chain(next_config.images.image_sizes.iter())
+        .map(|&size| size as u32)
+        .collect();
+    widths.sort_unstable();
+    widths.dedup();
+    Some(widths)
+}
+
+/// Returns the widths of the resized variants of an image. Variants are only
+/// generated up to the width of the image, as they would otherwise just be
+/// upscaled.
+pub(super) fn resized_widths(widths: &[u32], image_width: u32) -> Vec<u32> {
+    widths
+        .iter()
+        .copied()
+        .filter(|&width| width < image_width)
+        .collect()
+}
+
+fn modifier(width: u32) -> Vc<String> {
+    Vc::cell(format!("resized to {width}w"))
+}
+
+/// An image resized to a maximum width, emitted as its own asset.
+#[turbo_tasks::value(shared)]
+pub struct ResizedImageSource {
+    pub image: Vc<Box<dyn Source>>,
+    pub width: u32,
+}
+
+#[turbo_tasks::value_impl]
+impl ResizedImageSource {
+    #[turbo_tasks::function]
+    pub fn new(image: Vc<Box<dyn Source>>, width: u32) -> Vc<Self> {
+        ResizedImageSource { image, width }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for ResizedImageSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.image.ident().with_modifier(modifier(self.width))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ResizedImageSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let AssetContent::File(content) = *self.image.content().await? else {
+            return Ok(self.image.content());
+        };
+        Ok(AssetContent::File(optimize(
+            self.image.ident(),
+            content,
+            self.width,
+            u32::MAX,
+            SRCSET_QUALITY,
+        ))
+        .cell())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resized_widths, static_srcset_widths};
+    use crate::{
+        mode::NextMode,
+        next_config::{NextConfig, OutputType},
+    };
+
+    #[test]
+    fn test_static_srcset_only_for_export_builds() {
+        let mut next_config = NextConfig::default();
+        assert_eq!(static_srcset_widths(&next_config, NextMode::Build), None);
+
+        next_config.output = Some(OutputType::Export);
+        assert_eq!(
+            static_srcset_widths(&next_config, NextMode::Development),
+            None
+        );
+        assert_eq!(
+            static_srcset_widths(&next_config, NextMode::Build),
+            Some(vec![
+                16, 32, 48, 64, 96, 128, 256, 384, 640, 750, 828, 1080, 1200, 1920, 2048, 3840
+            ])
+        );
+    }
+
+    #[test]
+    fn test_skips_upscaled_widths() {
+        assert_eq!(
+            resized_widths(&[16, 640, 1080, 1200, 3840], 1200),
+            vec![16, 640, 1080]
+        );
+    }
+}
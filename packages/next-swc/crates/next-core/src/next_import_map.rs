@@ -26,10 +26,18 @@ use crate::{
     next_client::context::ClientContextType,
     next_config::NextConfig,
     next_font::{
-        google::{NextFontGoogleCssModuleReplacer, NextFontGoogleReplacer},
-        local::{NextFontLocalCssModuleReplacer, NextFontLocalReplacer},
+        google::{
+            cache::{NextFontGoogleFontFileReplacer, NEXT_FONT_GOOGLE_FONT_FILE_REQUEST},
+            NextFontGoogleCssModuleReplacer, NextFontGoogleReplacer,
+        },
+        local::{
+            subset::{NextFontLocalSubsetReplacer, NEXT_FONT_LOCAL_SUBSET_REQUEST},
+            NextFontLocalCssModuleReplacer, NextFontLocalFileReplacer, NextFontLocalReplacer,
+            NEXT_FONT_LOCAL_FILE_REQUEST,
+        },
     },
     next_server::context::ServerContextType,
+    next_shared::transforms::styled_jsx::{StyledJsxCssReplacer, STYLED_JSX_CSS_REQUEST},
     util::NextRuntime,
 };
 
@@ -1022,6 +1030,15 @@ async fn insert_next_shared_aliases(
         .into(),
     );
 
+    import_map.insert_alias(
+        // Request path from `url()`s in the css module above for cached font files
+        AliasPattern::exact(NEXT_FONT_GOOGLE_FONT_FILE_REQUEST),
+        ImportMapping::Dynamic(Vc::upcast(NextFontGoogleFontFileReplacer::new(
+            project_path,
+        )))
+        .into(),
+    );
+
     import_map.insert_alias(
         // Request path from js via next-font swc transform
         AliasPattern::exact("next/font/local/target.css"),
@@ -1042,6 +1059,24 @@ async fn insert_next_shared_aliases(
         .into(),
     );
 
+    import_map.insert_alias(
+        // Request path from `url()`s in the css module above
+        AliasPattern::exact(NEXT_FONT_LOCAL_FILE_REQUEST),
+        ImportMapping::Dynamic(Vc::upcast(NextFontLocalFileReplacer::new())).into(),
+    );
+
+    import_map.insert_alias(
+        // Request path from `url()`s in the css module above when the font is subset
+        AliasPattern::exact(NEXT_FONT_LOCAL_SUBSET_REQUEST),
+        ImportMapping::Dynamic(Vc::upcast(NextFontLocalSubsetReplacer::new(project_path))).into(),
+    );
+
+    import_map.insert_alias(
+        // Request path from js via the styled-jsx transform when static styles are extracted
+        AliasPattern::exact(STYLED_JSX_CSS_REQUEST),
+        ImportMapping::Dynamic(Vc::upcast(StyledJsxCssReplacer::new())).into(),
+    );
+
     import_map.insert_singleton_alias("@swc/helpers", get_next_package(project_path));
     import_map.insert_singleton_alias("styled-jsx", get_next_package(project_path));
     import_map.insert_singleton_alias("next", project_path);
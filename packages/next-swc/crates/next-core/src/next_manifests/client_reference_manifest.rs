@@ -16,6 +16,7 @@ use super::{ClientReferenceManifest, ManifestNode, ManifestNodeEntry, ModuleId};
 use crate::{
     next_app::ClientReferencesChunks,
     next_client_reference::{ClientReferenceType, ClientReferences},
+    util::NextRuntime,
 };
 
 #[turbo_tasks::value_impl]
@@ -29,6 +30,7 @@ impl ClientReferenceManifest {
         client_references_chunks: Vc<ClientReferencesChunks>,
         client_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
         ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        runtime: NextRuntime,
         asset_prefix: Vc<Option<String>>,
     ) -> Result<Vc<Box<dyn OutputAsset>>> {
         let mut entry_manifest: ClientReferenceManifest = Default::default();
@@ -177,9 +179,11 @@ impl ClientReferenceManifest {
                         },
                     );
 
-                    entry_manifest
-                        .ssr_module_mapping
-                        .insert((&*client_module_id).into(), ssr_manifest_node);
+                    let ssr_module_mapping = match runtime {
+                        NextRuntime::NodeJs => &mut entry_manifest.ssr_module_mapping,
+                        NextRuntime::Edge => &mut entry_manifest.edge_ssr_module_mapping,
+                    };
+                    ssr_module_mapping.insert((&*client_module_id).into(), ssr_manifest_node);
                 }
             }
         }
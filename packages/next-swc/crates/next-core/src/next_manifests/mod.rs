@@ -4,7 +4,8 @@ pub(crate) mod client_reference_manifest;
 
 use std::collections::HashMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::TraceRawVcs;
 
 use crate::next_config::Rewrites;
 
@@ -41,7 +42,7 @@ impl Default for MiddlewaresManifest {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TraceRawVcs)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum RouteHas {
     Header {
@@ -89,16 +90,23 @@ pub struct EdgeFunctionDefinition {
     pub name: String,
     pub page: String,
     pub matchers: Vec<MiddlewareMatcher>,
-    // TODO: AssetBinding[]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub wasm: Option<Vec<()>>,
-    // TODO: AssetBinding[]
+    pub wasm: Option<Vec<AssetBinding>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub assets: Option<Vec<()>>,
+    pub assets: Option<Vec<AssetBinding>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub regions: Option<Regions>,
 }
 
+/// A file the edge runtime provides to an edge function under a name.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBinding {
+    pub name: String,
+    /// The path of the file, relative to the dist directory.
+    pub file_path: String,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(untagged)]
 pub enum Regions {
@@ -107,6 +115,7 @@ pub enum Regions {
 }
 
 #[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct MiddlewaresManifestV2 {
     pub sorted_middleware: Vec<String>,
     pub middleware: HashMap<String, EdgeFunctionDefinition>,
@@ -123,7 +132,7 @@ pub struct ReactLoadableManifest {
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ReactLoadableManifestEntry {
-    pub id: u32,
+    pub id: ModuleId,
     pub files: Vec<String>,
 }
 
@@ -154,6 +163,21 @@ pub struct ServerReferenceManifest {
     pub edge: HashMap<String, ActionManifestEntry>,
 }
 
+impl ServerReferenceManifest {
+    /// Merges the actions of another manifest, usually the manifest of another
+    /// page, into this one.
+    pub fn extend(&mut self, other: ServerReferenceManifest) {
+        for (mapping, other_mapping) in [(&mut self.node, other.node), (&mut self.edge, other.edge)]
+        {
+            for (hash, other_entry) in other_mapping {
+                let entry = mapping.entry(hash).or_default();
+                entry.workers.extend(other_entry.workers);
+                entry.layer.extend(other_entry.layer);
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ActionManifestEntry {
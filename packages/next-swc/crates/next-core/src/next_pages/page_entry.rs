@@ -22,7 +22,7 @@ use turbopack_binding::{
 };
 
 use crate::{
-    next_edge::entry::wrap_edge_entry,
+    next_edge::entry::{pages_edge_function_name, wrap_edge_entry},
     util::{file_content_rope, load_next_js_template, NextRuntime},
 };
 
@@ -118,7 +118,7 @@ pub async fn create_page_ssr_entry_module(
             ssr_module_context,
             project_root,
             ssr_module,
-            definition_pathname.to_string(),
+            pages_edge_function_name(definition_pathname),
         );
     }
 
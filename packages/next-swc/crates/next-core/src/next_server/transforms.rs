@@ -7,10 +7,12 @@ use crate::{
     mode::NextMode,
     next_client_reference::css_client_reference::css_client_reference_rule::get_next_css_client_reference_transforms_rule,
     next_config::NextConfig,
+    next_image::srcset::static_srcset_widths,
     next_server::context::ServerContextType,
     next_shared::transforms::{
         get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
         get_next_modularize_imports_rule, get_next_pages_transforms_rule,
+        get_react_remove_properties_transform_rule, get_remove_console_transform_rule,
         get_server_actions_transform_rule, server_actions::ActionsTransform,
     },
 };
@@ -24,13 +26,25 @@ pub async fn get_next_server_transforms_rules(
 ) -> Result<Vec<ModuleRule>> {
     let mut rules = vec![];
 
-    let modularize_imports_config = &next_config.await?.modularize_imports;
+    let next_config_value = next_config.await?;
+    let modularize_imports_config = &next_config_value.modularize_imports;
     let enable_server_actions = *next_config.enable_server_actions().await?;
     if let Some(modularize_imports_config) = modularize_imports_config {
         rules.push(get_next_modularize_imports_rule(modularize_imports_config));
     }
     rules.push(get_next_font_transform_rule());
 
+    if let Some(compiler) = &next_config_value.compiler {
+        if let Some(remove_console_config) = &compiler.remove_console {
+            rules.extend(get_remove_console_transform_rule(remove_console_config)?);
+        }
+        if let Some(react_remove_properties_config) = &compiler.react_remove_properties {
+            rules.extend(get_react_remove_properties_transform_rule(
+                react_remove_properties_config,
+            )?);
+        }
+    }
+
     let (is_server_components, pages_dir) = match context_ty {
         ServerContextType::Pages { pages_dir } => (false, Some(pages_dir)),
         ServerContextType::PagesData { pages_dir } => {
@@ -64,7 +78,10 @@ pub async fn get_next_server_transforms_rules(
 
     rules.push(get_next_dynamic_transform_rule(true, is_server_components, pages_dir, mode).await?);
 
-    rules.push(get_next_image_rule());
+    rules.push(get_next_image_rule(static_srcset_widths(
+        &next_config_value,
+        mode,
+    )));
 
     Ok(rules)
 }
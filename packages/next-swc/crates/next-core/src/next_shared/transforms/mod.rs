@@ -3,7 +3,9 @@ pub(crate) mod modularize_imports;
 pub(crate) mod next_dynamic;
 pub(crate) mod next_font;
 pub(crate) mod next_strip_page_exports;
+pub(crate) mod react_remove_properties;
 pub(crate) mod relay;
+pub(crate) mod remove_console;
 pub(crate) mod server_actions;
 pub(crate) mod styled_components;
 pub(crate) mod styled_jsx;
@@ -13,7 +15,9 @@ pub use modularize_imports::{get_next_modularize_imports_rule, ModularizeImportP
 pub use next_dynamic::get_next_dynamic_transform_rule;
 pub use next_font::get_next_font_transform_rule;
 pub use next_strip_page_exports::get_next_pages_transforms_rule;
+pub use react_remove_properties::get_react_remove_properties_transform_rule;
 pub use relay::get_relay_transform_plugin;
+pub use remove_console::get_remove_console_transform_rule;
 pub use server_actions::get_server_actions_transform_rule;
 use turbo_tasks::{Value, Vc};
 use turbopack_binding::turbopack::{
@@ -23,8 +27,10 @@ use turbopack_binding::turbopack::{
 
 use crate::next_image::{module::BlurPlaceholderMode, StructuredImageModuleType};
 
-/// Returns a rule which applies the Next.js dynamic transform.
-pub fn get_next_image_rule() -> ModuleRule {
+/// Returns a rule which turns image imports into structured image modules.
+/// `srcset_widths` are the widths to pre-resize images to, see
+/// [crate::next_image::srcset::static_srcset_widths].
+pub fn get_next_image_rule(srcset_widths: Option<Vec<u32>>) -> ModuleRule {
     ModuleRule::new(
         ModuleRuleCondition::any(vec![
             ModuleRuleCondition::ResourcePathEndsWith(".jpg".to_string()),
@@ -42,9 +48,10 @@ pub fn get_next_image_rule() -> ModuleRule {
             ModuleRuleCondition::ResourcePathEndsWith(".avif".to_string()),
         ]),
         vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
-            Vc::upcast(StructuredImageModuleType::new(Value::new(
-                BlurPlaceholderMode::DataUrl,
-            ))),
+            Vc::upcast(StructuredImageModuleType::new(
+                Value::new(BlurPlaceholderMode::DataUrl),
+                srcset_widths,
+            )),
         ))],
     )
 }
@@ -11,6 +11,18 @@ use super::module_rule_match_js_no_url;
 
 /// Returns a rule which applies the Next.js font transform.
 pub fn get_next_font_transform_rule() -> ModuleRule {
+    ModuleRule::new(
+        // TODO: Only match in pages (not pages/api), app/, etc.
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
+            get_next_font_transform(),
+        ]))],
+    )
+}
+
+/// Returns the Next.js font transform, which replaces font loader calls with
+/// imports of `<loader>/target.css?<options>`.
+pub fn get_next_font_transform() -> EcmascriptInputTransform {
     let font_loaders = vec![
         "next/font/google".into(),
         "@next/font/google".into(),
@@ -18,15 +30,7 @@ pub fn get_next_font_transform_rule() -> ModuleRule {
         "@next/font/local".into(),
     ];
 
-    let transformer =
-        EcmascriptInputTransform::Plugin(Vc::cell(Box::new(NextJsFont { font_loaders }) as _));
-    ModuleRule::new(
-        // TODO: Only match in pages (not pages/api), app/, etc.
-        module_rule_match_js_no_url(),
-        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
-            transformer,
-        ]))],
-    )
+    EcmascriptInputTransform::Plugin(Vc::cell(Box::new(NextJsFont { font_loaders }) as _))
 }
 
 #[derive(Debug)]
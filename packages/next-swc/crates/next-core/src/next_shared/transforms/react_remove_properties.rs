@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use react_remove_properties::{react_remove_properties, Config};
+use swc_core::{
+    common::util::take::Take,
+    ecma::{
+        ast::{Module, Program},
+        visit::{Fold, FoldWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::{
+    ecmascript::{CustomTransformer, EcmascriptInputTransform, TransformContext},
+    turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+};
+
+use super::module_rule_match_js_no_url;
+use crate::next_config::ReactRemoveProperties;
+
+/// Returns a rule which applies the `compiler.reactRemoveProperties`
+/// transform, or `None` when it is disabled.
+pub fn get_react_remove_properties_transform_rule(
+    react_remove_properties_config: &ReactRemoveProperties,
+) -> Result<Option<ModuleRule>> {
+    let Some(config) = react_remove_properties_config_for(react_remove_properties_config)? else {
+        return Ok(None);
+    };
+
+    let transformer =
+        EcmascriptInputTransform::Plugin(Vc::cell(Box::new(ReactRemovePropertiesTransformer {
+            config,
+        }) as _));
+    Ok(Some(ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
+            transformer,
+        ]))],
+    )))
+}
+
+/// Converts the next.config option into the config of the swc transform.
+/// Returns `None` when the transform is disabled.
+fn react_remove_properties_config_for(
+    react_remove_properties_config: &ReactRemoveProperties,
+) -> Result<Option<Config>> {
+    // The next.config shape is the same one the napi transform accepts.
+    let config: Config =
+        serde_json::from_value(serde_json::to_value(react_remove_properties_config)?)
+            .context("invalid `compiler.reactRemoveProperties` config")?;
+    Ok(config.truthy().then_some(config))
+}
+
+#[derive(Debug)]
+struct ReactRemovePropertiesTransformer {
+    config: Config,
+}
+
+impl ReactRemovePropertiesTransformer {
+    fn visitor(&self) -> impl Fold {
+        react_remove_properties(self.config.clone())
+    }
+}
+
+#[async_trait]
+impl CustomTransformer for ReactRemovePropertiesTransformer {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        let p = std::mem::replace(program, Program::Module(Module::dummy()));
+        *program = p.fold_with(&mut self.visitor());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, path::PathBuf};
+
+    use anyhow::Result;
+    use turbopack_binding::swc::{
+        core::ecma::{
+            parser::{EsConfig, Syntax},
+            transforms::testing::test_fixture,
+        },
+        testing::fixture,
+    };
+
+    use super::{react_remove_properties_config_for, ReactRemovePropertiesTransformer};
+    use crate::next_config::CompilerConfig;
+
+    fn compiler_config(json: &str) -> CompilerConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_react_remove_properties_disabled() -> Result<()> {
+        let config = compiler_config(r#"{"reactRemoveProperties": false}"#);
+        assert!(react_remove_properties_config_for(
+            config.react_remove_properties.as_ref().unwrap()
+        )?
+        .is_none());
+        Ok(())
+    }
+
+    /// Runs the transform with the `compiler` config of `config.json`. The
+    /// outputs are the ones of the napi transform with the same config.
+    #[fixture("tests/fixture/react-remove-properties/**/input.js")]
+    fn react_remove_properties_fixture(input: PathBuf) {
+        let dir = input.parent().unwrap();
+        let output = dir.join("output.js");
+        let config = compiler_config(&read_to_string(dir.join("config.json")).unwrap());
+        let transformer = ReactRemovePropertiesTransformer {
+            config: react_remove_properties_config_for(
+                config.react_remove_properties.as_ref().unwrap(),
+            )
+            .unwrap()
+            .unwrap(),
+        };
+        test_fixture(
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            &|_tr| transformer.visitor(),
+            &input,
+            &output,
+            Default::default(),
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use remove_console::{remove_console, Config};
+use swc_core::{
+    common::{util::take::Take, Mark, SyntaxContext},
+    ecma::{
+        ast::{Module, Program},
+        visit::{Fold, FoldWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::{
+    ecmascript::{CustomTransformer, EcmascriptInputTransform, TransformContext},
+    turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+};
+
+use super::module_rule_match_js_no_url;
+use crate::next_config::RemoveConsoleConfig;
+
+/// Returns a rule which applies the `compiler.removeConsole` transform, or
+/// `None` when it is disabled.
+pub fn get_remove_console_transform_rule(
+    remove_console_config: &RemoveConsoleConfig,
+) -> Result<Option<ModuleRule>> {
+    let Some(config) = remove_console_config_for(remove_console_config)? else {
+        return Ok(None);
+    };
+
+    let transformer = EcmascriptInputTransform::Plugin(Vc::cell(
+        Box::new(RemoveConsoleTransformer { config }) as _,
+    ));
+    Ok(Some(ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
+            transformer,
+        ]))],
+    )))
+}
+
+/// Converts the next.config option into the config of the swc transform.
+/// Returns `None` when the transform is disabled.
+fn remove_console_config_for(
+    remove_console_config: &RemoveConsoleConfig,
+) -> Result<Option<Config>> {
+    // The next.config shape is the same one the napi transform accepts.
+    let config: Config = serde_json::from_value(serde_json::to_value(remove_console_config)?)
+        .context("invalid `compiler.removeConsole` config")?;
+    Ok(config.truthy().then_some(config))
+}
+
+#[derive(Debug)]
+struct RemoveConsoleTransformer {
+    config: Config,
+}
+
+impl RemoveConsoleTransformer {
+    fn visitor(&self, unresolved_mark: Mark) -> impl Fold {
+        remove_console(
+            self.config.clone(),
+            SyntaxContext::empty().apply_mark(unresolved_mark),
+        )
+    }
+}
+
+#[async_trait]
+impl CustomTransformer for RemoveConsoleTransformer {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        let p = std::mem::replace(program, Program::Module(Module::dummy()));
+        *program = p.fold_with(&mut self.visitor(ctx.unresolved_mark));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, path::PathBuf};
+
+    use anyhow::Result;
+    use turbopack_binding::swc::{
+        core::{
+            common::{chain, Mark},
+            ecma::{
+                parser::{EsConfig, Syntax},
+                transforms::{base::resolver, testing::test_fixture},
+            },
+        },
+        testing::fixture,
+    };
+
+    use super::{remove_console_config_for, RemoveConsoleTransformer};
+    use crate::next_config::CompilerConfig;
+
+    fn compiler_config(json: &str) -> CompilerConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_remove_console_disabled() -> Result<()> {
+        let config = compiler_config(r#"{"removeConsole": false}"#);
+        assert!(remove_console_config_for(config.remove_console.as_ref().unwrap())?.is_none());
+        Ok(())
+    }
+
+    /// Runs the transform with the `compiler` config of `config.json`. The
+    /// outputs are the ones of the napi transform with the same config.
+    #[fixture("tests/fixture/remove-console/**/input.js")]
+    fn remove_console_fixture(input: PathBuf) {
+        let dir = input.parent().unwrap();
+        let output = dir.join("output.js");
+        let config = compiler_config(&read_to_string(dir.join("config.json")).unwrap());
+        let transformer = RemoveConsoleTransformer {
+            config: remove_console_config_for(config.remove_console.as_ref().unwrap())
+                .unwrap()
+                .unwrap(),
+        };
+        test_fixture(
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            &|_tr| {
+                let unresolved_mark = Mark::new();
+                let top_level_mark = Mark::new();
+                chain!(
+                    resolver(unresolved_mark, top_level_mark, false),
+                    transformer.visitor(unresolved_mark)
+                )
+            },
+            &input,
+            &output,
+            Default::default(),
+        );
+    }
+}
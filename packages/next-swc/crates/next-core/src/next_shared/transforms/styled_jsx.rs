@@ -1,14 +1,185 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use next_swc::styled_jsx_extract::{extracted_css, styled_jsx_extract, ExtractedStyles};
+use swc_core::{
+    common::{util::take::Take, DUMMY_SP},
+    ecma::{
+        ast::{ImportDecl, Module, ModuleDecl, ModuleItem, Program, Str},
+        visit::{Fold, FoldWith},
+    },
+};
 use turbo_tasks::Vc;
-use turbopack_binding::turbopack::{
-    ecmascript::OptionTransformPlugin,
-    ecmascript_plugin::transform::styled_jsx::StyledJsxTransformer,
+use turbopack_binding::{
+    turbo::{
+        tasks_fs::{File, FileSystemPath},
+        tasks_hash::hash_xxh3_hash64,
+    },
+    turbopack::{
+        core::{
+            asset::AssetContent,
+            resolve::{
+                options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+                parse::Request,
+                ResolveResult,
+            },
+            virtual_source::VirtualSource,
+        },
+        ecmascript::{CustomTransformer, OptionTransformPlugin, TransformContext},
+        ecmascript_plugin::transform::styled_jsx::StyledJsxTransformer,
+    },
 };
 
-/// Returns a transform plugin for the relay graphql transform.
+use crate::next_config::NextConfig;
+
+/// The request for the stylesheet of the static styled-jsx styles of a module,
+/// with the base64 encoded CSS as query. Intercepted by [StyledJsxCssReplacer].
+pub const STYLED_JSX_CSS_REQUEST: &str = "@vercel/turbopack-next/internal/styled-jsx/extracted.css";
+
+/// Returns a transform plugin for the styled-jsx transform. With
+/// `experimental.extractStyledJsxCss`, static styles are moved to a
+/// stylesheet imported by the module.
 #[turbo_tasks::function]
-pub async fn get_styled_jsx_transform_plugin() -> Result<Vc<OptionTransformPlugin>> {
+pub async fn get_styled_jsx_transform_plugin(
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<OptionTransformPlugin>> {
+    let transformer = StyledJsxTransformer::new();
     Ok(Vc::cell(Some(Vc::cell(
-        Box::new(StyledJsxTransformer::new()) as _,
+        if *next_config.extract_styled_jsx_css().await? {
+            Box::new(StyledJsxExtractTransformer { transformer }) as _
+        } else {
+            Box::new(transformer) as _
+        },
     ))))
 }
+
+#[derive(Debug)]
+struct StyledJsxExtractTransformer {
+    transformer: StyledJsxTransformer,
+}
+
+#[async_trait]
+impl CustomTransformer for StyledJsxExtractTransformer {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        self.transformer.transform(program, ctx).await?;
+
+        let p = std::mem::replace(program, Program::Module(Module::dummy()));
+        *program = p.fold_with(&mut StyledJsxCssImport);
+
+        Ok(())
+    }
+}
+
+/// Moves the static styles of the styled-jsx output to a stylesheet imported
+/// by the module.
+struct StyledJsxCssImport;
+
+impl Fold for StyledJsxCssImport {
+    fn fold_module(&mut self, module: Module) -> Module {
+        let extracted_styles: ExtractedStyles = Default::default();
+        let mut module = module.fold_with(&mut styled_jsx_extract(extracted_styles.clone()));
+
+        let css = extracted_css(&extracted_styles.borrow());
+        if css.is_empty() {
+            return module;
+        }
+        let request = format!("{}?{}", STYLED_JSX_CSS_REQUEST, URL_SAFE_NO_PAD.encode(css));
+        module.body.insert(
+            0,
+            ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                span: DUMMY_SP,
+                specifiers: vec![],
+                src: Box::new(Str {
+                    span: DUMMY_SP,
+                    value: request.into(),
+                    raw: None,
+                }),
+                type_only: false,
+                with: None,
+            })),
+        );
+
+        module
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct StyledJsxCssReplacer;
+
+#[turbo_tasks::value_impl]
+impl StyledJsxCssReplacer {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        Self::cell(StyledJsxCssReplacer)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for StyledJsxCssReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    /// Intercepts the stylesheet requests added by the styled-jsx transform
+    /// and returns a VirtualSource of the extracted CSS, next to the module it
+    /// was extracted from.
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module {
+            module: _,
+            path: _,
+            query: query_vc,
+        } = &*request.await?
+        else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let query = query_vc.await?;
+        let css = URL_SAFE_NO_PAD
+            .decode(query.trim_start_matches('?'))
+            .ok()
+            .and_then(|css| String::from_utf8(css).ok())
+            .context("Expected the extracted styled-jsx CSS as query")?;
+
+        let css_asset = VirtualSource::new(
+            context.join(format!("styled-jsx-{:x}.css", hash_xxh3_hash64(&css))),
+            AssetContent::file(File::from(css).into()),
+        );
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(css_asset)).into()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use turbopack_binding::swc::{
+        core::ecma::{
+            parser::{EsConfig, Syntax},
+            transforms::testing::test_fixture,
+        },
+        testing::fixture,
+    };
+
+    use super::StyledJsxCssImport;
+
+    #[fixture("tests/fixture/styled-jsx-css/**/input.js")]
+    fn styled_jsx_css_fixture(input: PathBuf) {
+        let output = input.parent().unwrap().join("output.js");
+        test_fixture(
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            &|_tr| StyledJsxCssImport,
+            &input,
+            &output,
+            Default::default(),
+        );
+    }
+}
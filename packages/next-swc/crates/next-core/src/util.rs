@@ -1,5 +1,6 @@
 use anyhow::{bail, Context, Result};
 use indexmap::{IndexMap, IndexSet};
+use next_transform_strip_page_exports::{get_page_mode, PageMode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use swc_core::ecma::ast::Program;
@@ -18,7 +19,7 @@ use turbopack_binding::{
             virtual_source::VirtualSource,
         },
         ecmascript::{
-            analyzer::{JsValue, ObjectPart},
+            analyzer::{ConstantNumber, ConstantValue, JsValue, ObjectPart},
             parse::ParseResult,
             utils::StringifyJs,
             EcmascriptModuleAsset,
@@ -30,6 +31,7 @@ use turbopack_binding::{
 use crate::{
     next_config::{NextConfig, OutputType},
     next_import_map::get_next_package,
+    next_manifests::RouteHas,
 };
 
 const NEXT_TEMPLATE_PATH: &str = "dist/esm/build/templates";
@@ -150,7 +152,38 @@ pub struct NextSourceConfig {
     pub runtime: NextRuntime,
 
     /// Middleware router matchers
-    pub matcher: Option<Vec<String>>,
+    pub matcher: Option<Vec<MiddlewareMatcherConfig>>,
+}
+
+/// A middleware matcher of the `config` export, given either as a source or
+/// as an object with conditions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MiddlewareMatcherConfig {
+    pub source: String,
+    /// Whether the source is matched after a locale prefix when i18n is
+    /// configured.
+    #[serde(default = "default_matcher_locale")]
+    pub locale: bool,
+    #[serde(default)]
+    pub has: Vec<RouteHas>,
+    #[serde(default)]
+    pub missing: Vec<RouteHas>,
+}
+
+fn default_matcher_locale() -> bool {
+    true
+}
+
+impl MiddlewareMatcherConfig {
+    fn from_source(source: &str) -> Self {
+        MiddlewareMatcherConfig {
+            source: source.to_string(),
+            locale: true,
+            has: vec![],
+            missing: vec![],
+        }
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -205,6 +238,24 @@ impl Issue for NextSourceConfigParsingIssue {
     }
 }
 
+/// Returns whether a page module exports `getStaticProps` or `getStaticPaths`,
+/// which makes it statically generated.
+#[turbo_tasks::function]
+pub async fn is_ssg_page(module: Vc<Box<dyn Module>>) -> Result<Vc<bool>> {
+    if let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    {
+        if let ParseResult::Ok {
+            program: Program::Module(module_ast),
+            ..
+        } = &*ecmascript_asset.parse().await?
+        {
+            return Ok(Vc::cell(get_page_mode(module_ast) == PageMode::Ssg));
+        }
+    }
+    Ok(Vc::cell(false))
+}
+
 #[turbo_tasks::function]
 pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<NextSourceConfig>> {
     if let Some(ecmascript_asset) =
@@ -301,35 +352,21 @@ fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> N
                         }
                         if key == "matcher" {
                             let mut matchers = vec![];
-                            match value {
-                                JsValue::Constant(matcher) => {
-                                    if let Some(matcher) = matcher.as_str() {
-                                        matchers.push(matcher.to_string());
-                                    } else {
-                                        invalid_config(
-                                            "The matcher property must be a string or array of \
-                                             strings",
-                                            value,
-                                        );
-                                    }
-                                }
-                                JsValue::Array { items, .. } => {
-                                    for item in items {
-                                        if let Some(matcher) = item.as_str() {
-                                            matchers.push(matcher.to_string());
-                                        } else {
-                                            invalid_config(
-                                                "The matcher property must be a string or array \
-                                                 of strings",
-                                                value,
-                                            );
-                                        }
-                                    }
+                            let items = match value {
+                                JsValue::Array { items, .. } => &items[..],
+                                value => std::slice::from_ref(value),
+                            };
+                            for item in items {
+                                if let Some(matcher) = parse_matcher_from_js_value(item) {
+                                    matchers.push(matcher);
+                                } else {
+                                    invalid_config(
+                                        "The matcher property must be a string, an object with \
+                                         `source`, `has`, `missing` and `locale`, or an array of \
+                                         them.",
+                                        item,
+                                    );
                                 }
-                                _ => invalid_config(
-                                    "The matcher property must be a string or array of strings",
-                                    value,
-                                ),
                             }
                             config.matcher = Some(matchers);
                         }
@@ -352,6 +389,42 @@ fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> N
     config
 }
 
+fn parse_matcher_from_js_value(value: &JsValue) -> Option<MiddlewareMatcherConfig> {
+    if let Some(source) = value.as_str() {
+        return Some(MiddlewareMatcherConfig::from_source(source));
+    }
+    serde_json::from_value(js_value_to_json(value)?).ok()
+}
+
+/// Converts a constant value of the config export, like an object literal, to
+/// JSON.
+fn js_value_to_json(value: &JsValue) -> Option<JsonValue> {
+    Some(match value {
+        JsValue::Constant(ConstantValue::Str(str)) => JsonValue::String(str.to_string()),
+        JsValue::Constant(ConstantValue::Num(ConstantNumber(num))) => {
+            serde_json::Number::from_f64(*num)?.into()
+        }
+        JsValue::Constant(ConstantValue::True) => JsonValue::Bool(true),
+        JsValue::Constant(ConstantValue::False) => JsonValue::Bool(false),
+        JsValue::Constant(ConstantValue::Null) => JsonValue::Null,
+        JsValue::Array { items, .. } => {
+            JsonValue::Array(items.iter().map(js_value_to_json).collect::<Option<_>>()?)
+        }
+        JsValue::Object { parts, .. } => JsonValue::Object(
+            parts
+                .iter()
+                .map(|part| match part {
+                    ObjectPart::KeyValue(key, value) => {
+                        Some((key.as_str()?.to_string(), js_value_to_json(value)?))
+                    }
+                    ObjectPart::Spread(_) => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        _ => return None,
+    })
+}
+
 /// Loads a next.js template, replaces `replacements` and `injections` and makes
 /// sure there are none left over.
 pub async fn load_next_js_template(
@@ -0,0 +1,3 @@
+export default function Home() {
+    return <div data-test-id="1" data-custom="2"><p data-testid="3">Hello</p></div>;
+}
@@ -0,0 +1,3 @@
+export default function Home() {
+    return <div data-custom="2"><p>Hello</p></div>;
+}
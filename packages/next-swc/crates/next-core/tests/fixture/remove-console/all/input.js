@@ -0,0 +1,9 @@
+console.log("module");
+function log(console) {
+    console.log("local binding");
+}
+export default function Page() {
+    console.warn("warning");
+    console.error("error");
+    return log;
+}
@@ -0,0 +1,9 @@
+;
+function log(console) {
+    console.log("local binding");
+}
+export default function Page() {
+    ;
+    ;
+    return log;
+}
@@ -0,0 +1,5 @@
+export default function Page() {
+    console.log("log");
+    console.error("error");
+    return null;
+}
@@ -0,0 +1,5 @@
+export default function Page() {
+    ;
+    console.error("error");
+    return null;
+}
@@ -0,0 +1,13 @@
+import _JSXStyle from "styled-jsx/style";
+export default function Button({ color }) {
+    return <button className={_JSXStyle.dynamic([
+        [
+            "5c1a3e7d",
+            [
+                color
+            ]
+        ]
+    ])}>Click<_JSXStyle id={"5c1a3e7d"} dynamic={[
+        color
+    ]}>{`button.__jsx-style-dynamic-selector{color:${color}}`}</_JSXStyle></button>;
+}
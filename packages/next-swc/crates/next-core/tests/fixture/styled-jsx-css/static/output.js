@@ -0,0 +1,4 @@
+import "@vercel/turbopack-next/internal/styled-jsx/extracted.css?cC5qc3gtMmI2ZDlhMWZ7Y29sb3I6cmVkfQ";
+export default function Home() {
+    return <div className={"jsx-2b6d9a1f"}><p className={"jsx-2b6d9a1f"}>Hello</p></div>;
+}
@@ -29,7 +29,7 @@ pub enum ExportFilter {
     StripDefaultExport,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum PageMode {
     #[default]
     None,
@@ -71,6 +71,59 @@ pub fn next_transform_strip_page_exports(
     })
 }
 
+/// Returns the mode of a page from its top-level data exports, without
+/// transforming it.
+///
+/// Conflicting data exports resolve to the first one encountered, like in the
+/// transform, which is also where they are reported.
+pub fn get_page_mode(module: &Module) -> PageMode {
+    let mut page_mode = PageMode::None;
+    let mut encounter_export = |export_type: ExportType| match export_type {
+        ExportType::GetServerSideProps if page_mode != PageMode::Ssg => {
+            page_mode = PageMode::Ssr;
+        }
+        ExportType::GetStaticPaths | ExportType::GetStaticProps if page_mode != PageMode::Ssr => {
+            page_mode = PageMode::Ssg;
+        }
+        _ => {}
+    };
+
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(n)) => {
+                for specifier in &n.specifiers {
+                    if let Some(result) = ExportType::from_specifier(specifier) {
+                        encounter_export(result.export_type);
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(d),
+                ..
+            })) => {
+                for decl in &d.decls {
+                    if let Some(export_type) =
+                        decl.name.as_ident().and_then(|i| ExportType::from_ident(i))
+                    {
+                        encounter_export(export_type);
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(decl),
+                ..
+            })) => {
+                if let Some(export_type) = ExportType::from_ident(&decl.ident) {
+                    encounter_export(export_type);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    page_mode
+}
+
 /// State of the transforms. Shared by the analyzer and the transform.
 #[derive(Debug, Default)]
 struct State {
@@ -0,0 +1,61 @@
+use next_transform_strip_page_exports::{get_page_mode, PageMode};
+use swc_core::{
+    common::{sync::Lrc, FileName, SourceMap},
+    ecma::{
+        ast::EsVersion,
+        parser::{parse_file_as_module, EsConfig, Syntax},
+    },
+};
+
+fn page_mode(src: &str) -> PageMode {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, src.to_string());
+    let module = parse_file_as_module(
+        &fm,
+        Syntax::Es(EsConfig {
+            jsx: true,
+            ..Default::default()
+        }),
+        EsVersion::latest(),
+        None,
+        &mut vec![],
+    )
+    .unwrap();
+    get_page_mode(&module)
+}
+
+#[test]
+fn page_mode_from_exports() {
+    assert_eq!(
+        page_mode("export default function Home() { return <div /> }"),
+        PageMode::None
+    );
+    assert_eq!(
+        page_mode("export async function getStaticProps() { return { props: {} } }"),
+        PageMode::Ssg
+    );
+    assert_eq!(
+        page_mode("export const getStaticPaths = () => ({ paths: [], fallback: false })"),
+        PageMode::Ssg
+    );
+    assert_eq!(
+        page_mode("function gsp() {}\nexport { gsp as getStaticProps }"),
+        PageMode::Ssg
+    );
+    assert_eq!(
+        page_mode("export { getStaticProps } from './data'"),
+        PageMode::Ssg
+    );
+    assert_eq!(
+        page_mode("export async function getServerSideProps() { return { props: {} } }"),
+        PageMode::Ssr
+    );
+}
+
+#[test]
+fn page_mode_first_data_export_wins() {
+    assert_eq!(
+        page_mode("export function getServerSideProps() {}\nexport function getStaticProps() {}"),
+        PageMode::Ssr
+    );
+}
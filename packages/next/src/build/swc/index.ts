@@ -146,6 +146,7 @@ export interface Binding {
   turbo: {
     startTrace: any
     nextBuild?: any
+    prefetchGoogleFonts?: any
     createTurboTasks?: any
     entrypoints: {
       stream: any
@@ -1319,6 +1320,9 @@ function loadNative(importPath?: string) {
 
           return ret
         },
+        prefetchGoogleFonts: (options: unknown) => {
+          return (customBindings ?? bindings).nextPrefetchGoogleFonts(options)
+        },
         startTrace: (options = {}, turboTasks: unknown) => {
           initHeapProfiler()
           const ret = (customBindings ?? bindings).runTurboTracing(
@@ -0,0 +1,9 @@
+import type arg from 'next/dist/compiled/arg/index.js'
+
+export const validArgs: arg.Spec = {
+  // Types
+  '--help': Boolean,
+  '--experimental-turbo-root': String,
+  // Aliases
+  '-h': '--help',
+}
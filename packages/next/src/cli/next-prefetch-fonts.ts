@@ -0,0 +1,55 @@
+#!/usr/bin/env node
+
+import { existsSync } from 'fs'
+import * as Log from '../build/output/log'
+import type { CliCommand } from '../lib/commands'
+import { loadBindings } from '../build/swc'
+import { printAndExit } from '../server/lib/utils'
+import { getProjectDir } from '../lib/get-project-dir'
+
+const nextPrefetchFonts: CliCommand = async (args) => {
+  if (args['--help']) {
+    printAndExit(
+      `
+      Description
+        Fetches the Google Fonts used by the application into the directory
+        set in NEXT_FONT_GOOGLE_CACHE_DIR, so that Turbopack builds can run
+        without network access
+
+      Usage
+        $ next experimental-prefetch-fonts <dir>
+
+      <dir> represents the directory of the Next.js application.
+      If no directory is provided, the current directory will be used.
+
+      Options
+      --experimental-turbo-root  The root directory of the workspace
+      --help, -h                 Displays this message
+    `,
+      0
+    )
+  }
+  if (!process.env.NEXT_FONT_GOOGLE_CACHE_DIR) {
+    printAndExit('> NEXT_FONT_GOOGLE_CACHE_DIR must be set to prefetch fonts')
+  }
+  const dir = getProjectDir(args._[0])
+
+  // Check if the provided directory exists
+  if (!existsSync(dir)) {
+    printAndExit(`> No such directory exists as the project root: ${dir}`)
+  }
+
+  const bindings = await loadBindings()
+  try {
+    await bindings.turbo.prefetchGoogleFonts({
+      dir,
+      root: args['--experimental-turbo-root'],
+    })
+  } catch (err) {
+    console.error('> Prefetching fonts failed')
+    printAndExit(err)
+  }
+  Log.info(`Fonts are cached in ${process.env.NEXT_FONT_GOOGLE_CACHE_DIR}`)
+}
+
+export { nextPrefetchFonts }
@@ -14,4 +14,6 @@ export const commandArgs: {
   info: () => require('../cli/next-info-args').validArgs,
   'experimental-compile': () => require('../cli/next-build-args').validArgs,
   'experimental-generate': () => require('../cli/next-build-args').validArgs,
+  'experimental-prefetch-fonts': () =>
+    require('../cli/next-prefetch-fonts-args').validArgs,
 }
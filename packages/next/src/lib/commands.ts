@@ -15,4 +15,6 @@ export const commands: { [command: string]: () => Promise<CliCommand> } = {
     Promise.resolve(require('../cli/next-build').nextBuild),
   'experimental-generate': () =>
     Promise.resolve(require('../cli/next-build').nextBuild),
+  'experimental-prefetch-fonts': () =>
+    Promise.resolve(require('../cli/next-prefetch-fonts').nextPrefetchFonts),
 }
@@ -40,6 +40,7 @@ const supportedTurbopackNextConfigOptions = [
   'amp',
   'devIndicators',
   'analyticsId',
+  'outputFileTracing',
 
   // Options that are ignored as they don't affect Turbopack
   'webpack',
@@ -58,11 +59,16 @@ const supportedTurbopackNextConfigOptions = [
   'experimental.externalMiddlewareRewritesResolve',
   'experimental.serverComponentsExternalPackages',
   'experimental.mdxRs',
+  'experimental.extractStyledJsxCss',
   'experimental.turbo',
   'experimental.useDeploymentId',
   'experimental.useDeploymentIdServerActions',
   'experimental.deploymentId',
   'experimental.serverActions',
+  'experimental.bundleBudgets',
+  'experimental.outputFileTracingRoot',
+  'experimental.outputFileTracingIncludes',
+  'experimental.outputFileTracingIgnores',
 
   // Experimental options that don't affect compilation
   'serverRuntimeConfig',
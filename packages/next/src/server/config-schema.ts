@@ -230,6 +230,13 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
         adjustFontFallbacks: z.boolean().optional(),
         adjustFontFallbacksWithSizeAdjust: z.boolean().optional(),
         allowedRevalidateHeaderKeys: z.array(z.string()).optional(),
+        bundleBudgets: z
+          .strictObject({
+            maxFirstLoadJsSize: z.number().int().gte(0).optional(),
+            maxChunkSize: z.number().int().gte(0).optional(),
+            maxTotalCssSize: z.number().int().gte(0).optional(),
+          })
+          .optional(),
         amp: z
           .object({
             // AMP optimizer option is unknown, use z.any() here
@@ -312,6 +319,7 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
           )
           .optional(),
         mdxRs: z.boolean().optional(),
+        extractStyledJsxCss: z.boolean().optional(),
         typedRoutes: z.boolean().optional(),
         webpackBuildWorker: z.boolean().optional(),
         turbo: z
@@ -236,6 +236,19 @@ export interface ExperimentalConfig {
   adjustFontFallbacks?: boolean
   adjustFontFallbacksWithSizeAdjust?: boolean
 
+  /**
+   * Maximum gzipped sizes in bytes of the client bundles. Turbopack builds
+   * exceeding a budget fail.
+   */
+  bundleBudgets?: {
+    /** The JavaScript a route loads when it is first visited. */
+    maxFirstLoadJsSize?: number
+    /** Each client JavaScript chunk. */
+    maxChunkSize?: number
+    /** All client CSS chunks together. */
+    maxTotalCssSize?: number
+  }
+
   /**
    * A list of packages that should be treated as external in the RSC server build.
    * @see https://nextjs.org/docs/app/api-reference/next-config-js/serverComponentsExternalPackages
@@ -279,6 +292,12 @@ export interface ExperimentalConfig {
    */
   mdxRs?: boolean
 
+  /**
+   * Moves static styled-jsx styles into CSS files instead of injecting them at
+   * runtime. Only supported by Turbopack.
+   */
+  extractStyledJsxCss?: boolean
+
   /**
    * Generate Route types and enable type checking for Link and Router.push, etc.
    * This option requires `appDir` to be enabled first.
@@ -0,0 +1,11 @@
+import localFont from 'next/font/local'
+
+const font = localFont({ src: './font.woff2' })
+
+export default function Layout({ children }) {
+  return (
+    <html>
+      <body className={font.className}>{children}</body>
+    </html>
+  )
+}
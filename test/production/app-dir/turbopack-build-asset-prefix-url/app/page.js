@@ -0,0 +1,5 @@
+import styles from './page.module.css'
+
+export default function Page() {
+  return <p className={styles.background}>hello world</p>
+}
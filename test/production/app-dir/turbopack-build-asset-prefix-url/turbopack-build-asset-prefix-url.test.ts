@@ -0,0 +1,78 @@
+import { createNextDescribe } from 'e2e-utils'
+
+const ASSET_PREFIX = 'https://cdn.example.com/app/_next/'
+
+createNextDescribe(
+  'app dir - turbopack build - asset prefix url',
+  {
+    files: __dirname,
+    buildCommand: 'pnpm next build --experimental-turbo',
+  },
+  ({ next }) => {
+    it('should load chunks from the asset prefix', async () => {
+      const $ = await next.render$('/')
+      const scripts = $('script[src]')
+        .map((_, script) => $(script).attr('src'))
+        .get()
+      expect(scripts).not.toHaveLength(0)
+      for (const src of scripts) {
+        expect(src).toStartWith(ASSET_PREFIX)
+      }
+
+      const stylesheets = $('link[rel="stylesheet"]')
+        .map((_, link) => $(link).attr('href'))
+        .get()
+      expect(stylesheets).not.toHaveLength(0)
+      for (const href of stylesheets) {
+        expect(href).toStartWith(ASSET_PREFIX)
+      }
+    })
+
+    it('should rewrite CSS urls to the asset prefix', async () => {
+      const $ = await next.render$('/')
+      const cssFiles = $('link[rel="stylesheet"]')
+        .map((_, link) => $(link).attr('href').slice(ASSET_PREFIX.length))
+        .get()
+      const css = (
+        await Promise.all(
+          cssFiles.map((file) => next.readFile(`.next/${file}`))
+        )
+      ).join('\n')
+      expect(css).toMatch(
+        /url\("?https:\/\/cdn\.example\.com\/app\/_next\/static\/media\/background\.[^.]+\.png"?\)/
+      )
+    })
+
+    it('should preload next/font files from the asset prefix', async () => {
+      const $ = await next.render$('/')
+      const preloads = $('link[rel="preload"][as="font"]')
+        .map((_, link) => $(link).attr('href'))
+        .get()
+      expect(preloads).toHaveLength(1)
+      expect(preloads[0]).toStartWith(`${ASSET_PREFIX}static/media/`)
+    })
+
+    it('should keep manifest paths relative and prefix module loading', async () => {
+      const buildManifest = JSON.parse(
+        await next.readFile('.next/build-manifest.json')
+      )
+      for (const file of buildManifest.rootMainFiles) {
+        expect(file).toStartWith('static/')
+      }
+
+      const appBuildManifest = JSON.parse(
+        await next.readFile('.next/app-build-manifest.json')
+      )
+      for (const file of appBuildManifest.pages['/page']) {
+        expect(file).toStartWith('static/')
+      }
+
+      const source = await next.readFile(
+        '.next/server/app/page_client-reference-manifest.js'
+      )
+      // globalThis.__RSC_MANIFEST["/page"] = {...}
+      const manifest = JSON.parse(source.slice(source.indexOf('] = ') + 4))
+      expect(manifest.moduleLoading.prefix).toBe(ASSET_PREFIX)
+    })
+  }
+)
@@ -0,0 +1,12 @@
+import Counter from './counter'
+
+export const runtime = 'edge'
+
+export default function Page() {
+  return (
+    <>
+      <p id="runtime">{typeof EdgeRuntime}</p>
+      <Counter />
+    </>
+  )
+}
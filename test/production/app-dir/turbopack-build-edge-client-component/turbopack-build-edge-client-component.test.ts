@@ -0,0 +1,41 @@
+import { createNextDescribe } from 'e2e-utils'
+import { check } from 'next-test-utils'
+
+createNextDescribe(
+  'app dir - turbopack build - edge client component',
+  {
+    files: __dirname,
+    buildCommand: 'pnpm next build --experimental-turbo',
+  },
+  ({ next }) => {
+    it('should server render the client component in the edge runtime', async () => {
+      const $ = await next.render$('/')
+      expect($('#runtime').text()).toBe('string')
+      expect($('#counter').text()).toBe('count: 0')
+    })
+
+    it('should hydrate the client component', async () => {
+      const browser = await next.browser('/')
+      await browser.elementByCss('#counter').click()
+      await check(() => browser.elementByCss('#counter').text(), 'count: 1')
+    })
+
+    it('should list the client reference manifest in the edge function', async () => {
+      const middlewareManifest = JSON.parse(
+        await next.readFile('.next/server/middleware-manifest.json')
+      )
+      const { files } = middlewareManifest.functions['/page']
+      expect(files[0]).toBe('server/app/page_client-reference-manifest.js')
+    })
+
+    it('should fill the edge SSR module mapping', async () => {
+      const source = await next.readFile(
+        '.next/server/app/page_client-reference-manifest.js'
+      )
+      // globalThis.__RSC_MANIFEST["/page"] = {...}
+      const manifest = JSON.parse(source.slice(source.indexOf('] = ') + 4))
+      expect(Object.keys(manifest.edgeSSRModuleMapping)).not.toHaveLength(0)
+      expect(manifest.ssrModuleMapping).toEqual({})
+    })
+  }
+)
@@ -0,0 +1,11 @@
+import { Open_Sans } from 'next/font/google'
+
+const openSans = Open_Sans({ subsets: ['latin'] })
+
+export default function Layout({ children }) {
+  return (
+    <html>
+      <body className={openSans.className}>{children}</body>
+    </html>
+  )
+}
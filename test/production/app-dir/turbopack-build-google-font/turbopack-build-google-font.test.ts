@@ -0,0 +1,52 @@
+import { createNextDescribe } from 'e2e-utils'
+
+const mockedGoogleFontResponses = require.resolve(
+  '../../../e2e/next-font/google-font-mocked-responses.js'
+)
+
+createNextDescribe(
+  'app dir - turbopack build - google font',
+  {
+    files: __dirname,
+    buildCommand: 'pnpm next build --experimental-turbo',
+    env: {
+      NEXT_FONT_GOOGLE_MOCKED_RESPONSES: mockedGoogleFontResponses,
+    },
+  },
+  ({ next }) => {
+    it('should list the preloaded font file in the font manifest', async () => {
+      const manifest = JSON.parse(
+        await next.readFile('.next/server/next-font-manifest.json')
+      )
+      const entries = Object.entries(manifest.app).filter(([page]) =>
+        page.replace(/\\/g, '/').endsWith('/app/page')
+      )
+      expect(entries).toHaveLength(1)
+
+      const [, fontFiles] = entries[0] as [string, string[]]
+      // Only the latin subset is preloaded.
+      expect(fontFiles).toHaveLength(1)
+      expect(fontFiles[0]).toMatch(/^static\/media\/[^/]+\.p\.[^/]*woff2$/)
+    })
+
+    it('should self-host the font files', async () => {
+      const $ = await next.render$('/')
+      const preloads = $('link[rel="preload"][as="font"]')
+        .map((_, link) => $(link).attr('href'))
+        .get()
+      expect(preloads).toHaveLength(1)
+      expect(preloads[0]).toStartWith('/_next/static/media/')
+
+      const css = (
+        await Promise.all(
+          $('link[rel="stylesheet"]')
+            .map((_, link) => $(link).attr('href').replace('/_next/', ''))
+            .get()
+            .map((file) => next.readFile(`.next/${file}`))
+        )
+      ).join('\n')
+      expect(css).toContain('/_next/static/media/')
+      expect(css).not.toContain('fonts.gstatic.com')
+    })
+  }
+)